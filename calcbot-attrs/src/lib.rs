@@ -3,14 +3,14 @@ mod util;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use structures::CommandInfo;
+use structures::{ArgEnum, CommandInfo};
 use syn::{parse_macro_input, ItemStruct};
 
 /// Derives the [`Info`] trait for the given struct.
 ///
 /// The information of the command can be customized using the `info` attribute by adding
 /// the corresponding tags to it:
-/// ```
+/// ```ignore
 /// #[info(aliases = ["foo", "bar"], syntax = "baz")]
 /// pub struct Foo;
 /// ```
@@ -26,6 +26,7 @@ use syn::{parse_macro_input, ItemStruct};
 /// | `syntax`      | The syntax of the command.        | `[&str]`                                    | The `syntax` tag in the `info` attribute.                            |
 /// | `examples`    | Example usage of the command.     | `[&str]`                                    | The `examples` tag in the `info` attribute.                          |
 /// | `children`    | The subcommands of the command.   | `[impl Command]`                            | The `children` tag in the `info` attribute.                          |
+/// | `deprecated`  | What to use instead, if any.      | `&str`                                      | The `deprecated` tag in the `info` attribute.                        |
 ///
 /// There are also some special tags that provide additional functionality:
 ///
@@ -41,13 +42,19 @@ use syn::{parse_macro_input, ItemStruct};
 ///
 /// Optional arguments can be indicated by wrapping the type in an [`Option`].
 ///
+/// If an argument cannot be parsed as its type, `parse_args` returns an `InvalidArgument` error
+/// describing the values the argument accepts, such as the range of an integer type.
+///
+/// The special `Quoted` type parses a single word, or several words wrapped in double quotes, like
+/// `"stop watching tv"`, into a [`String`]. Quotes inside the argument can be escaped as `\"`.
+///
 /// If the generated parser is not sufficient, you can add the special `Unlimited` type to the end of
 /// the array to indicate that the rest of the arguments should be treated as a single string. This
 /// type should be placed at the end of the array.
 ///
 /// ### Example
 ///
-/// ```
+/// ```ignore
 /// #[info(args = [Option<u64>, u64, Unlimited], syntax = "[number] <number> <string>")]
 /// pub struct Foo;
 ///
@@ -78,9 +85,10 @@ pub fn info(item: TokenStream) -> TokenStream {
     let description = description.trim();
     let category = util::wrap(info_args.category);
     let aliases = util::wrap(info_args.aliases);
-    let syntax = util::wrap(info_args.syntax);
+    let syntax = util::wrap(info_args.syntax.as_ref());
     let examples = util::wrap(info_args.examples);
     let children = info_args.children;
+    let deprecated = util::wrap(info_args.deprecated);
 
     let mut result = quote! {
         impl crate::commands::Info for #name {
@@ -93,13 +101,14 @@ pub fn info(item: TokenStream) -> TokenStream {
                     syntax: #syntax,
                     examples: #examples,
                     children: #children,
+                    deprecated: #deprecated,
                 }
             }
         }
     };
 
     if let Some(args) = info_args.args {
-        result.extend(args.generate_parse_args());
+        result.extend(args.generate_parse_args(info_args.syntax.as_ref()));
     }
 
     quote! { #result }.into()
@@ -130,3 +139,50 @@ pub fn command(item: TokenStream) -> TokenStream {
         }
     }.into()
 }
+
+/// Derives [`std::str::FromStr`] for an enum with unit variants, so that it can be used as a
+/// command argument in the `args` tag of the `info` attribute.
+///
+/// Each variant is parsed from the aliases given in its `arg` attribute, ignoring case. A variant
+/// without aliases is parsed from its name in snake_case. If the input matches no alias, the error
+/// is an `InvalidOption` listing the first alias of each variant. When the enum is used in the
+/// `args` tag, these options are also listed in the `InvalidArgument` error.
+///
+/// ### Example
+///
+/// ```ignore
+/// #[derive(ParseArg)]
+/// pub enum AngleMode {
+///     #[arg(aliases = ["radians", "radian", "rad", "r"])]
+///     Radians,
+///
+///     #[arg(aliases = ["degrees", "degree", "deg", "d"])]
+///     Degrees,
+/// }
+/// ```
+#[proc_macro_derive(ParseArg, attributes(arg))]
+pub fn parse_arg(item: TokenStream) -> TokenStream {
+    let ArgEnum { name, variants } = parse_macro_input!(item as ArgEnum);
+    let arms = variants.iter().map(|variant| {
+        let variant_name = &variant.name;
+        let aliases = &variant.aliases;
+        quote! { #(#aliases)|* => Ok(Self::#variant_name), }
+    });
+    let options = variants.iter().map(|variant| &variant.aliases[0]);
+
+    quote! {
+        impl std::str::FromStr for #name {
+            type Err = crate::error::InvalidOption;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.to_lowercase().as_str() {
+                    #(#arms)*
+                    _ => Err(crate::error::InvalidOption {
+                        value: s.to_owned(),
+                        options: &[#(#options),*],
+                    }),
+                }
+            }
+        }
+    }.into()
+}
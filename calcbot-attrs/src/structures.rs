@@ -1,12 +1,15 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
+use super::util;
 use syn::{
     parse::{Parse, ParseStream},
     spanned::Spanned,
     AngleBracketedGenericArguments,
     Attribute,
     Expr,
+    Fields,
     Ident,
+    ItemEnum,
     ItemStruct,
     Lit,
     LitStr,
@@ -90,9 +93,84 @@ impl ToTokens for CommandGroup {
 pub struct Args(pub Vec<Type>);
 
 impl Args {
+    /// Describes the values accepted by the given type, shown when an argument cannot be parsed.
+    /// Integer types describe their range. Returns `None` for other types, which are described by
+    /// their parse error instead.
+    fn describe_type(ty: &TokenStream2) -> Option<TokenStream2> {
+        let name = ty.to_string();
+        match name.as_str() {
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+                | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => Some(quote! {
+                format!("an integer from {} to {}", <#ty>::MIN, <#ty>::MAX)
+            }),
+            "f32" | "f64" => Some(quote! { String::from("a number") }),
+            "Number" => Some(quote! { String::from("a number, percentage, or fraction") }),
+            _ => None,
+        }
+    }
+
+    /// Creates the expression that parses the string `s` as the given type, resulting in an
+    /// `InvalidArgument` error if it cannot be parsed.
+    ///
+    /// Enums deriving `ParseArg` fail with an `InvalidOption` error, whose options are listed as
+    /// the expected values.
+    fn parse_value(index: usize, ty: &TokenStream2) -> TokenStream2 {
+        let expected = match Self::describe_type(ty) {
+            Some(expected) => quote! { |_| #expected },
+            None => {
+                let name = ty.to_string();
+                quote! {
+                    |err| (&err as &dyn std::any::Any)
+                        .downcast_ref::<crate::error::InvalidOption>()
+                        .map(crate::error::InvalidOption::expected)
+                        .unwrap_or_else(|| format!("a valid `{}`", #name))
+                }
+            },
+        };
+        quote! {
+            <#ty as std::str::FromStr>::from_str(s)
+                .map_err(#expected)
+                .map_err(|expected| crate::error::InvalidArgument {
+                    index: #index,
+                    value: s.to_owned(),
+                    expected,
+                })
+        }
+    }
+
+    /// Creates the `MissingArgument` error for the argument at the given index.
+    ///
+    /// If the command has exactly one syntax, the argument is named by its placeholder in that
+    /// syntax. Every syntax of the command is included as a hint.
+    fn missing_argument(index: usize, syntax: Option<&SliceLitStr>) -> TokenStream2 {
+        let syntaxes = syntax.map(|syntax| syntax.0.elements.iter().map(LitStr::value).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let name = match syntaxes.as_slice() {
+            [syntax] => util::syntax_placeholders(syntax).get(index).cloned(),
+            _ => None,
+        };
+        let syntax_hint = match syntaxes.is_empty() {
+            true => None,
+            false => Some(syntaxes.iter()
+                .map(|syntax| format!("`{}`", syntax))
+                .collect::<Vec<_>>()
+                .join(" or ")),
+        };
+
+        let name = util::wrap(name);
+        let syntax_hint = util::wrap(syntax_hint);
+        quote! {
+            crate::error::MissingArgument {
+                index: #index,
+                name: #name,
+                syntax_hint: #syntax_hint,
+            }
+        }
+    }
+
     /// Create the specialized parser for the special given type. Returns `None` if the type is not
     /// special.
-    fn parse_special_type(&self, ty: &Type) -> Result<Option<TokenStream2>> {
+    fn parse_special_type(&self, index: usize, ty: &Type, missing: &TokenStream2) -> Result<Option<TokenStream2>> {
         Ok(match ty {
             Type::Path(path) => {
                 let path = &path.path;
@@ -103,8 +181,16 @@ impl Args {
                     let PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &last.arguments else {
                         return Err(syn::Error::new(last.arguments.span(), "expected angle bracketed generic arguments"));
                     };
+                    let parser = Self::parse_value(index, &args.to_token_stream());
                     Some(quote! {
-                        args.next().map(|s| <#args as std::str::FromStr>::from_str(s).unwrap_or_default())
+                        args.next().map(|s| #parser).transpose()?
+                    })
+                } else if ident == "Quoted" { // a word, or several words wrapped in quotes
+                    Some(quote! {
+                        {
+                            let s = args.next().ok_or(#missing)?;
+                            crate::util::parse_quoted(s, &mut args)?
+                        }
                     })
                 } else if ident == "Unlimited" { // make the remaining arguments a string
                     // TODO: handle the case when "Unlimited" is not the final argument
@@ -132,7 +218,7 @@ impl Args {
     }
 
     /// Generates the `parse_args` function that parses the arguments of the command.
-    pub fn generate_parse_args(&self) -> Result<TokenStream2> {
+    pub fn generate_parse_args(&self, syntax: Option<&SliceLitStr>) -> Result<TokenStream2> {
         let mut args = Vec::new();
         let mut arg_names = Vec::new();
         let mut arg_types = Vec::new();
@@ -142,14 +228,15 @@ impl Args {
             let arg_name = Ident::new(&format!("arg{}", i), arg.span());
             let arg_type = arg;
 
-            let arg_parser = if let Some(parser) = self.parse_special_type(arg)? {
+            let missing = Self::missing_argument(i, syntax);
+            let arg_parser = if let Some(parser) = self.parse_special_type(i, arg, &missing)? {
                 parser
             } else {
+                let parser = Self::parse_value(i, &arg_type.to_token_stream());
                 quote! {
                     {
-                        let s = args.next().ok_or(crate::error::MissingArgument { index: #i })?;
-                        let arg = <#arg_type as std::str::FromStr>::from_str(s).unwrap_or_default();
-                        arg
+                        let s = args.next().ok_or(#missing)?;
+                        #parser?
                     }
                 }
             };
@@ -163,7 +250,7 @@ impl Args {
                 let last = path.segments.last().unwrap();
                 let ident = &last.ident;
 
-                if ident == "Unlimited" {
+                if ident == "Quoted" || ident == "Unlimited" {
                     arg_types.push(quote! { String });
                 } else {
                     arg_types.push(quote! { #arg_type });
@@ -211,6 +298,7 @@ pub struct InfoArgs {
     pub examples: Option<SliceLitStr>,
     pub children: CommandGroup,
     pub args: Option<Args>,
+    pub deprecated: Option<LitStr>,
 }
 
 impl InfoArgs {
@@ -227,6 +315,7 @@ impl InfoArgs {
             "examples" => self.examples = Some(input.parse()?),
             "children" => self.children = input.parse()?,
             "args" => self.args = Some(input.parse()?),
+            "deprecated" => self.deprecated = Some(input.parse()?),
             _ => return Err(syn::Error::new_spanned(ident, format!("unknown tag `{}`", ident_str))),
         }
 
@@ -301,3 +390,77 @@ impl Parse for CommandInfo {
         })
     }
 }
+
+/// The arguments that can be passed to the `arg` attribute of an enum variant.
+#[derive(Debug, Default)]
+pub struct ArgArgs {
+    pub aliases: Option<SliceLitStr>,
+}
+
+impl Parse for ArgArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut args = ArgArgs::default();
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            let ident_str = ident.to_string();
+            match ident_str.as_str() {
+                "aliases" => args.aliases = Some(input.parse()?),
+                _ => return Err(syn::Error::new_spanned(ident, format!("unknown tag `{}`", ident_str))),
+            }
+
+            if input.parse::<Token![,]>().is_err() {
+                break;
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// A variant of an enum deriving `ParseArg`, with the lowercase aliases it can be parsed from.
+#[derive(Debug)]
+pub struct ArgVariant {
+    pub name: Ident,
+    pub aliases: Vec<String>,
+}
+
+/// An enum deriving `ParseArg`.
+#[derive(Debug)]
+pub struct ArgEnum {
+    pub name: Ident,
+    pub variants: Vec<ArgVariant>,
+}
+
+impl Parse for ArgEnum {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let item = input.parse::<ItemEnum>()?;
+
+        let mut variants = Vec::new();
+        for variant in item.variants {
+            if !matches!(variant.fields, Fields::Unit) {
+                return Err(syn::Error::new_spanned(variant, "`ParseArg` can only be derived for unit variants"));
+            }
+
+            let mut aliases = Vec::new();
+            for attr in &variant.attrs {
+                if attr.path().is_ident("arg") {
+                    if let Some(slice) = attr.parse_args::<ArgArgs>()?.aliases {
+                        aliases.extend(slice.0.elements.iter().map(|alias| alias.value().to_lowercase()));
+                    }
+                }
+            }
+
+            // by default, the variant is parsed from its name
+            if aliases.is_empty() {
+                aliases.push(util::pascal_to_snake_case(&variant.ident.to_string()));
+            }
+
+            variants.push(ArgVariant { name: variant.ident, aliases });
+        }
+
+        Ok(ArgEnum { name: item.ident, variants })
+    }
+}
@@ -13,6 +13,28 @@ pub fn pascal_to_snake_case(name: &str) -> String {
     snake
 }
 
+/// Returns the argument placeholders in a syntax string, like `<time unit>` and `[message]` in
+/// `<quantity> <time unit> [message]`.
+pub fn syntax_placeholders(syntax: &str) -> Vec<String> {
+    let mut placeholders = Vec::new();
+    let mut current: Option<(char, String)> = None;
+    for ch in syntax.chars() {
+        match (&mut current, ch) {
+            (None, '<') => current = Some(('>', ch.to_string())),
+            (None, '[') => current = Some((']', ch.to_string())),
+            (Some((close, placeholder)), _) => {
+                placeholder.push(ch);
+                if ch == *close {
+                    placeholders.push(placeholder.clone());
+                    current = None;
+                }
+            },
+            (None, _) => (),
+        }
+    }
+    placeholders
+}
+
 /// Wrap an [`Option<Token>`] in a token that actually wraps the token in an [`Option`].
 ///
 /// This is different from the default implementation of [`ToTokens`] for [`Option`];
@@ -23,3 +45,33 @@ pub fn wrap<T: ToTokens>(option: Option<T>) -> TokenStream2 {
         None => quote! { None },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_and_optional_placeholders() {
+        assert_eq!(
+            syntax_placeholders("<quantity> <time unit> [message]"),
+            ["<quantity>", "<time unit>", "[message]"],
+        );
+    }
+
+    #[test]
+    fn literal_words_are_skipped() {
+        assert_eq!(syntax_placeholders("message <id> <new message>"), ["<id>", "<new message>"]);
+        assert!(syntax_placeholders("units").is_empty());
+    }
+
+    #[test]
+    fn unclosed_placeholders_are_dropped() {
+        assert_eq!(syntax_placeholders("<id> <time unit"), ["<id>"]);
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(pascal_to_snake_case("UnitConvert"), "unit_convert");
+        assert_eq!(pascal_to_snake_case("Remind"), "remind");
+    }
+}
@@ -0,0 +1,151 @@
+mod support;
+
+use calcbot_attrs::{Info, ParseArg};
+use support::error::{downcast, Error, InvalidArgument};
+pub use support::{commands, error};
+
+mod integers {
+    use super::*;
+
+    /// Takes a signed and an unsigned integer.
+    #[derive(Info)]
+    #[info(syntax = ["<signed> <unsigned>"], args = [i8, u8])]
+    pub struct Integers;
+
+    #[test]
+    fn signs_are_accepted() {
+        assert_eq!(parse_args(vec!["-5", "+10"]).unwrap(), (-5, 10));
+    }
+
+    #[test]
+    fn invalid_integers_show_the_range() {
+        for value in ["abc", "-"] {
+            let err = downcast::<InvalidArgument>(parse_args(vec!["0", value]).unwrap_err());
+            assert_eq!(err, InvalidArgument {
+                index: 1,
+                value: value.to_string(),
+                expected: "an integer from 0 to 255".to_string(),
+            });
+        }
+    }
+
+    #[test]
+    fn out_of_range_integers_are_rejected() {
+        let err = downcast::<InvalidArgument>(parse_args(vec!["128", "0"]).unwrap_err());
+        assert_eq!(err.index, 0);
+        assert_eq!(err.expected, "an integer from -128 to 127");
+    }
+
+    #[test]
+    fn info_is_generated() {
+        use support::commands::Info as _;
+
+        let info = Integers.info();
+        assert_eq!(info.name, "integers");
+        assert_eq!(info.description, "Takes a signed and an unsigned integer.");
+        assert_eq!(info.syntax, Some(&["<signed> <unsigned>"][..]));
+        assert_eq!((info.category, info.aliases, info.examples, info.deprecated), (None, None, None, None));
+        assert!(info.children.0.is_empty());
+    }
+}
+
+mod options {
+    use super::*;
+
+    #[derive(Debug, PartialEq, ParseArg)]
+    pub enum Mode {
+        #[arg(aliases = ["radians", "rad"])]
+        Radians,
+        Degrees,
+    }
+
+    /// Takes a mode and a number.
+    #[derive(Info)]
+    #[info(syntax = ["<mode> <number>"], args = [Mode, f64])]
+    pub struct Options;
+
+    #[test]
+    fn name_is_snake_case() {
+        use support::commands::Info as _;
+
+        assert_eq!(Options.info().name, "options");
+    }
+
+    #[test]
+    fn options_ignore_case() {
+        assert_eq!(parse_args(vec!["RAD", "1.5"]).unwrap(), (Mode::Radians, 1.5));
+        assert_eq!(parse_args(vec!["degrees", "-2"]).unwrap(), (Mode::Degrees, -2.0));
+    }
+
+    #[test]
+    fn invalid_options_are_listed() {
+        let err = downcast::<InvalidArgument>(parse_args(vec!["gradians", "1"]).unwrap_err());
+        assert_eq!(err.expected, "one of: `radians`, `degrees`");
+    }
+
+    #[test]
+    fn invalid_numbers() {
+        let err = downcast::<InvalidArgument>(parse_args(vec!["rad", "abc"]).unwrap_err());
+        assert_eq!((err.index, err.expected.as_str()), (1, "a number"));
+    }
+}
+
+mod placeholders {
+    use super::*;
+    use support::error::MissingArgument;
+
+    /// Takes a quantity of time.
+    #[derive(Info)]
+    #[info(syntax = ["<quantity> <time unit>"], args = [f64, u8])]
+    pub struct Placeholders;
+
+    #[test]
+    fn missing_second_argument_is_named() {
+        let err = downcast::<MissingArgument>(parse_args(vec!["10"]).unwrap_err());
+        assert_eq!(err, MissingArgument {
+            index: 1,
+            name: Some("<time unit>"),
+            syntax_hint: Some("`<quantity> <time unit>`"),
+        });
+    }
+
+    #[test]
+    fn invalid_second_argument_is_reported() {
+        let err = downcast::<InvalidArgument>(parse_args(vec!["10", "minutes"]).unwrap_err());
+        assert_eq!((err.index, err.value.as_str()), (1, "minutes"));
+    }
+
+    #[test]
+    fn name_is_snake_case() {
+        use support::commands::Info as _;
+
+        assert_eq!(Placeholders.info().name, "placeholders");
+    }
+}
+
+mod several_syntaxes {
+    use super::*;
+    use support::error::MissingArgument;
+
+    /// Takes one or two numbers.
+    #[derive(Info)]
+    #[info(syntax = ["<a> <b>", "<a>"], args = [f64, f64])]
+    pub struct SeveralSyntaxes;
+
+    #[test]
+    fn argument_is_not_named() {
+        let err = downcast::<MissingArgument>(parse_args(vec!["1"]).unwrap_err());
+        assert_eq!(err, MissingArgument {
+            index: 1,
+            name: None,
+            syntax_hint: Some("`<a> <b>` or `<a>`"),
+        });
+    }
+
+    #[test]
+    fn name_is_snake_case() {
+        use support::commands::Info as _;
+
+        assert_eq!(SeveralSyntaxes.info().name, "several_syntaxes");
+    }
+}
@@ -0,0 +1,100 @@
+//! Stand-ins for the items of the `calcbot` crate that the generated code refers to, so that the
+//! derives can be expanded and run in these tests.
+
+pub mod commands {
+    pub trait Info {
+        fn info(&self) -> CommandInfo;
+    }
+
+    pub struct CommandInfo {
+        pub name: &'static str,
+        pub description: &'static str,
+        pub category: Option<&'static str>,
+        pub aliases: Option<&'static [&'static str]>,
+        pub syntax: Option<&'static [&'static str]>,
+        pub examples: Option<&'static [&'static str]>,
+        pub children: CommandGroup,
+        pub deprecated: Option<&'static str>,
+    }
+
+    pub struct CommandGroup(pub Vec<Box<dyn Info>>);
+
+    impl CommandGroup {
+        pub fn new(children: Vec<Box<dyn Info>>) -> Self {
+            Self(children)
+        }
+    }
+}
+
+pub mod error {
+    use std::{any::Any, fmt::Debug};
+
+    pub trait Error: Debug {
+        fn as_any(&self) -> &dyn Any;
+    }
+
+    impl<T> From<T> for Box<dyn Error + Send + Sync>
+    where
+        T: Error + Send + Sync + 'static,
+    {
+        fn from(err: T) -> Self {
+            Box::new(err)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct MissingArgument {
+        pub index: usize,
+        pub name: Option<&'static str>,
+        pub syntax_hint: Option<&'static str>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct InvalidArgument {
+        pub index: usize,
+        pub value: String,
+        pub expected: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct InvalidOption {
+        pub value: String,
+        pub options: &'static [&'static str],
+    }
+
+    impl InvalidOption {
+        pub fn expected(&self) -> String {
+            let options = self.options.iter()
+                .map(|option| format!("`{}`", option))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("one of: {}", options)
+        }
+    }
+
+    impl Error for MissingArgument {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl Error for InvalidArgument {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl Error for InvalidOption {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    /// Returns the concrete error inside the boxed error, panicking if it has another type.
+    pub fn downcast<T: Clone + 'static>(err: Box<dyn Error + Send + Sync>) -> T {
+        err.as_any()
+            .downcast_ref::<T>()
+            .unwrap_or_else(|| panic!("unexpected error: {:?}", err))
+            .clone()
+    }
+}
@@ -0,0 +1,277 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context, Trigger, TriggerEvent},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::{fmt::{self, Display, Formatter}, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use twilight_model::guild::Permissions;
+use twilight_util::builder::embed::EmbedBuilder;
+
+/// The path of this command. The user is always on cooldown for it while it runs, so it is left
+/// out of the cooldown check.
+const PATH: &str = "about diagnose";
+
+/// A single check performed by the diagnostic.
+struct Check {
+    /// Whether the check passed.
+    passed: bool,
+
+    /// The name of the check.
+    name: &'static str,
+
+    /// A one-line explanation of the result.
+    explanation: String,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} **{}**: {}",
+            if self.passed { "✅" } else { "❌" },
+            self.name,
+            self.explanation,
+        )
+    }
+}
+
+/// Renders the given checks as one line each.
+fn render(checks: &[Check]) -> String {
+    checks.iter()
+        .map(|check| check.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the permissions CalcBot has in the channel the command was run in, or [`None`] if they
+/// could not be determined. CalcBot can always send messages and embeds in DMs.
+fn bot_permissions(state: &State, trigger: Trigger<'_>) -> Option<Permissions> {
+    if trigger.guild_id().is_none() {
+        return Some(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS);
+    }
+
+    match trigger.event {
+        TriggerEvent::Message(msg) => {
+            let bot_id = state.cache.current_user()?.id;
+            state.cache.permissions()
+                .in_channel(bot_id, msg.channel_id)
+                .map_err(|err| log::warn!("could not compute permissions in channel {}: {}", msg.channel_id, err))
+                .ok()
+        },
+        TriggerEvent::Interaction(interaction) => interaction.app_permissions,
+    }
+}
+
+/// Checks whether CalcBot can send messages and embeds, given its permissions in the channel.
+fn permission_checks(permissions: Option<Permissions>) -> [Check; 2] {
+    let check = |permission, name, allowed: &str, denied: &str| match permissions {
+        Some(permissions) => Check {
+            passed: permissions.contains(permission),
+            name,
+            explanation: match permissions.contains(permission) {
+                true => allowed.to_owned(),
+                false => denied.to_owned(),
+            },
+        },
+        None => Check {
+            passed: true,
+            name,
+            explanation: "CalcBot could not look up its permissions in this channel.".to_owned(),
+        },
+    };
+
+    [
+        check(
+            Permissions::SEND_MESSAGES,
+            "Sending messages",
+            "CalcBot can send messages in this channel.",
+            "CalcBot cannot send messages here, so it can only respond to slash commands. Ask a moderator to give CalcBot the **Send Messages** permission.",
+        ),
+        check(
+            Permissions::EMBED_LINKS,
+            "Embeds",
+            "CalcBot can send embeds in this channel.",
+            "CalcBot cannot send embeds here, so many commands will fail. Ask a moderator to give CalcBot the **Embed Links** permission.",
+        ),
+    ]
+}
+
+/// Checks whether any commands are disabled, given their paths.
+fn disabled_check(disabled: &[String]) -> Check {
+    Check {
+        passed: disabled.is_empty(),
+        name: "Disabled commands",
+        explanation: match disabled {
+            [] => "No commands are disabled.".to_owned(),
+            _ => format!("These commands are temporarily disabled for maintenance: `{}`", disabled.join("`, `")),
+        },
+    }
+}
+
+/// Checks whether the user is on cooldown for any commands, given the paths of the commands and
+/// the time left on each cooldown.
+fn cooldown_check(cooldowns: &[(String, Duration)]) -> Check {
+    Check {
+        passed: cooldowns.is_empty(),
+        name: "Cooldowns",
+        explanation: match cooldowns {
+            [] => "You can run any command right now.".to_owned(),
+            _ => format!(
+                "You must wait before running these commands again: {}",
+                cooldowns.iter()
+                    .map(|(path, remaining)| format!("`{}` ({:.1}s)", path, remaining.as_secs_f64()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        },
+    }
+}
+
+/// Describes whether inline math is evaluated in the server. This is a setting rather than a
+/// problem, so the check always passes.
+fn inline_math_check(inline_math: bool) -> Check {
+    Check {
+        passed: true,
+        name: "Inline math",
+        explanation: match inline_math {
+            true => "`{{expression}}` spans in messages are evaluated in this server.".to_owned(),
+            false => "`{{expression}}` spans in messages are not evaluated in this server.".to_owned(),
+        },
+    }
+}
+
+/// Find out why a command might not be working for you in this channel. CalcBot will check
+/// whether it can respond here, whether any commands are disabled or on cooldown for you, and show
+/// the settings that affect how it responds to you.
+#[derive(Clone, Info)]
+#[info(aliases = ["diagnose", "diag", "why"])]
+pub struct Diagnose;
+
+#[async_trait]
+impl Command for Diagnose {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let permissions = bot_permissions(state, ctxt.trigger);
+        let mut checks = Vec::from(permission_checks(permissions));
+
+        match ctxt.prefix {
+            Some(prefix) => checks.push(Check {
+                passed: true,
+                name: "Prefix",
+                explanation: format!("This server's prefix is `{0}`. Commands must start with it, like `{0}help`.", prefix),
+            }),
+            None => checks.push(Check {
+                passed: true,
+                name: "Prefix",
+                explanation: "No prefix is needed in DMs; type the command directly, like `help`.".to_owned(),
+            }),
+        }
+
+        if let Some(guild_id) = ctxt.trigger.guild_id() {
+            let inline_math = database.lock().await
+                .get_server(guild_id).await
+                .inline_math;
+            checks.push(inline_math_check(inline_math));
+        }
+
+        checks.push(disabled_check(&state.disabled_commands()));
+        let cooldowns = state.active_cooldowns(ctxt.trigger.author_id())
+            .into_iter()
+            .filter(|(path, _)| path != PATH)
+            .collect::<Vec<_>>();
+        checks.push(cooldown_check(&cooldowns));
+
+        if permissions.is_some_and(|permissions| !permissions.contains(Permissions::EMBED_LINKS)) {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**Diagnostics**\n{}", render(&checks)))?
+                .await?;
+            return Ok(());
+        }
+
+        let embed = EmbedBuilder::new()
+            .title("Diagnostics")
+            .color(0x988bc2)
+            .description(render(&checks))
+            .build();
+        let sent = ctxt.trigger.reply(&state.http)
+            .embeds(&[embed])?
+            .await;
+        if sent.is_err() {
+            // the permissions could not be looked up, but embeds are not allowed
+            let [_, embeds] = permission_checks(Some(Permissions::SEND_MESSAGES));
+            checks[1] = embeds;
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**Diagnostics**\n{}", render(&checks)))?
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_permissions() {
+        let checks = permission_checks(Some(Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS));
+        assert_eq!(
+            render(&checks),
+            "✅ **Sending messages**: CalcBot can send messages in this channel.\n\
+            ✅ **Embeds**: CalcBot can send embeds in this channel.",
+        );
+    }
+
+    #[test]
+    fn missing_embed_links() {
+        let [send, embeds] = permission_checks(Some(Permissions::SEND_MESSAGES));
+        assert!(send.passed);
+        assert!(!embeds.passed);
+        assert!(embeds.explanation.contains("**Embed Links**"));
+    }
+
+    #[test]
+    fn unknown_permissions_pass() {
+        let checks = permission_checks(None);
+        assert!(checks.iter().all(|check| check.passed));
+    }
+
+    #[test]
+    fn disabled_commands() {
+        assert_eq!(disabled_check(&[]).to_string(), "✅ **Disabled commands**: No commands are disabled.");
+        assert_eq!(
+            disabled_check(&["calculate".to_owned(), "remind view".to_owned()]).to_string(),
+            "❌ **Disabled commands**: These commands are temporarily disabled for maintenance: `calculate`, `remind view`",
+        );
+    }
+
+    #[test]
+    fn cooldowns() {
+        assert!(cooldown_check(&[]).passed);
+        assert_eq!(
+            cooldown_check(&[("calculate".to_owned(), Duration::from_millis(1500))]).to_string(),
+            "❌ **Cooldowns**: You must wait before running these commands again: `calculate` (1.5s)",
+        );
+    }
+
+    #[test]
+    fn inline_math_is_informational() {
+        assert_eq!(
+            inline_math_check(true).to_string(),
+            "✅ **Inline math**: `{{expression}}` spans in messages are evaluated in this server.",
+        );
+        assert_eq!(
+            inline_math_check(false).to_string(),
+            "✅ **Inline math**: `{{expression}}` spans in messages are not evaluated in this server.",
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod diagnose;
+
 use async_trait::async_trait;
 use calcbot_attrs::Info;
 use crate::{
@@ -7,14 +9,14 @@ use crate::{
     global::State,
     util::format_duration,
 };
-use std::{env, num::NonZeroU64, sync::Arc};
+use std::sync::{atomic::Ordering, Arc};
 use sysinfo::{Pid, ProcessExt, System, SystemExt};
 use tokio::sync::Mutex;
 use twilight_util::builder::embed::EmbedBuilder;
 
 /// View information about CalcBot.
 #[derive(Clone, Info)]
-#[info(category = "Miscellaneous")]
+#[info(category = "Miscellaneous", children = [diagnose::Diagnose])]
 pub struct About;
 
 #[async_trait]
@@ -33,11 +35,22 @@ impl Command for About {
 
         // we fetch the author's tag from the api because just using the "<@author_id>" syntax will
         // not work if the author is not in the same server as the user who ran the command
-        let author_id = env::var("AUTHOR_ID")?.parse::<NonZeroU64>().unwrap();
-        let author = {
-            let user = state.http.user(author_id.into()).await?.model().await?;
-            format!("{}#{}", user.name, user.discriminator())
-        };
+        //
+        // if no owner is configured, or the lookup fails, a generic credit is shown instead
+        let author = match state.owners.first() {
+            Some(author_id) => match state.http.user(*author_id).await {
+                Ok(response) => response.model()
+                    .await
+                    .map(|user| format!("{}#{}", user.name, user.discriminator()))
+                    .map_err(|err| log::warn!("could not read the author of CalcBot: {}", err))
+                    .ok(),
+                Err(err) => {
+                    log::warn!("could not fetch the author of CalcBot: {}", err);
+                    None
+                },
+            },
+            None => None,
+        }.unwrap_or_else(|| "the CalcBot developers".to_owned());
 
         let bot_id = state.cache
             .current_user()
@@ -51,17 +64,25 @@ impl Command for About {
             .description(format!("
             <@{}> is being constantly developed by **{}**.
 
+            Version: {} (`{}`)
             Uptime: {}
+            Servers: {}
             Shard CPU usage: {}%
             Shard memory usage: {} MB
             Commands: {}
+            Messages: {} processed, {} skipped
             ",
                 bot_id,
                 author,
+                env!("CARGO_PKG_VERSION"),
+                env!("GIT_HASH"),
                 format_duration(state.start_time.elapsed()),
+                state.cache.stats().guilds(),
                 process.cpu_usage(),
                 process.memory() / 1024 / 1024,
                 state.commands.count(),
+                state.message_stats.processed.load(Ordering::Relaxed),
+                state.message_stats.filtered.load(Ordering::Relaxed),
             ))
             .build();
 
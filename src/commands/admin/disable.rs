@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::toggle;
+use tokio::sync::Mutex;
+
+/// Globally disable a command, such as one that is misbehaving in production. Users who run it
+/// are told that it is temporarily disabled, and it is hidden from `{prefix}help commands`. This
+/// lasts until the bot restarts.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["disable", "off"],
+    syntax = ["<command path>"],
+    examples = ["dictionary", "nm unscramble"],
+)]
+pub struct Disable;
+
+#[async_trait]
+impl Command for Disable {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        toggle(state, ctxt, true).await
+    }
+}
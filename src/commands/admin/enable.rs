@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::toggle;
+use tokio::sync::Mutex;
+
+/// Re-enable a command that was disabled with `{prefix}admin disable`, or through the
+/// `DISABLED_COMMANDS` environment variable.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["enable", "on"],
+    syntax = ["<command path>"],
+    examples = ["dictionary", "nm unscramble"],
+)]
+pub struct Enable;
+
+#[async_trait]
+impl Command for Enable {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        toggle(state, ctxt, false).await
+    }
+}
@@ -0,0 +1,63 @@
+pub mod disable;
+pub mod enable;
+pub mod stats;
+
+use calcbot_attrs::{Command, Info};
+use crate::{
+    commands::{Context, Info},
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+
+/// Operational commands for CalcBot's owners. These commands cannot be used by anyone else.
+#[derive(Clone, Command, Info)]
+#[info(
+    category = "Miscellaneous",
+    aliases = ["admin"],
+    syntax = [""],
+    children = [
+        disable::Disable,
+        enable::Enable,
+        stats::Stats,
+    ],
+)]
+pub struct Admin;
+
+/// Disables or enables the command at the path given in the raw input globally, replying with the
+/// result. Only owners can do this.
+async fn toggle(
+    state: &Arc<State>,
+    ctxt: Context<'_>,
+    disabled: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if !state.is_owner(ctxt.trigger.author_id()) {
+        return Err("**This command can only be used by CalcBot's owners.**".into());
+    }
+
+    if ctxt.raw_input.is_empty() {
+        return Err("**You must provide the path of a command, like `nm unscramble`.**".into());
+    }
+
+    // disabling the admin commands would make it impossible to enable them again
+    let is_admin = state.commands.resolve_path(ctxt.raw_input)
+        .map_or(false, |path| path.split(' ').next() == Some("admin"));
+    if disabled && is_admin {
+        return Err("**The admin commands cannot be disabled.**".into());
+    }
+
+    let output = match state.set_disabled(ctxt.raw_input, disabled) {
+        Some(path) => format!(
+            "**{} `{}`** until the next restart.",
+            if disabled { "Disabled" } else { "Enabled" },
+            path,
+        ),
+        None => format!("**There is no command at `{}`.**", ctxt.raw_input),
+    };
+
+    ctxt.trigger.reply(&state.http)
+        .content(&output)?
+        .await?;
+
+    Ok(())
+}
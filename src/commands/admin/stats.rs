@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+    tasks::{CategoryStats, TaskCategory},
+    util::format_duration,
+};
+use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// The maximum number of commands listed.
+const MAX_LISTED: usize = 25;
+
+/// Describes the running long-lived tasks in each category, like `2 timers (oldest 5 minutes)`.
+fn format_task_stats(stats: &BTreeMap<TaskCategory, CategoryStats>) -> String {
+    if stats.is_empty() {
+        return "none".to_owned();
+    }
+
+    stats.iter()
+        .map(|(category, stats)| format!(
+            "{} {} (oldest {})",
+            stats.count,
+            category,
+            format_duration(stats.oldest.unwrap_or_default()),
+        ))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// View how often each command has been run since the last restart, how often it failed, and its
+/// average execution time, weighted towards recent runs. The long-lived tasks that are running,
+/// like reminder timers, are listed too.
+#[derive(Clone, Info)]
+#[info(aliases = ["stats", "metrics"])]
+pub struct Stats;
+
+#[async_trait]
+impl Command for Stats {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if !state.is_owner(ctxt.trigger.author_id()) {
+            return Err("**This command can only be used by CalcBot's owners.**".into());
+        }
+
+        let stats = state.command_stats();
+        let lines = stats.iter()
+            .take(MAX_LISTED)
+            .map(|(path, stats)| format!(
+                "`{}`: {} runs, {} errors, {}ms average",
+                path,
+                stats.invocations,
+                stats.errors,
+                stats.average_latency.as_millis(),
+            ))
+            .collect::<Vec<_>>();
+        let mut description = if lines.is_empty() {
+            "_no commands have been run yet_".to_owned()
+        } else {
+            lines.join("\n")
+        };
+        if stats.len() > MAX_LISTED {
+            description.push_str(&format!("\n_…and {} more_", stats.len() - MAX_LISTED));
+        }
+
+        let embed = EmbedBuilder::new()
+            .title("Command stats")
+            .color(0x988bc2)
+            .description(description)
+            .field(EmbedFieldBuilder::new("Active tasks", format_task_stats(&state.tasks.stats())))
+            .build();
+
+        ctxt.trigger.reply(&state.http)
+            .embeds(&[embed])?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn no_tasks() {
+        assert_eq!(format_task_stats(&BTreeMap::new()), "none");
+    }
+
+    #[test]
+    fn tasks_by_category() {
+        let stats = BTreeMap::from([
+            (TaskCategory::Pager, CategoryStats { count: 1, oldest: Some(Duration::from_secs(30)) }),
+            (TaskCategory::Timer, CategoryStats { count: 2, oldest: Some(Duration::from_secs(300)) }),
+        ]);
+        assert_eq!(format_task_stats(&stats), "2 timers (oldest 5 minutes), 1 pagers (oldest 30 seconds)");
+    }
+}
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// The error message for a base outside the supported range.
+const INVALID_BASE: &str = "**Bases must be whole numbers from 2 to 36.**";
+
+/// Parses a base from 2 to 36.
+fn parse_base(raw: &str) -> Result<u32, &'static str> {
+    raw.parse::<u32>()
+        .ok()
+        .filter(|base| (2..=36).contains(base))
+        .ok_or(INVALID_BASE)
+}
+
+/// Parses a number written in the given base, which may start with a minus sign. Letters are
+/// case-insensitive. If the number is invalid, the message to show the user is returned instead.
+fn parse_in_base(raw: &str, base: u32) -> Result<(bool, u128), String> {
+    let (negative, digits) = match raw.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, raw),
+    };
+    if digits.is_empty() {
+        return Err("**You must provide a number to convert.**".to_owned());
+    }
+
+    let mut magnitude = 0u128;
+    for c in digits.chars() {
+        let Some(digit) = c.to_digit(base) else {
+            return Err(format!("**`{}` is not a valid digit in base {}.**", c, base));
+        };
+        magnitude = magnitude.checked_mul(base as u128)
+            .and_then(|magnitude| magnitude.checked_add(digit as u128))
+            .ok_or_else(|| "**That number is too large to convert.**".to_owned())?;
+    }
+    Ok((negative && magnitude != 0, magnitude))
+}
+
+/// Writes a number in the given base, using lowercase letters for digits above 9.
+fn format_in_base(negative: bool, mut magnitude: u128, base: u32) -> String {
+    let mut digits = Vec::new();
+    loop {
+        digits.push(char::from_digit((magnitude % base as u128) as u32, base).unwrap());
+        magnitude /= base as u128;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    if negative {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+/// Converts a whole number from one base to another. Bases from 2 to 36 are supported, with the
+/// letters `a` to `z` used for digits above 9.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["base", "radix"],
+    syntax = ["<number> <from base> <to base>"],
+    examples = ["255 10 16", "ff 16 2", "-1010 2 10"],
+)]
+pub struct Base;
+
+#[async_trait]
+impl Command for Base {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let [number, from, to] = ctxt.raw_input.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err("**You must provide a number, the base it is in, and the base to convert it to, like `255 10 16`.**".into());
+        };
+        let from = parse_base(from)?;
+        let to = parse_base(to)?;
+
+        let (trigger, output) = match parse_in_base(number, from) {
+            Ok((negative, magnitude)) => (ctxt.trigger, format!(
+                "**`{}`** in base {} is **`{}`** in base {}",
+                number,
+                from,
+                format_in_base(negative, magnitude, to),
+                to,
+            )),
+            Err(message) => (ctxt.trigger.unattributed(), message),
+        };
+
+        trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bases() {
+        assert_eq!(parse_base("2"), Ok(2));
+        assert_eq!(parse_base("36"), Ok(36));
+        assert_eq!(parse_base("1"), Err(INVALID_BASE));
+        assert_eq!(parse_base("37"), Err(INVALID_BASE));
+        assert_eq!(parse_base("ten"), Err(INVALID_BASE));
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse_in_base("255", 10), Ok((false, 255)));
+        assert_eq!(parse_in_base("FF", 16), Ok((false, 255)));
+        assert_eq!(parse_in_base("-1010", 2), Ok((true, 10)));
+        assert_eq!(parse_in_base("zz", 36), Ok((false, 1295)));
+    }
+
+    #[test]
+    fn negative_zero_is_zero() {
+        assert_eq!(parse_in_base("-0", 10), Ok((false, 0)));
+    }
+
+    #[test]
+    fn parse_invalid_numbers() {
+        assert!(parse_in_base("", 10).is_err());
+        assert!(parse_in_base("-", 10).is_err());
+        assert_eq!(parse_in_base("12", 2), Err("**`2` is not a valid digit in base 2.**".to_owned()));
+        assert_eq!(parse_in_base(&"f".repeat(33), 16), Err("**That number is too large to convert.**".to_owned()));
+    }
+
+    #[test]
+    fn parse_largest_number() {
+        assert_eq!(parse_in_base(&"f".repeat(32), 16), Ok((false, u128::MAX)));
+    }
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(format_in_base(false, 0, 2), "0");
+        assert_eq!(format_in_base(false, 255, 16), "ff");
+        assert_eq!(format_in_base(true, 10, 2), "-1010");
+        assert_eq!(format_in_base(false, u128::MAX, 36), "f5lxx1zz5pnorynqglhzmsp33");
+    }
+
+    #[test]
+    fn round_trips() {
+        for base in 2..=36 {
+            for raw in ["0", "1", "-1", "10", "-zz"] {
+                let Ok((negative, magnitude)) = parse_in_base(raw, base) else {
+                    continue;
+                };
+                assert_eq!(parse_in_base(&format_in_base(negative, magnitude, base), base), Ok((negative, magnitude)));
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use cas_compute::numerical::ctxt::{Ctxt, Func};
+use crate::{
+    commands::{Command, Context},
+    database::{user::{remove_definition, reset_ctxt, RemovedDefinition, UserField}, Database},
+    error::Error,
+    global::State,
+    util::pluralize,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Returns the number of variables and user-defined functions in the context that are not in the
+/// default context.
+fn count_definitions(ctxt: &Ctxt) -> usize {
+    let default = Ctxt::default();
+    let vars = ctxt.get_vars()
+        .keys()
+        .filter(|name| !default.get_vars().contains_key(*name))
+        .count();
+    let funcs = ctxt.get_funcs()
+        .values()
+        .filter(|func| matches!(func, Func::UserFunc(_)))
+        .count();
+    vars + funcs
+}
+
+/// Removes all of your variables and functions defined using `{prefix}calculate`, or only the one
+/// with the given name. Your calculation mode is kept. Built-in constants like `pi` cannot be
+/// removed, but if you have redefined one, its built-in value is restored.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["clear"],
+    syntax = ["", "<name>"],
+    examples = ["", "x"],
+)]
+pub struct Clear;
+
+#[async_trait]
+impl Command for Clear {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let user_id = ctxt.trigger.author_id();
+        let mut database = database.lock().await;
+        let old_ctxt = database.get_user(user_id).await.ctxt.clone();
+
+        let output = match ctxt.raw_input.split_whitespace().next() {
+            Some(name) => {
+                let forgotten = database.forget_quarantined(user_id, Some(name));
+                match remove_definition(&old_ctxt, name) {
+                    RemovedDefinition::Removed(new_ctxt) => {
+                        database.set_user_field(user_id, UserField::Ctxt(new_ctxt)).await;
+                        format!("**Removed the definition of `{}`.**", name)
+                    },
+                    RemovedDefinition::Restored(new_ctxt) => {
+                        database.set_user_field(user_id, UserField::Ctxt(new_ctxt)).await;
+                        format!("**Restored the built-in value of `{}`.**", name)
+                    },
+                    RemovedDefinition::Builtin => format!("**`{}` is built in, and cannot be removed.**", name),
+                    RemovedDefinition::NotDefined if forgotten > 0 => {
+                        // the quarantined definition is dropped the next time the context is saved
+                        database.set_user_field(user_id, UserField::Ctxt(old_ctxt)).await;
+                        format!("**Removed the definition of `{}`.**", name)
+                    },
+                    RemovedDefinition::NotDefined => format!("**`{}` is not defined.**", name),
+                }
+            },
+            None => {
+                let removed = count_definitions(&old_ctxt) + database.forget_quarantined(user_id, None);
+                database.set_user_field(user_id, UserField::Ctxt(reset_ctxt(&old_ctxt))).await;
+                format!("**Removed {}.**", pluralize(removed, "definition"))
+            },
+        };
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_constants_are_not_counted() {
+        assert_eq!(count_definitions(&Ctxt::default()), 0);
+
+        let mut ctxt = Ctxt::default();
+        ctxt.add_var("x", Ctxt::default().get_vars()["e"].clone());
+        assert_eq!(count_definitions(&ctxt), 1);
+    }
+}
@@ -0,0 +1,167 @@
+use ariadne::Source;
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use cas_compute::numerical::{ctxt::Ctxt, eval::eval_stmts};
+use cas_parser::parser::Parser;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::{CasErrors, Error},
+    global::State,
+    util::format_displayed,
+};
+use std::{ops::Range, sync::Arc};
+use super::run_calculation;
+use tokio::sync::Mutex;
+
+/// The name of the temporary function the expression is defined as, so that it can be
+/// differentiated with the `f'(x)` syntax.
+const FUNCTION_NAME: &str = "deriv_fn";
+
+/// Splits the input into the expression, the variable, and the point to evaluate the derivative
+/// at. The variable and point are only recognized if the second-to-last word is a name.
+fn split_input(input: &str) -> (String, &str, Option<&str>) {
+    let words = input.split_whitespace().collect::<Vec<_>>();
+    match words.as_slice() {
+        [expr @ .., var, point] if !expr.is_empty() && var.chars().all(char::is_alphabetic) => {
+            (expr.join(" "), var, Some(point))
+        },
+        _ => (input.to_owned(), "x", None),
+    }
+}
+
+/// Moves the given spans, which point into generated source, back by `offset` bytes, so that they
+/// point into the part of the source the user typed, which is `len` bytes long.
+fn shift_spans(spans: &mut [Range<usize>], offset: usize, len: usize) {
+    for span in spans {
+        span.start = span.start.saturating_sub(offset).min(len);
+        span.end = span.end.saturating_sub(offset).min(len).max(span.start);
+    }
+}
+
+/// Evaluates the given source in the given context, returning the result as a string, or the
+/// errors if it could not be parsed or evaluated.
+///
+/// The source is generated from the user's input, which is `typed`, starting `offset` bytes into
+/// the source. Errors are shown against what the user typed rather than the generated source.
+fn eval(source: &str, offset: usize, typed: &str, ctxt: &mut Ctxt) -> Result<String, CasErrors> {
+    let stmts = Parser::new(source).try_parse_full_many().map_err(|errs| CasErrors::new(errs.into_iter()
+        .map(|mut err| {
+            shift_spans(&mut err.spans, offset, typed.len());
+            CasErrors::render_report(|buf| err.build_report().write(("input", Source::from(typed)), buf))
+        })
+        .collect()))?;
+    eval_stmts(&stmts, ctxt)
+        .map(|value| value.to_string())
+        .map_err(|mut err| {
+            shift_spans(&mut err.spans, offset, typed.len());
+            CasErrors::new(vec![
+                CasErrors::render_report(|buf| err.build_report().write(("input", Source::from(typed)), buf)),
+            ])
+        })
+}
+
+/// Differentiates the expression with respect to the variable, and evaluates the derivative at the
+/// given point in the given context.
+///
+/// The expression is defined as a temporary function named [`FUNCTION_NAME`], which is left in the
+/// context.
+fn differentiate(expr: &str, var: &str, point: &str, ctxt: &mut Ctxt) -> Result<String, CasErrors> {
+    let definition = format!("{}({}) = {}", FUNCTION_NAME, var, expr);
+    eval(&definition, definition.len() - expr.len(), expr, ctxt)?;
+
+    let call = format!("{}'({})", FUNCTION_NAME, point);
+    eval(&call, FUNCTION_NAME.len() + 2, point, ctxt)
+}
+
+/// Differentiates an expression with respect to a variable (default `x`), and evaluates the
+/// derivative at the given point. Your variables and functions can be used in the expression.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["derivative", "deriv"],
+    syntax = ["<expression> <variable> <point>"],
+    examples = ["x^2 x 3", "sin(t) t pi"],
+)]
+pub struct Derivative;
+
+#[async_trait]
+impl Command for Derivative {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (expr, var, point) = split_input(ctxt.raw_input);
+        let Some(point) = point else {
+            return Err("**You must give a variable and a point to evaluate the derivative at**, like `x^2 x 3`.".into());
+        };
+
+        // the user's context is used, but not saved, so the temporary function is discarded
+        let user_data = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .clone();
+        let mut eval_ctxt = user_data.ctxt;
+
+        let result = {
+            let (expr, var, point) = (expr.clone(), var.to_owned(), point.to_owned());
+            run_calculation(state, move |_| differentiate(&expr, &var, &point, &mut eval_ctxt)).await
+        };
+        let (trigger, output) = match result {
+            Ok(Ok(slope)) => (ctxt.trigger, format!(
+                "**Derivative** of `{}` with respect to `{}` at `{} = {}`\n{}",
+                expr,
+                var,
+                var,
+                point,
+                format_displayed(&slope, user_data.settings.notation, user_data.settings.group_digits),
+            )),
+            Ok(Err(errors)) => (ctxt.trigger.unattributed(), errors.code_block()),
+            Err(message) => (ctxt.trigger.unattributed(), message),
+        };
+
+        trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_variable_and_point() {
+        assert_eq!(split_input("x^2 x 3"), ("x^2".to_owned(), "x", Some("3")));
+        assert_eq!(split_input("sin(t) + t t pi"), ("sin(t) + t".to_owned(), "t", Some("pi")));
+    }
+
+    #[test]
+    fn split_without_variable() {
+        assert_eq!(split_input("x^2"), ("x^2".to_owned(), "x", None));
+        assert_eq!(split_input("x^2 3"), ("x^2 3".to_owned(), "x", None));
+        assert_eq!(split_input("x + 1 2 3"), ("x + 1 2 3".to_owned(), "x", None));
+    }
+
+    #[test]
+    fn derivative_at_point() {
+        assert_eq!(differentiate("x^2", "x", "3", &mut Ctxt::default()).ok(), Some("6".to_owned()));
+    }
+
+    #[test]
+    fn spans_are_shifted_into_typed_input() {
+        let mut spans = [14..17, 0..8, 10..30];
+        shift_spans(&mut spans, 14, 5);
+        assert_eq!(spans, [0..3, 0..0, 0..5]);
+    }
+
+    #[test]
+    fn errors_point_at_typed_input() {
+        let errors = differentiate("x +", "x", "3", &mut Ctxt::default()).unwrap_err();
+        let block = errors.code_block();
+        assert!(block.contains("x +"));
+        assert!(!block.contains(FUNCTION_NAME));
+    }
+}
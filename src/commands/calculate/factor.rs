@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+    util::Cancellation,
+};
+use std::sync::Arc;
+use super::run_calculation;
+use tokio::sync::Mutex;
+
+/// The largest number that can be factored. Trial division needs at most a million or so steps for
+/// numbers up to 10^15, which finishes well within the calculation timeout.
+const MAX_NUMBER: u64 = 1_000_000_000_000_000;
+
+/// Factors a number of at least 2 into its prime factors by trial division, returning each prime
+/// factor in ascending order with its exponent.
+///
+/// Returns [`None`] if the calculation is cancelled before it finishes.
+fn prime_factors(mut n: u64, cancellation: &Cancellation) -> Option<Vec<(u64, u32)>> {
+    let mut factors = Vec::new();
+    let mut divisor = 2;
+    while divisor * divisor <= n {
+        if cancellation.is_cancelled() {
+            return None;
+        }
+
+        let mut exponent = 0;
+        while n % divisor == 0 {
+            n /= divisor;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((divisor, exponent));
+        }
+
+        // after 2, only odd numbers can be prime
+        divisor += if divisor == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    Some(factors)
+}
+
+/// Formats a prime factorization like `2^2 * 3`.
+fn format_factors(factors: &[(u64, u32)]) -> String {
+    factors.iter()
+        .map(|&(prime, exponent)| match exponent {
+            1 => prime.to_string(),
+            _ => format!("{}^{}", prime, exponent),
+        })
+        .collect::<Vec<_>>()
+        .join(" * ")
+}
+
+/// Finds the prime factorization of a whole number, and whether it is prime. Numbers from 2 to
+/// 10^15 are supported.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["factor", "factorize", "prime"],
+    syntax = ["<whole number>"],
+    examples = ["12", "17", "600851475143"],
+)]
+pub struct Factor;
+
+#[async_trait]
+impl Command for Factor {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let n = ctxt.raw_input.trim()
+            .parse::<u64>()
+            .ok()
+            .filter(|n| (2..=MAX_NUMBER).contains(n))
+            .ok_or("**You must provide a whole number from 2 to 10^15.**")?;
+
+        let result = run_calculation(state, move |cancellation| prime_factors(n, cancellation)).await;
+        let (trigger, output) = match result {
+            // factoring is only cancelled after the calculation has timed out
+            Ok(None) => return Ok(()),
+            Ok(Some(factors)) if factors == [(n, 1)] => (ctxt.trigger, format!("**{}** is prime.", n)),
+            Ok(Some(factors)) => (ctxt.trigger, format!(
+                "**{}** is not prime.\n{} = {}",
+                n,
+                n,
+                format_factors(&factors),
+            )),
+            Err(message) => (ctxt.trigger.unattributed(), message),
+        };
+
+        trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Factors the number with a cancellation that is never cancelled.
+    fn factor(n: u64) -> Vec<(u64, u32)> {
+        prime_factors(n, &Cancellation::default()).unwrap()
+    }
+
+    #[test]
+    fn factor_primes() {
+        assert_eq!(factor(2), vec![(2, 1)]);
+        assert_eq!(factor(3), vec![(3, 1)]);
+        assert_eq!(factor(97), vec![(97, 1)]);
+    }
+
+    #[test]
+    fn factor_composites() {
+        assert_eq!(factor(4), vec![(2, 2)]);
+        assert_eq!(factor(12), vec![(2, 2), (3, 1)]);
+        assert_eq!(factor(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factor(1001), vec![(7, 1), (11, 1), (13, 1)]);
+    }
+
+    #[test]
+    fn factor_large_numbers() {
+        assert_eq!(factor(MAX_NUMBER), vec![(2, 15), (5, 15)]);
+        // the largest prime below 10^15
+        assert_eq!(factor(999_999_999_999_989), vec![(999_999_999_999_989, 1)]);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_factors(&[(2, 2), (3, 1)]), "2^2 * 3");
+        assert_eq!(format_factors(&[(97, 1)]), "97");
+    }
+}
@@ -1,16 +1,68 @@
 use async_trait::async_trait;
 use calcbot_attrs::Info;
-use cas_compute::numerical::ctxt::{Func, UserFunc};
+use cas_compute::numerical::ctxt::{Ctxt, Func, UserFunc};
 use crate::{
     commands::{Command, Context},
     database::Database,
     error::Error,
     global::State,
+    util::send_paged_message,
 };
+use serde_json::to_value;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use twilight_model::channel::message::Embed;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFooterBuilder};
 
-/// Lists all variables and functions defined using `{prefix}calculate`.
+/// The maximum length of a Discord message, in characters. Longer listings are split into pages.
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Formats the variables the user defined, sorted by name. Built-in constants, like `pi`, are
+/// left out unless the user has given them a different value.
+fn format_vars(ctxt: &Ctxt) -> Vec<String> {
+    let default = Ctxt::default();
+    let mut vars = ctxt.get_vars()
+        .iter()
+        .filter(|(name, value)| default.get_vars()
+            .get(*name)
+            .map_or(true, |default_value| to_value(value).ok() != to_value(default_value).ok()))
+        .map(|(name, value)| format!("`{} = {}`", name, value))
+        .collect::<Vec<_>>();
+    vars.sort();
+    vars
+}
+
+/// Splits the listing into embed pages, each at most [`MAX_MESSAGE_LENGTH`] characters long.
+/// Lines are never split across pages.
+fn paginate(output: &str) -> Vec<Embed> {
+    let mut pages = vec![String::new()];
+    for line in output.lines() {
+        let page = pages.last_mut().unwrap();
+        if !page.is_empty() && page.chars().count() + line.chars().count() + 1 > MAX_MESSAGE_LENGTH {
+            pages.push(String::new());
+        }
+
+        let page = pages.last_mut().unwrap();
+        if !page.is_empty() {
+            page.push('\n');
+        }
+        page.push_str(line);
+    }
+
+    let total = pages.len();
+    pages.into_iter()
+        .enumerate()
+        .map(|(i, page)| EmbedBuilder::new()
+            .title("Your definitions")
+            .color(0x66d2e8)
+            .description(page)
+            .footer(EmbedFooterBuilder::new(format!("Page {} of {}", i + 1, total)))
+            .build())
+        .collect()
+}
+
+/// Lists all variables and functions defined using `{prefix}calculate`. Long lists are split into
+/// pages.
 #[derive(Clone, Info)]
 #[info(aliases = ["listdefs", "listdef", "ld", "ls"])]
 pub struct ListDefinitions;
@@ -23,15 +75,12 @@ impl Command for ListDefinitions {
         database: &Arc<Mutex<Database>>,
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let (vars, funcs) = {
+        let (vars, funcs, quarantined) = {
             let mut database = database.lock().await;
             let user_data = database.get_user(ctxt.trigger.author_id()).await;
 
             (
-                user_data.ctxt.get_vars()
-                    .iter()
-                    .map(|(name, value)| format!("`{} = {}`", name, value))
-                    .collect::<Vec<_>>(),
+                format_vars(&user_data.ctxt),
                 user_data.ctxt.get_funcs()
                     .values()
                     .filter_map(|func| match func {
@@ -39,13 +88,80 @@ impl Command for ListDefinitions {
                         Func::Builtin(_) => None,
                     })
                     .collect::<Vec<_>>(),
+                user_data.quarantined.iter()
+                    .map(|definition| format!("`{}` ⚠️", definition.name))
+                    .collect::<Vec<_>>(),
             )
         };
 
+        let mut output = format!("**Variables**:\n{}\n\n**Functions**:\n{}", vars.join("\n"), funcs.join("\n"));
+        if !quarantined.is_empty() {
+            output.push_str(&format!(
+                "\n\n**Could not be loaded** (redefine these to replace them):\n{}",
+                quarantined.join("\n"),
+            ));
+        }
+
+        if output.chars().count() > MAX_MESSAGE_LENGTH {
+            send_paged_message(state, database, ctxt.trigger, &paginate(&output), 0)?;
+            return Ok(());
+        }
+
         ctxt.trigger.reply(&state.http)
-            .content(&format!("**Variables**:\n{}\n\n**Functions**:\n{}", vars.join("\n"), funcs.join("\n")))?
+            .content(&output)?
             .await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_constants_are_not_listed() {
+        assert!(format_vars(&Ctxt::default()).is_empty());
+
+        let mut ctxt = Ctxt::default();
+        let e = Ctxt::default().get_vars()["e"].clone();
+        ctxt.add_var("x", e.clone());
+        assert_eq!(format_vars(&ctxt), [format!("`x = {}`", e)]);
+    }
+
+    #[test]
+    fn overridden_constants_are_listed() {
+        let mut ctxt = Ctxt::default();
+        let e = Ctxt::default().get_vars()["e"].clone();
+        ctxt.add_var("pi", e.clone());
+        assert_eq!(format_vars(&ctxt), [format!("`pi = {}`", e)]);
+    }
+
+    #[test]
+    fn short_listing_is_one_page() {
+        let pages = paginate("**Variables**:\n`x = 2`");
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].description.as_deref(), Some("**Variables**:\n`x = 2`"));
+        assert_eq!(pages[0].footer.as_ref().unwrap().text, "Page 1 of 1");
+    }
+
+    #[test]
+    fn long_listing_is_split_between_lines() {
+        let line = format!("`x = {}`", "1".repeat(90));
+        let output = vec![line.as_str(); 50].join("\n");
+        let pages = paginate(&output);
+
+        assert_eq!(pages.len(), 3);
+        for (i, page) in pages.iter().enumerate() {
+            let description = page.description.as_deref().unwrap();
+            assert!(description.chars().count() <= MAX_MESSAGE_LENGTH);
+            assert!(description.lines().all(|l| l == line));
+            assert_eq!(page.footer.as_ref().unwrap().text, format!("Page {} of 3", i + 1));
+        }
+
+        let lines = pages.iter()
+            .map(|page| page.description.as_deref().unwrap().lines().count())
+            .sum::<usize>();
+        assert_eq!(lines, 50);
+    }
+}
@@ -1,5 +1,10 @@
+pub mod base;
+pub mod clear;
+pub mod derivative;
+pub mod factor;
 pub mod list_definitions;
 pub mod mode;
+pub mod stats;
 pub mod to_latex;
 
 use ariadne::Source;
@@ -8,17 +13,99 @@ use calcbot_attrs::Info;
 use cas_compute::numerical::eval::eval_stmts;
 use cas_parser::parser::Parser;
 use crate::{
-    commands::{Command, Context},
+    commands::{Command, Context, SentReply},
     database::{user::UserField, Database},
-    error::Error,
+    error::{CasErrors, Error},
     global::State,
+    util::{format_displayed, format_duration, run_with_timeout, Cancellation, Unfinished},
 };
-use strip_ansi_escapes::strip;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
+/// The maximum length of the results of a calculation with multiple statements, in characters.
+/// This leaves room in the message for the header and notes.
+const MAX_RESULTS_LENGTH: usize = 1500;
+
+/// Formats the results of the statements in a calculation. A single result is shown on its own,
+/// and multiple results are shown as a numbered list, one line per statement.
+///
+/// If the list is too long, statements are left out before the last one, so that the final result
+/// is always shown.
+fn format_results(results: &[String]) -> String {
+    let [.., last] = results else {
+        return String::new();
+    };
+    if results.len() == 1 {
+        return last.clone();
+    }
+
+    let last_line = format!("{}. {}", results.len(), last);
+    let mut lines = Vec::new();
+    let mut length = last_line.chars().count();
+    for (i, result) in results[..results.len() - 1].iter().enumerate() {
+        let line = format!("{}. {}", i + 1, result);
+        length += line.chars().count() + 1;
+        if length > MAX_RESULTS_LENGTH {
+            lines.push(format!("_… {} more_", results.len() - 1 - i));
+            break;
+        }
+        lines.push(line);
+    }
+    lines.push(last_line);
+    lines.join("\n")
+}
+
+/// Returns the message to show the user when a calculation allowed the given time limit does not
+/// finish.
+fn unfinished_message(unfinished: Unfinished, limit: Duration) -> String {
+    match unfinished {
+        Unfinished::TimedOut => format!(
+            "**The calculation took too long.** Its result was abandoned after {}.",
+            format_duration(limit),
+        ),
+        Unfinished::Panicked => "**Something went wrong during the calculation.** Please report this to the developers.".to_owned(),
+    }
+}
+
+/// Runs a calculation with [`run_with_timeout`], allowing it [`State::calculation_timeout`]. If
+/// the calculation does not finish, the message to show the user is returned instead.
+async fn run_calculation<T>(
+    state: &State,
+    calculation: impl FnOnce(&Cancellation) -> T + Send + 'static,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+{
+    run_with_timeout(state.calculation_timeout, calculation).await
+        .map_err(|unfinished| unfinished_message(unfinished, state.calculation_timeout))
+}
+
+/// Sends the reply to a calculation, replacing the placeholder reply if one was sent. Errors are
+/// not attributed, even if the server asked for attribution.
+async fn respond(
+    state: &State,
+    ctxt: &Context<'_>,
+    placeholder: Option<SentReply<'_>>,
+    content: &str,
+    is_error: bool,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (trigger, placeholder) = match is_error {
+        true => (ctxt.trigger.unattributed(), placeholder.map(SentReply::unattributed)),
+        false => (ctxt.trigger, placeholder),
+    };
+    match placeholder {
+        Some(placeholder) => placeholder.edit_content(&state.http, content).await?,
+        None => {
+            trigger.reply(&state.http)
+                .content(content)?
+                .await?;
+        },
+    }
+    Ok(())
+}
+
 /// Evaluates a given expression, like `1 + 1`. You can declare variables by typing `variablename =
-/// [value]`.
+/// [value]`. If you give multiple statements, the result of each one is shown.
 ///
 /// You can find extended documentation for this command
 /// [here](https://chillant.gitbook.io/calcbot/commands/calculate).
@@ -29,8 +116,13 @@ use tokio::sync::Mutex;
     syntax = ["<expression>"],
     examples = ["1+1", "x=2", "5sin(pi/2)", "6!", "f(x)=x^2+5x+6", "f(2)", "cos'(0)"],
     children = [
+        base::Base,
+        clear::Clear,
+        derivative::Derivative,
+        factor::Factor,
         list_definitions::ListDefinitions,
         mode::Mode,
+        stats::Stats,
         to_latex::ToLatex,
     ],
 )]
@@ -47,50 +139,135 @@ impl Command for Calculate {
         let mut parser = Parser::new(ctxt.raw_input);
         match parser.try_parse_full_many() {
             Ok(stmts) => {
+                // calculations with many statements can take a while, so show that CalcBot is
+                // working on it, and replace the placeholder with the result
+                let placeholder = match stmts.len() {
+                    1 => None,
+                    _ => Some(ctxt.trigger.reply_placeholder(&state.http, "**Calculating…**").await?),
+                };
+
                 let mut user_data = database.lock().await
                     .get_user(ctxt.trigger.author_id()).await
                     .clone();
 
-                let ans = match eval_stmts(&stmts, &mut user_data.ctxt) {
-                    Ok(ans) => ans,
-                    Err(err) => {
-                        let mut buf = Vec::new();
-                        err.build_report()
-                            .write(("input", Source::from(ctxt.raw_input)), &mut buf)
-                            .unwrap();
-
-                        ctxt.trigger.reply(&state.http)
-                            .content(&format!("```rs\n{}\n```", String::from_utf8_lossy(&strip(buf).unwrap())))?
-                            .await?;
+                // evaluate each statement on its own so that every result can be shown, stopping
+                // early if the calculation times out
+                let input = ctxt.raw_input.to_owned();
+                let mut eval_ctxt = user_data.ctxt;
+                let evaluated = run_calculation(state, move |cancellation| {
+                    let results = stmts.iter()
+                        .take_while(|_| !cancellation.is_cancelled())
+                        .map(|stmt| eval_stmts(std::slice::from_ref(stmt), &mut eval_ctxt))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| CasErrors::new(vec![CasErrors::render_report(|buf| {
+                            err.build_report().write(("input", Source::from(input.as_str())), buf)
+                        })]));
+                    (results, eval_ctxt)
+                }).await;
+                let results = match evaluated {
+                    Ok((Ok(results), eval_ctxt)) => {
+                        user_data.ctxt = eval_ctxt;
+                        results
+                    },
+                    Ok((Err(errors), _)) => {
+                        respond(state, &ctxt, placeholder, &errors.code_block(), true).await?;
+                        return Ok(());
+                    },
+                    Err(message) => {
+                        respond(state, &ctxt, placeholder, &message, true).await?;
                         return Ok(());
                     },
                 };
-                ctxt.trigger.reply(&state.http)
-                    .content(&format!("**Calculation** (mode: {})\n{}", user_data.ctxt.trig_mode, ans))?
-                    .await?;
+                let displayed = results.iter()
+                    .map(|value| format_displayed(&value.to_string(), user_data.settings.notation, user_data.settings.group_digits))
+                    .collect::<Vec<_>>();
+                let mut output = format!(
+                    "**Calculation** (mode: {})\n{}",
+                    user_data.ctxt.trig_mode,
+                    format_results(&displayed),
+                );
+                if let Some(names) = database.lock().await.take_quarantine_notice(ctxt.trigger.author_id()) {
+                    output.push_str(&format!(
+                        "\n_note: some of your saved definitions could not be loaded after a CalcBot update, and were skipped: `{}`. Redefine them to replace them._",
+                        names.join("`, `"),
+                    ));
+                }
+                respond(state, &ctxt, placeholder, &output, false).await?;
 
-                user_data.ctxt.add_var("ans", ans);
+                if let Some(ans) = results.into_iter().last() {
+                    user_data.ctxt.add_var("ans", ans);
+                }
                 database.lock().await
                     .set_user_field(ctxt.trigger.author_id(), UserField::Ctxt(user_data.ctxt)).await;
             },
             Err(errs) => {
-                let msg = errs.into_iter()
-                    .map(|err| {
-                        let mut buf = Vec::new();
-                        err.build_report()
-                            .write(("input", Source::from(ctxt.raw_input)), &mut buf)
-                            .unwrap();
-                        String::from_utf8(strip(buf).unwrap()).unwrap()
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                ctxt.trigger.reply(&state.http)
-                    .content(&format!("```rs\n{}\n```", msg))?
-                    .await?;
+                return Err(CasErrors::new(errs.into_iter()
+                    .map(|err| CasErrors::render_report(|buf| {
+                        err.build_report().write(("input", Source::from(ctxt.raw_input)), buf)
+                    }))
+                    .collect())
+                    .into());
             },
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn slow_calculation_times_out() {
+        let limit = Duration::from_secs(1);
+        let result = run_with_timeout(limit, |_| std::thread::sleep(Duration::from_secs(3))).await
+            .map_err(|unfinished| unfinished_message(unfinished, limit));
+        assert_eq!(
+            result,
+            Err("**The calculation took too long.** Its result was abandoned after 1 second.".to_owned()),
+        );
+    }
+
+    #[tokio::test]
+    async fn timed_out_calculation_is_cancelled() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let result = run_with_timeout(Duration::from_millis(100), move |cancellation| {
+            while !cancellation.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            sender.send(()).unwrap();
+        }).await;
+        assert_eq!(result, Err(Unfinished::TimedOut));
+
+        // the calculation stops on its own once it sees the cancellation
+        assert!(receiver.recv_timeout(Duration::from_secs(1)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn fast_calculation_finishes() {
+        assert_eq!(run_with_timeout(Duration::from_secs(1), |_| 1 + 1).await, Ok(2));
+    }
+
+    #[test]
+    fn single_result_is_shown_alone() {
+        assert_eq!(format_results(&["2".to_owned()]), "2");
+        assert_eq!(format_results(&[]), "");
+    }
+
+    #[test]
+    fn multiple_results_are_numbered() {
+        let results = ["1".to_owned(), "2".to_owned(), "3".to_owned()];
+        assert_eq!(format_results(&results), "1. 1\n2. 2\n3. 3");
+    }
+
+    #[test]
+    fn long_results_keep_the_last_one() {
+        let mut results = vec!["x".repeat(100); 30];
+        results.push("last".to_owned());
+        let formatted = format_results(&results);
+        assert!(formatted.chars().count() <= MAX_RESULTS_LENGTH + 20);
+        assert!(formatted.ends_with("\n31. last"));
+        assert!(formatted.contains("more_"));
+    }
+}
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use calcbot_attrs::Info;
+use calcbot_attrs::{Info, ParseArg};
 use cas_compute::numerical::ctxt::TrigMode;
 use crate::{
     commands::{Command, Context},
@@ -10,6 +10,25 @@ use crate::{
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// An angle calculation mode, as given by the user.
+#[derive(Clone, Copy, Debug, ParseArg)]
+enum AngleMode {
+    #[arg(aliases = ["radians", "radian", "rad", "r"])]
+    Radians,
+
+    #[arg(aliases = ["degrees", "degree", "deg", "d"])]
+    Degrees,
+}
+
+impl From<AngleMode> for TrigMode {
+    fn from(mode: AngleMode) -> Self {
+        match mode {
+            AngleMode::Radians => TrigMode::Radians,
+            AngleMode::Degrees => TrigMode::Degrees,
+        }
+    }
+}
+
 /// View or set the angle calculation mode of the calculator. (default **radians**)
 #[derive(Clone, Info)]
 #[info(
@@ -29,10 +48,10 @@ impl Command for Mode {
             .get_user(ctxt.trigger.author_id()).await
             .clone();
 
-        let new_mode = match ctxt.raw_input.get(0..1) {
-            Some("r") => TrigMode::Radians,
-            Some("d") => TrigMode::Degrees,
-            _ => {
+        let new_mode = match ctxt.raw_input.split_whitespace().next().map(str::parse::<AngleMode>) {
+            Some(Ok(mode)) => TrigMode::from(mode),
+            Some(Err(err)) => return Err(err.into()),
+            None => {
                 ctxt.trigger.reply(&state.http)
                     .content(&format!("Current calculation mode: **{}**", user_data.ctxt.trig_mode))?
                     .await?;
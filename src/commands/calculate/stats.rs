@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context, Info},
+    database::Database,
+    error::Error,
+    global::State,
+    util::{format_number, Number},
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Summary statistics of a list of numbers.
+struct Summary {
+    /// The number of values.
+    count: usize,
+
+    /// The arithmetic mean.
+    mean: f64,
+
+    /// The median, the average of the two middle values if there are an even number of values.
+    median: f64,
+
+    /// The values that appear most often, in ascending order. This is empty if every value appears
+    /// the same number of times.
+    modes: Vec<f64>,
+
+    /// The population variance.
+    variance: f64,
+
+    /// The sample variance, or [`None`] if there is only one value.
+    sample_variance: Option<f64>,
+
+    /// The smallest value.
+    min: f64,
+
+    /// The largest value.
+    max: f64,
+}
+
+impl Summary {
+    /// Computes the summary statistics of the given values, which must not be empty.
+    fn new(mut values: Vec<f64>) -> Self {
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let median = if count % 2 == 0 {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+
+        // the values are sorted, so equal values are next to each other
+        let mut runs = Vec::<(f64, usize)>::new();
+        for &value in &values {
+            match runs.last_mut() {
+                Some((last, run)) if *last == value => *run += 1,
+                _ => runs.push((value, 1)),
+            }
+        }
+        let longest = runs.iter().map(|(_, run)| *run).max().unwrap_or(0);
+        let modes = if runs.iter().all(|(_, run)| *run == longest) {
+            Vec::new()
+        } else {
+            runs.iter().filter(|(_, run)| *run == longest).map(|(value, _)| *value).collect()
+        };
+
+        let squared_deviations = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>();
+        Self {
+            count,
+            mean,
+            median,
+            modes,
+            variance: squared_deviations / count as f64,
+            sample_variance: (count > 1).then(|| squared_deviations / (count - 1) as f64),
+            min: values[0],
+            max: values[count - 1],
+        }
+    }
+}
+
+/// Calculates summary statistics of a list of numbers: the count, mean, median, mode, minimum,
+/// maximum, and the population and sample variance and standard deviation. Numbers can be
+/// separated by spaces or commas.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["stats", "statistics"],
+    syntax = ["<number>..."],
+    examples = ["1 2 3 4", "2.5, 3, 3, 10"],
+)]
+pub struct Stats;
+
+#[async_trait]
+impl Command for Stats {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut values = Vec::new();
+        for raw in ctxt.raw_input.split(|c: char| c.is_whitespace() || c == ',') {
+            if raw.is_empty() {
+                continue;
+            }
+            match raw.parse::<Number>() {
+                Ok(Number(value)) if value.is_finite() => values.push(value),
+                _ => {
+                    ctxt.trigger.unattributed().reply(&state.http)
+                        .content(&format!("**`{}` is not a finite number.**", raw))?
+                        .await?;
+                    return Ok(());
+                },
+            }
+        }
+
+        // with nothing to summarize, show how to use the command instead
+        if values.is_empty() {
+            ctxt.trigger.unattributed().reply(&state.http)
+                .embeds(&[self.info().build_embed(ctxt.prefix)])?
+                .await?;
+            return Ok(());
+        }
+
+        let settings = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .clone();
+        let fmt = |value: f64| format_number(value, settings.notation, settings.group_digits);
+
+        let summary = Summary::new(values);
+        let modes = if summary.modes.is_empty() {
+            "none".to_owned()
+        } else {
+            summary.modes.iter().map(|&mode| fmt(mode)).collect::<Vec<_>>().join(", ")
+        };
+        let (sample_variance, sample_deviation) = match summary.sample_variance {
+            Some(variance) => (fmt(variance), fmt(variance.sqrt())),
+            None => ("undefined".to_owned(), "undefined".to_owned()),
+        };
+        let output = format!(
+            "**Statistics** of {} numbers\n**Mean**: {}\n**Median**: {}\n**Mode**: {}\n**Min**: {}\n**Max**: {}\n**Variance**: {} (population), {} (sample)\n**Standard deviation**: {} (population), {} (sample)",
+            summary.count,
+            fmt(summary.mean),
+            fmt(summary.median),
+            modes,
+            fmt(summary.min),
+            fmt(summary.max),
+            fmt(summary.variance),
+            sample_variance,
+            fmt(summary.variance.sqrt()),
+            sample_deviation,
+        );
+
+        ctxt.trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_odd_count() {
+        let summary = Summary::new(vec![9.0, 1.0, 5.0]);
+        assert_eq!(summary.count, 3);
+        assert_eq!(summary.mean, 5.0);
+        assert_eq!(summary.median, 5.0);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 9.0);
+    }
+
+    #[test]
+    fn summarize_even_count() {
+        let summary = Summary::new(vec![4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.variance, 1.25);
+        assert_eq!(summary.sample_variance, Some(5.0 / 3.0));
+    }
+
+    #[test]
+    fn modes_are_sorted() {
+        let summary = Summary::new(vec![3.0, 1.0, 3.0, 2.0, 1.0]);
+        assert_eq!(summary.modes, vec![1.0, 3.0]);
+    }
+
+    #[test]
+    fn no_mode_when_all_values_tie() {
+        assert!(Summary::new(vec![1.0, 2.0, 3.0]).modes.is_empty());
+        assert!(Summary::new(vec![1.0, 1.0, 2.0, 2.0]).modes.is_empty());
+    }
+
+    #[test]
+    fn single_value() {
+        let summary = Summary::new(vec![7.0]);
+        assert_eq!(summary.median, 7.0);
+        assert_eq!(summary.variance, 0.0);
+        assert_eq!(summary.sample_variance, None);
+        assert!(summary.modes.is_empty());
+    }
+}
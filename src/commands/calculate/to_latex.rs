@@ -5,14 +5,31 @@ use cas_parser::parser::{ast::expr::Expr, fmt::Latex, Parser};
 use crate::{
     commands::{Command, Context},
     database::Database,
-    error::Error,
+    error::{CasErrors, Error},
     global::State,
 };
-use strip_ansi_escapes::strip;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, ImageSource};
 
-/// Converts an expression to LaTeX.
+/// The URL of the service used to render LaTeX as an image. The percent-encoded LaTeX is appended
+/// to it.
+const RENDER_URL: &str = "https://latex.codecogs.com/png.image?";
+
+/// Returns the URL of an image of the given LaTeX, rendered in black on a white background so that
+/// it is readable in both light and dark themes.
+fn render_url(latex: &str) -> String {
+    let encoded = format!("\\dpi{{200}}\\bg{{white}}{}", latex)
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect::<String>();
+    format!("{}{}", RENDER_URL, encoded)
+}
+
+/// Converts an expression to LaTeX, and shows an image of the rendered LaTeX.
 #[derive(Clone, Info)]
 #[info(
     aliases = ["tolatex", "tolat", "latex", "tl"],
@@ -32,28 +49,69 @@ impl Command for ToLatex {
         let mut parser = Parser::new(ctxt.raw_input);
         match parser.try_parse_full::<Expr>() {
             Ok(expr) => {
+                let latex = expr.as_display().to_string();
+                let embed = EmbedBuilder::new()
+                    .color(0x66d2e8)
+                    .image(ImageSource::url(render_url(&latex))?)
+                    .build();
                 ctxt.trigger.reply(&state.http)
-                    .content(&format!("**Converting** `{}` to LaTeX\n```{}```", ctxt.raw_input, expr.as_display()))?
+                    .content(&format!("**Converting** `{}` to LaTeX\n```{}```", ctxt.raw_input, latex))?
+                    .embeds(&[embed])?
                     .await?;
             },
             Err(errs) => {
-                let msg = errs.into_iter()
-                    .map(|err| {
-                        let mut buf = Vec::new();
-                        err.build_report()
-                            .write(("input", Source::from(ctxt.raw_input)), &mut buf)
-                            .unwrap();
-                        String::from_utf8(strip(buf).unwrap()).unwrap()
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n");
-
-                ctxt.trigger.reply(&state.http)
-                    .content(&format!("```{}```", msg))?
-                    .await?;
+                return Err(CasErrors::new(errs.into_iter()
+                    .map(|err| CasErrors::render_report(|buf| {
+                        err.build_report().write(("input", Source::from(ctxt.raw_input)), buf)
+                    }))
+                    .collect())
+                    .into());
             },
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the percent-encoded LaTeX of a render URL.
+    fn decode(url: &str) -> String {
+        let encoded = url.strip_prefix(RENDER_URL).unwrap().as_bytes();
+        let mut bytes = Vec::new();
+        let mut i = 0;
+        while i < encoded.len() {
+            if encoded[i] == b'%' {
+                let hex = std::str::from_utf8(&encoded[i + 1..i + 3]).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                i += 3;
+            } else {
+                bytes.push(encoded[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn reserved_characters_are_encoded() {
+        assert_eq!(
+            render_url("x^2 + 1"),
+            format!("{}%5Cdpi%7B200%7D%5Cbg%7Bwhite%7Dx%5E2%20%2B%201", RENDER_URL),
+        );
+        assert_eq!(render_url("π"), format!("{}%5Cdpi%7B200%7D%5Cbg%7Bwhite%7D%CF%80", RENDER_URL));
+    }
+
+    #[test]
+    fn expression_round_trips_through_url() {
+        let expr = Parser::new("x^2 + 1/y").try_parse_full::<Expr>().unwrap();
+        let latex = expr.as_display().to_string();
+        let url = render_url(&latex);
+
+        let query = url.strip_prefix(RENDER_URL).unwrap();
+        assert!(query.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"-_.~%".contains(&byte)));
+        assert_eq!(decode(&url), format!("\\dpi{{200}}\\bg{{white}}{}", latex));
+    }
+}
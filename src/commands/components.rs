@@ -0,0 +1,91 @@
+//! Helpers for building message components, filling in the fields that CalcBot's components
+//! always leave at their defaults.
+
+use crate::custom_id::{CustomId, CustomIdError};
+use twilight_model::channel::message::{
+    component::{ActionRow, Button, ButtonStyle, SelectMenu},
+    Component,
+    ReactionType,
+};
+
+/// Creates an enabled button with the given custom ID, label, and style, and an optional unicode
+/// emoji shown before the label.
+pub fn button(
+    custom_id: CustomId,
+    label: &str,
+    emoji: Option<&str>,
+    style: ButtonStyle,
+) -> Result<Button, CustomIdError> {
+    Ok(Button {
+        custom_id: Some(custom_id.encode()?),
+        disabled: false,
+        emoji: emoji.map(|emoji| ReactionType::Unicode {
+            name: emoji.to_owned(),
+        }),
+        label: Some(label.to_owned()),
+        style,
+        url: None,
+    })
+}
+
+/// Creates an action row containing the given buttons.
+pub fn action_row(buttons: impl IntoIterator<Item = Button>) -> Component {
+    Component::ActionRow(ActionRow {
+        components: buttons.into_iter().map(Component::Button).collect(),
+    })
+}
+
+/// Returns the given components with every button and select menu disabled, such as when the
+/// message they belong to has expired.
+pub fn disable_all(components: Vec<Component>) -> Vec<Component> {
+    components.into_iter()
+        .map(|component| match component {
+            Component::ActionRow(row) => Component::ActionRow(ActionRow {
+                components: disable_all(row.components),
+            }),
+            Component::Button(button) => Component::Button(Button { disabled: true, ..button }),
+            Component::SelectMenu(menu) => Component::SelectMenu(SelectMenu { disabled: true, ..menu }),
+            component => component,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_button() {
+        let built = button(CustomId::new("pager", "next"), "Next", Some("▶️"), ButtonStyle::Primary).unwrap();
+        assert_eq!(built, Button {
+            custom_id: Some("pager:next".to_owned()),
+            disabled: false,
+            emoji: Some(ReactionType::Unicode { name: "▶️".to_owned() }),
+            label: Some("Next".to_owned()),
+            style: ButtonStyle::Primary,
+            url: None,
+        });
+    }
+
+    #[test]
+    fn invalid_custom_id_is_rejected() {
+        let custom_id = CustomId::new("pager", "next").with_payload(&"a".repeat(100));
+        assert!(button(custom_id, "Next", None, ButtonStyle::Primary).is_err());
+    }
+
+    #[test]
+    fn builds_action_row() {
+        let yes = button(CustomId::new("confirm", "yes"), "Yes", None, ButtonStyle::Success).unwrap();
+        let no = button(CustomId::new("confirm", "no"), "No", None, ButtonStyle::Danger).unwrap();
+        assert_eq!(action_row([yes.clone(), no.clone()]), Component::ActionRow(ActionRow {
+            components: vec![Component::Button(yes), Component::Button(no)],
+        }));
+    }
+
+    #[test]
+    fn disables_nested_buttons() {
+        let yes = button(CustomId::new("confirm", "yes"), "Yes", None, ButtonStyle::Success).unwrap();
+        let disabled = disable_all(vec![action_row([yes.clone()])]);
+        assert_eq!(disabled, vec![action_row([Button { disabled: true, ..yes }])]);
+    }
+}
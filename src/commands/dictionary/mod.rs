@@ -0,0 +1,602 @@
+pub mod synonyms;
+
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context, Reply, TriggerEvent},
+    database::Database,
+    error::Error,
+    global::State,
+    util::pluralize,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    future::IntoFuture,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use twilight_http::response::ResponseFuture;
+use twilight_model::{
+    channel::message::{embed::{Embed, EmbedField}, Message},
+    id::{marker::{ChannelMarker, MessageMarker}, Id},
+};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+use twilight_validate::message::MessageValidationError;
+
+/// A language code supported by the Google Dictionary API.
+type LanguageCode<'a> = &'a str;
+
+/// All languages codes supported by the Google Dictionary API.
+const LANGUAGES: [LanguageCode<'static>; 12] = [
+    "en", "hi", "es", "fr", "ru", "de", "it", "ko", "pt-BR", "zh-CN", "ar", "tr",
+];
+
+/// The maximum length of a thread name, in characters.
+const MAX_THREAD_NAME_LENGTH: usize = 100;
+
+/// The maximum number of fields in a single embed.
+const MAX_EMBED_FIELDS: usize = 25;
+
+/// The maximum number of embeds in a single message.
+const MAX_EMBEDS: usize = 10;
+
+/// The maximum number of pronunciation recordings linked.
+const MAX_RECORDINGS: usize = 5;
+
+/// The maximum number of words kept in the dictionary cache.
+const MAX_CACHED_ENTRIES: usize = 500;
+
+/// How long to wait for the dictionary API to respond before giving up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SUPERSCRIPT_NUMBERS: [&str; 10] = ["⁰", "¹", "²", "³", "⁴", "⁵", "⁶", "⁷", "⁸", "⁹"];
+
+/// Returns the given number in superscript.
+fn fmt_superscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|digit| SUPERSCRIPT_NUMBERS[digit as usize - '0' as usize])
+        .collect::<String>()
+}
+
+/// Represents a semantic domain of a word or phrase, a grouping of related meanings for a word.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Domain {
+    /// The word or phrase.
+    word: String,
+
+    /// The phonetic spelling of the word or phrase, if known.
+    #[serde(default)]
+    phonetic: Option<String>,
+
+    /// The phonetic spellings and pronunciation recordings of the word or phrase. This can be
+    /// empty.
+    #[serde(default)]
+    phonetics: Vec<Phonetic>,
+
+    /// The meanings of the word or phrase.
+    meanings: Vec<Meaning>,
+}
+
+/// Represents a pronunciation of a word or phrase.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Phonetic {
+    /// The phonetic spelling of the pronunciation, if known.
+    #[serde(default)]
+    text: Option<String>,
+
+    /// The URL of a recording of the pronunciation. This can be empty.
+    #[serde(default)]
+    audio: Option<String>,
+}
+
+/// Represents a specific meaning of a word or phrase.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Meaning {
+    /// The part of speech of the word or phrase.
+    #[serde(rename = "partOfSpeech")]
+    part_of_speech: String,
+
+    /// The definitions of the word or phrase.
+    definitions: Vec<Definition>,
+
+    /// The synonyms of the word or phrase. This can be empty.
+    synonyms: Vec<String>,
+
+    /// The antonyms of the word or phrase. This can be empty.
+    antonyms: Vec<String>,
+}
+
+/// Represents a specific definition of a word or phrase.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Definition {
+    /// The definition of the word or phrase.
+    definition: String,
+
+    /// Synonyms of the specific definition. This can be empty.
+    synonyms: Vec<String>,
+
+    /// Antonyms of the specific definition. This can be empty.
+    antonyms: Vec<String>,
+
+    /// The example of the word or phrase.
+    example: Option<String>,
+}
+
+/// A bounded cache of dictionary entries that have already been fetched, keyed by language code and
+/// lowercase word. When the cache is full, the oldest entry is evicted.
+#[derive(Default)]
+struct Cache {
+    /// The cached entries.
+    entries: HashMap<(String, String), Vec<Domain>>,
+
+    /// The keys of the cached entries, from oldest to newest.
+    order: VecDeque<(String, String)>,
+}
+
+impl Cache {
+    /// Returns the cached entry of the given word in the given language, if any.
+    fn get(&self, language: &str, word: &str) -> Option<Vec<Domain>> {
+        self.entries.get(&(language.to_owned(), word.to_lowercase())).cloned()
+    }
+
+    /// Caches the entry of the given word in the given language, evicting the oldest entry if the
+    /// cache is full.
+    fn insert(&mut self, language: &str, word: &str, domains: Vec<Domain>) {
+        let key = (language.to_owned(), word.to_lowercase());
+        if self.entries.insert(key.clone(), domains).is_some() {
+            return;
+        }
+
+        self.order.push_back(key);
+        if self.order.len() > MAX_CACHED_ENTRIES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Cache of words that have already been searched for.
+    static ref CACHE: StdMutex<Cache> = StdMutex::new(Cache::default());
+
+    /// The HTTP client used to fetch dictionary entries, which gives up after [`REQUEST_TIMEOUT`].
+    static ref CLIENT: Client = Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .unwrap();
+}
+
+/// Generic error type for we failed to fetch a word or phrase from the Google Dictionary API.
+#[derive(Debug)]
+enum FetchError {
+    /// The language code was invalid.
+    InvalidLanguageCode(String),
+
+    /// The word or phrase was not found in the given language.
+    NotFound(String, String),
+
+    /// An error occurred while fetching the word or phrase.
+    Reqwest,
+
+    /// The dictionary API took too long to respond.
+    Timeout,
+}
+
+impl Error for FetchError {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        match self {
+            FetchError::InvalidLanguageCode(language) => Ok(init.content(&format!("**The language code `{}` is invalid.** See [this link](<https://chillant.gitbook.io/calcbot/commands/dictionary>) for a list of valid language codes.", language))?.into_future()),
+            FetchError::NotFound(word, language) => Ok(init.content(&format!("**Could not find a dictionary entry for `{}` in the `{}` dictionary.**", word, language))?.into_future()),
+            FetchError::Reqwest => Ok(init.content("**An error occurred while fetching the definition. Please try again in a few seconds.**")?.into_future()),
+            FetchError::Timeout => Ok(init.content("**The dictionary took too long to respond. Please try again in a few seconds.**")?.into_future()),
+        }
+    }
+}
+
+/// Returns the name of a thread for the given word, truncated to fit Discord's thread name limit.
+fn thread_name(word: &str) -> String {
+    word.chars().take(MAX_THREAD_NAME_LENGTH).collect()
+}
+
+/// Returns the thread the entry was posted in, and the reply pointing to it, given the thread
+/// created off the invoking message. Returns [`None`] if the thread could not be created, in which
+/// case the entry is posted in the reply instead.
+fn thread_reply(summary: &str, thread_id: Option<Id<ChannelMarker>>) -> Option<(Id<ChannelMarker>, String)> {
+    thread_id.map(|thread_id| (thread_id, format!("{} See <#{}> for the full entry.", summary, thread_id)))
+}
+
+/// Returns the distinct phonetic spellings and pronunciation recording URLs of the given entries,
+/// in the order they appear.
+fn pronunciations(entries: &[Domain]) -> (Vec<String>, Vec<String>) {
+    let mut spellings = Vec::new();
+    let mut recordings = Vec::new();
+    for domain in entries {
+        let texts = domain.phonetic.iter()
+            .chain(domain.phonetics.iter().filter_map(|phonetic| phonetic.text.as_ref()));
+        for text in texts {
+            if !text.is_empty() && !spellings.contains(text) {
+                spellings.push(text.clone());
+            }
+        }
+
+        for audio in domain.phonetics.iter().filter_map(|phonetic| phonetic.audio.as_ref()) {
+            if !audio.is_empty() && !recordings.contains(audio) {
+                recordings.push(audio.clone());
+            }
+        }
+    }
+    (spellings, recordings)
+}
+
+/// Splits the fields of a dictionary entry across as many embeds as needed, since each embed can
+/// hold at most [`MAX_EMBED_FIELDS`] fields. Only the first embed is titled, and has the given
+/// description.
+fn build_embeds(word: &str, description: Option<String>, fields: Vec<EmbedField>) -> Vec<Embed> {
+    let mut embeds = fields.chunks(MAX_EMBED_FIELDS)
+        .take(MAX_EMBEDS)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut embed = EmbedBuilder::new().color(0x3468eb);
+            if i == 0 {
+                embed = embed.title(word);
+                if let Some(description) = &description {
+                    embed = embed.description(description);
+                }
+            }
+            chunk.iter()
+                .cloned()
+                .fold(embed, |embed, field| embed.field(field))
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    if embeds.is_empty() {
+        let mut embed = EmbedBuilder::new().title(word).color(0x3468eb);
+        if let Some(description) = description {
+            embed = embed.description(description);
+        }
+        embeds.push(embed.build());
+    }
+
+    embeds
+}
+
+/// Creates a public thread off the given message, named after the word. Returns [`None`] if the
+/// thread could not be created.
+async fn create_thread(
+    state: &State,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+    word: &str,
+) -> Option<Id<ChannelMarker>> {
+    let name = thread_name(word);
+    let thread = state.http.create_thread_from_message(channel_id, message_id, &name)
+        .ok()?
+        .await
+        .map_err(|err| log::info!("could not create dictionary thread: {}", err))
+        .ok()?
+        .model()
+        .await
+        .ok()?;
+    Some(thread.id)
+}
+
+/// Fetch the Google Dictionary entry of a word or phrase, using the cache if possible.
+async fn get_dictionary_entry<'a>(
+    word: &'a str,
+    language: LanguageCode<'a>,
+) -> Result<Vec<Domain>, FetchError> {
+    if !LANGUAGES.contains(&language) {
+        return Err(FetchError::InvalidLanguageCode(language.to_string()).into());
+    }
+
+    if let Some(domains) = CACHE.lock().unwrap().get(language, word) {
+        return Ok(domains);
+    }
+
+    let url = format!(
+        "https://api.dictionaryapi.dev/api/v2/entries/{}/{}",
+        language, word
+    );
+    let response = CLIENT.get(&url)
+        .send()
+        .await
+        .map_err(|err| match err.is_timeout() {
+            true => FetchError::Timeout,
+            false => FetchError::Reqwest,
+        })?
+        .json::<Vec<Domain>>()
+        .await
+        .map_err(|err| match err.is_timeout() {
+            true => FetchError::Timeout,
+            false => FetchError::NotFound(word.to_string(), language.to_string()),
+        })?;
+    CACHE.lock().unwrap().insert(language, word, response.clone());
+    Ok(response)
+}
+
+/// Splits the raw input of a dictionary command into the word or phrase to look up, and the
+/// language code, which is the last word if more than one word is given, or `en` otherwise.
+fn parse_input(raw_input: &str) -> Result<(String, String), &'static str> {
+    let raw_args = raw_input.split_whitespace().collect::<Vec<&str>>();
+    match raw_args.split_last() {
+        Some((last, remainder)) => {
+            if raw_args.len() > 1 {
+                Ok((remainder.join(" "), last.to_ascii_lowercase()))
+            } else {
+                Ok((raw_args[0].to_string(), "en".to_string()))
+            }
+        },
+        None => Err("**You must provide a word or phrase to search for.**"),
+    }
+}
+
+/// Get the Google Dictionary entry of a word or phrase. You may also provide a [language
+/// code](https://chillant.gitbook.io/calcbot/commands/dictionary) for the second argument to
+/// search that language's dictionary.
+///
+/// **Note: CalcBot will not filter profanity or other words that might be considered offensive.
+/// Use with caution.**
+#[derive(Clone, Info)]
+#[info(
+    category = "Text",
+    aliases = ["dictionary", "define", "dict", "def"],
+    syntax = ["<word | phrase> [language code]"],
+    examples = ["hello", "안녕 ko"],
+    children = [synonyms::Synonyms],
+)]
+pub struct Dictionary;
+
+#[async_trait]
+impl Command for Dictionary {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (word, language) = parse_input(ctxt.raw_input)?;
+
+        let entries = get_dictionary_entry(&word, &language).await?;
+        let (spellings, recordings) = pronunciations(&entries);
+        let mut fields = Vec::new();
+        if !recordings.is_empty() {
+            let links = recordings.iter()
+                .take(MAX_RECORDINGS)
+                .enumerate()
+                .map(|(i, url)| format!("[🔊 Listen {}]({})", i + 1, url))
+                .collect::<Vec<_>>()
+                .join(" • ");
+            fields.push(EmbedFieldBuilder::new("Pronunciation", links).build());
+        }
+
+        for (superscript, domain) in entries.into_iter().enumerate() {
+            let superscript = fmt_superscript(superscript + 1);
+            for meaning in domain.meanings {
+                let mut description = Vec::new();
+                let mut synonym_antonyms = String::new();
+                if !meaning.synonyms.is_empty() {
+                    synonym_antonyms
+                        .push_str(&format!("**Synonyms**: {}", meaning.synonyms.join(", ")));
+                }
+
+                if !meaning.antonyms.is_empty() {
+                    synonym_antonyms
+                        .push_str(&format!("**Antonyms**: {}", meaning.antonyms.join(", ")));
+                }
+
+                if !synonym_antonyms.is_empty() {
+                    description.push(synonym_antonyms);
+                }
+
+                for definition in meaning.definitions {
+                    let mut definition_parts = Vec::new();
+                    definition_parts.push(definition.definition);
+                    if let Some(example) = definition.example {
+                        definition_parts.push(format!("_{}_", example));
+                    }
+                    if !definition.synonyms.is_empty() {
+                        definition_parts
+                            .push(format!("**Synonyms**: {}", definition.synonyms.join(", ")));
+                    }
+                    if !definition.antonyms.is_empty() {
+                        definition_parts
+                            .push(format!("**Antonyms**: {}", definition.antonyms.join(", ")));
+                    }
+                    description.push(definition_parts.join("\n"));
+
+                    // embed fields have a 1024 character limit
+                    // description.len() * 22 is the number of characters used for dividers
+                    if description.iter().fold(0, |acc, x| acc + x.len()) + description.len() * 22 > 1024 {
+                        description.pop();
+                        break;
+                    }
+                }
+
+                fields.push(
+                    EmbedFieldBuilder::new(
+                        format!("{}{}", &meaning.part_of_speech, superscript),
+                        description.join("\n**――――――――――――――――**\n"),
+                    )
+                    .inline()
+                    .build(),
+                );
+            }
+        }
+
+        let meanings = fields.len() - usize::from(!recordings.is_empty());
+        let description = (!spellings.is_empty()).then(|| spellings.join(", "));
+        let embeds = build_embeds(&word, description, fields);
+
+        // if the server opted in, post the entry in a thread, falling back to replying with it
+        // if the thread could not be created (e.g. because CalcBot is missing the Create Public
+        // Threads permission)
+        let dictionary_threads = match ctxt.trigger.guild_id() {
+            Some(guild_id) => database.lock().await
+                .get_server(guild_id).await
+                .dictionary_threads,
+            None => false,
+        };
+        if dictionary_threads {
+            let summary = format!("**{}**: found {}.", word, pluralize(meanings, "meaning"));
+            match ctxt.trigger.event {
+                // the thread starts from the invoking message, and the reply points to it
+                TriggerEvent::Message(msg) => {
+                    let thread_id = create_thread(state, msg.channel_id, msg.id, &word).await;
+                    if let Some((thread_id, content)) = thread_reply(&summary, thread_id) {
+                        state.http.create_message(thread_id)
+                            .embeds(&embeds)?
+                            .await?;
+                        ctxt.trigger.reply(&state.http)
+                            .content(&content)?
+                            .await?;
+                        return Ok(());
+                    }
+                },
+                // slash commands have no invoking message, so the thread starts from the reply
+                TriggerEvent::Interaction(_) => {
+                    let reply = ctxt.trigger.reply(&state.http)
+                        .content(&format!("{} The full entry is in the thread below.", summary))?
+                        .await?
+                        .model()
+                        .await?;
+                    if let Some(thread_id) = create_thread(state, reply.channel_id, reply.id, &word).await {
+                        state.http.create_message(thread_id)
+                            .embeds(&embeds)?
+                            .await?;
+                        return Ok(());
+                    }
+                },
+            }
+        }
+
+        ctxt.trigger.reply(&state.http)
+            .embeds(&embeds)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_thread_names_are_kept() {
+        assert_eq!(thread_name("hello"), "hello");
+    }
+
+    #[test]
+    fn long_thread_names_are_truncated() {
+        let name = thread_name(&"a".repeat(150));
+        assert_eq!(name, "a".repeat(MAX_THREAD_NAME_LENGTH));
+
+        // the limit is in characters, not bytes
+        let name = thread_name(&"안".repeat(150));
+        assert_eq!(name.chars().count(), MAX_THREAD_NAME_LENGTH);
+    }
+
+    #[test]
+    fn reply_points_to_thread() {
+        assert_eq!(
+            thread_reply("**hello**: found 2 meanings.", Some(Id::new(5))),
+            Some((Id::new(5), "**hello**: found 2 meanings. See <#5> for the full entry.".to_owned())),
+        );
+    }
+
+    #[test]
+    fn failed_thread_falls_back_to_reply() {
+        assert_eq!(thread_reply("**hello**: found 2 meanings.", None), None);
+    }
+
+    /// A trimmed response of the dictionary API for "hello", with two entries.
+    const HELLO: &str = r#"[
+        {
+            "word": "hello",
+            "phonetic": "həˈləʊ",
+            "phonetics": [
+                { "text": "həˈləʊ", "audio": "https://example.com/hello-uk.mp3" },
+                { "text": "hɛˈləʊ", "audio": "" },
+                { "audio": "https://example.com/hello-us.mp3" }
+            ],
+            "meanings": [
+                {
+                    "partOfSpeech": "exclamation",
+                    "definitions": [
+                        { "definition": "Used as a greeting.", "synonyms": [], "antonyms": [] }
+                    ],
+                    "synonyms": [],
+                    "antonyms": []
+                }
+            ]
+        },
+        {
+            "word": "hello",
+            "phonetics": [
+                { "text": "hɛˈləʊ", "audio": "https://example.com/hello-uk.mp3" },
+                { "text": "" }
+            ],
+            "meanings": []
+        }
+    ]"#;
+
+    #[test]
+    fn pronunciations_are_deduplicated() {
+        let entries = serde_json::from_str::<Vec<Domain>>(HELLO).unwrap();
+        let (spellings, recordings) = pronunciations(&entries);
+        assert_eq!(spellings, ["həˈləʊ", "hɛˈləʊ"]);
+        assert_eq!(recordings, ["https://example.com/hello-uk.mp3", "https://example.com/hello-us.mp3"]);
+    }
+
+    #[test]
+    fn no_pronunciations() {
+        let entries = serde_json::from_str::<Vec<Domain>>(r#"[{ "word": "hello", "meanings": [] }]"#).unwrap();
+        assert_eq!(pronunciations(&entries), (vec![], vec![]));
+    }
+
+    /// Returns an entry with no meanings for the given word.
+    fn entry(word: &str) -> Vec<Domain> {
+        vec![Domain { word: word.to_owned(), phonetic: None, phonetics: Vec::new(), meanings: Vec::new() }]
+    }
+
+    #[test]
+    fn cache_ignores_case() {
+        let mut cache = Cache::default();
+        cache.insert("en", "Hello", entry("Hello"));
+        assert_eq!(cache.get("en", "HELLO").unwrap()[0].word, "Hello");
+        assert!(cache.get("fr", "hello").is_none());
+    }
+
+    #[test]
+    fn full_cache_evicts_oldest_entry() {
+        let mut cache = Cache::default();
+        for i in 0..MAX_CACHED_ENTRIES {
+            cache.insert("en", &format!("word{}", i), entry("word"));
+        }
+        assert!(cache.get("en", "word0").is_some());
+
+        cache.insert("en", "one more", entry("one more"));
+        assert!(cache.get("en", "word0").is_none());
+        assert!(cache.get("en", "word1").is_some());
+        assert!(cache.get("en", "one more").is_some());
+        assert_eq!(cache.entries.len(), MAX_CACHED_ENTRIES);
+    }
+
+    #[test]
+    fn reinserting_does_not_evict() {
+        let mut cache = Cache::default();
+        for i in 0..MAX_CACHED_ENTRIES {
+            cache.insert("en", &format!("word{}", i), entry("word"));
+        }
+        cache.insert("en", "WORD5", entry("word"));
+        assert!(cache.get("en", "word0").is_some());
+        assert_eq!(cache.order.len(), MAX_CACHED_ENTRIES);
+    }
+}
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::{get_dictionary_entry, parse_input, Domain};
+use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// The maximum length of an embed field value, in characters.
+const MAX_FIELD_LENGTH: usize = 1024;
+
+/// Returns the distinct synonyms and antonyms of every meaning and definition of the given
+/// entries, in the order they appear.
+fn synonyms_antonyms(entries: &[Domain]) -> (Vec<String>, Vec<String>) {
+    let mut synonyms = Vec::new();
+    let mut antonyms = Vec::new();
+    let push_distinct = |list: &mut Vec<String>, words: &[String]| {
+        for word in words {
+            if !list.contains(word) {
+                list.push(word.clone());
+            }
+        }
+    };
+
+    for meaning in entries.iter().flat_map(|domain| &domain.meanings) {
+        push_distinct(&mut synonyms, &meaning.synonyms);
+        push_distinct(&mut antonyms, &meaning.antonyms);
+        for definition in &meaning.definitions {
+            push_distinct(&mut synonyms, &definition.synonyms);
+            push_distinct(&mut antonyms, &definition.antonyms);
+        }
+    }
+    (synonyms, antonyms)
+}
+
+/// Joins the given words into a comma-separated list, leaving out the words that do not fit in an
+/// embed field.
+fn join_to_fit(words: &[String]) -> String {
+    let mut joined = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let separator = if joined.is_empty() { "" } else { ", " };
+
+        // leave room for the trailing note if more words remain
+        let rest = words.len() - i;
+        let note = format!(", and {} more", rest);
+        if joined.chars().count() + separator.len() + word.chars().count() + note.len() > MAX_FIELD_LENGTH {
+            joined.push_str(&note);
+            break;
+        }
+
+        joined.push_str(separator);
+        joined.push_str(word);
+    }
+    joined
+}
+
+/// List the synonyms and antonyms of a word or phrase across all of its meanings, without the
+/// definitions. You may also provide a [language
+/// code](https://chillant.gitbook.io/calcbot/commands/dictionary) for the second argument to
+/// search that language's dictionary.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["synonyms", "thesaurus", "syn"],
+    syntax = ["<word | phrase> [language code]"],
+    examples = ["happy", "feliz es"],
+)]
+pub struct Synonyms;
+
+#[async_trait]
+impl Command for Synonyms {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (word, language) = parse_input(ctxt.raw_input)?;
+
+        let entries = get_dictionary_entry(&word, &language).await?;
+        let (synonyms, antonyms) = synonyms_antonyms(&entries);
+        if synonyms.is_empty() && antonyms.is_empty() {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("No synonyms found for `{}`.", word))?
+                .await?;
+            return Ok(());
+        }
+
+        let mut embed = EmbedBuilder::new()
+            .title(format!("Synonyms of {}", word))
+            .color(0x3468eb);
+        if !synonyms.is_empty() {
+            embed = embed.field(EmbedFieldBuilder::new("Synonyms", join_to_fit(&synonyms)));
+        }
+        if !antonyms.is_empty() {
+            embed = embed.field(EmbedFieldBuilder::new("Antonyms", join_to_fit(&antonyms)));
+        }
+
+        ctxt.trigger.reply(&state.http)
+            .embeds(&[embed.build()])?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A trimmed response of the dictionary API for "happy", with two meanings.
+    const HAPPY: &str = r#"[
+        {
+            "word": "happy",
+            "meanings": [
+                {
+                    "partOfSpeech": "adjective",
+                    "definitions": [
+                        { "definition": "Having a feeling of joy.", "synonyms": ["glad", "joyful"], "antonyms": [] }
+                    ],
+                    "synonyms": ["content", "glad"],
+                    "antonyms": ["sad"]
+                },
+                {
+                    "partOfSpeech": "verb",
+                    "definitions": [
+                        { "definition": "To become happy.", "synonyms": ["cheer"], "antonyms": ["sad", "sadden"] }
+                    ],
+                    "synonyms": ["joyful"],
+                    "antonyms": []
+                }
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn union_across_meanings() {
+        let entries = serde_json::from_str::<Vec<Domain>>(HAPPY).unwrap();
+        let (synonyms, antonyms) = synonyms_antonyms(&entries);
+        assert_eq!(synonyms, ["content", "glad", "joyful", "cheer"]);
+        assert_eq!(antonyms, ["sad", "sadden"]);
+    }
+
+    #[test]
+    fn long_lists_are_cut_off() {
+        let words = (0..500).map(|i| format!("word{}", i)).collect::<Vec<_>>();
+        let joined = join_to_fit(&words);
+        assert!(joined.chars().count() <= MAX_FIELD_LENGTH);
+        assert!(joined.starts_with("word0, word1, "));
+        assert!(joined.ends_with(" more"));
+    }
+}
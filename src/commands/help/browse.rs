@@ -0,0 +1,452 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{components::{action_row, button, disable_all}, Command, Context},
+    custom_id::CustomId,
+    database::Database,
+    error::Error,
+    global::State,
+    handler::{expired, unauthorized},
+    tasks::TaskCategory,
+};
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+use twilight_model::{
+    application::interaction::InteractionData,
+    channel::{
+        message::{
+            component::{ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption, SelectMenuType},
+            Component,
+            Embed,
+        },
+        Message,
+    },
+    gateway::payload::incoming::InteractionCreate,
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{marker::{ChannelMarker, MessageMarker, UserMarker}, Id},
+};
+use twilight_util::builder::{embed::EmbedBuilder, InteractionResponseDataBuilder};
+
+/// The maximum number of options in a select menu.
+const MAX_OPTIONS: usize = 25;
+
+/// The maximum number of select menus in a message. Discord allows 5 action rows, and one is used
+/// for the navigation buttons.
+const MAX_MENUS: usize = 4;
+
+/// The number of commands listed on each page of a category.
+const COMMANDS_PER_PAGE: usize = MAX_OPTIONS * MAX_MENUS;
+
+/// How long a browser can go unused before its menus are disabled.
+const EXPIRY: Duration = Duration::from_secs(5 * 60);
+
+/// A page of the command browser.
+#[derive(Debug, PartialEq, Eq)]
+enum View {
+    /// The list of command categories.
+    Categories,
+
+    /// A page of the commands in a category, starting from 0.
+    Category(String, usize),
+
+    /// The help embed of a command, given by its path.
+    Command(String),
+}
+
+/// Returns the categories of the root commands, skipping disabled commands.
+fn categories(state: &State) -> BTreeSet<&'static str> {
+    state.commands.commands.iter()
+        .map(|command| command.info())
+        .filter(|info| !state.is_disabled(&[info.default_alias()]))
+        .filter_map(|info| info.category)
+        .collect()
+}
+
+/// Returns the category of the root command of the given path.
+fn category_of(state: &State, path: &str) -> Option<&'static str> {
+    let root = path.split_whitespace().next()?;
+    state.commands.commands.iter()
+        .map(|command| command.info())
+        .find(|info| info.default_alias() == root)
+        .and_then(|info| info.category)
+}
+
+/// Returns the paths of every command in the given category, including subcommands, skipping
+/// disabled commands.
+fn category_commands(state: &State, category: &str) -> Vec<String> {
+    state.commands.walk()
+        .into_iter()
+        .filter(|(path, _)| category_of(state, path[0]) == Some(category) && !state.is_disabled(path))
+        .map(|(path, _)| path.join(" "))
+        .collect()
+}
+
+/// Returns the number of pages needed to list the given number of commands.
+fn page_count(commands: usize) -> usize {
+    ((commands + COMMANDS_PER_PAGE - 1) / COMMANDS_PER_PAGE).max(1)
+}
+
+/// Creates an action row with a single button that navigates back in the browser.
+fn back_button(custom_id: CustomId) -> Result<Component, Box<dyn Error + Send + Sync>> {
+    Ok(action_row([button(custom_id, "Back", None, ButtonStyle::Secondary)?]))
+}
+
+/// Creates the navigation buttons of the given page of a category: a button back to the category
+/// list, and if the category has multiple pages, buttons to move between them.
+fn category_navigation(
+    user_id: Id<UserMarker>,
+    category: &str,
+    page: usize,
+    pages: usize,
+) -> Result<Component, Box<dyn Error + Send + Sync>> {
+    let user = user_id.to_string();
+    let mut buttons = vec![button(CustomId::new("help", "home").with_payload(&user), "Back", None, ButtonStyle::Secondary)?];
+    if pages > 1 {
+        let page_button = |label, to: usize, disabled| -> Result<Button, Box<dyn Error + Send + Sync>> {
+            let custom_id = CustomId::new("help", "category").with_payload(&format!("{}:{}:{}", user, category, to));
+            Ok(Button { disabled, ..button(custom_id, label, None, ButtonStyle::Primary)? })
+        };
+        buttons.push(page_button("Previous", page.saturating_sub(1), page == 0)?);
+        buttons.push(page_button("Next", (page + 1).min(pages - 1), page + 1 >= pages)?);
+    }
+    Ok(action_row(buttons))
+}
+
+/// Returns how long it has been since the given message was sent or last edited. Moving through
+/// the browser edits its message, so this is how long the browser has gone unused.
+fn idle_time(message: &Message) -> Duration {
+    let last_activity = message.edited_timestamp.unwrap_or(message.timestamp).as_micros();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as i64;
+    Duration::from_micros(now.saturating_sub(last_activity).max(0) as u64)
+}
+
+/// Disables the menus of the browser in the given message once it has gone unused for [`EXPIRY`].
+/// Clicks on a browser that has expired are also handled by [`handle`], in case this task could not
+/// edit the message.
+async fn expire(
+    state: Arc<State>,
+    channel_id: Id<ChannelMarker>,
+    message_id: Id<MessageMarker>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut wait = EXPIRY;
+    loop {
+        tokio::time::sleep(wait).await;
+        let message = state.http.message(channel_id, message_id).await?.model().await?;
+        let idle = idle_time(&message);
+        if idle >= EXPIRY {
+            state.http.update_message(channel_id, message_id)
+                .components(Some(&disable_all(message.components)))?
+                .await?;
+            return Ok(());
+        }
+        wait = EXPIRY - idle;
+    }
+}
+
+/// Creates select menus with the given options, split across as many menus as needed.
+fn select_menus(
+    user_id: Id<UserMarker>,
+    action: &str,
+    placeholder: &str,
+    options: &[String],
+) -> Result<Vec<Component>, Box<dyn Error + Send + Sync>> {
+    options.chunks(MAX_OPTIONS)
+        .take(MAX_MENUS)
+        .enumerate()
+        .map(|(i, chunk)| {
+            // each component in a message must have a unique custom ID
+            let custom_id = CustomId::new("help", action)
+                .with_payload(&format!("{}:{}", user_id, i))
+                .encode()?;
+            Ok(Component::ActionRow(ActionRow {
+                components: vec![Component::SelectMenu(SelectMenu {
+                    channel_types: None,
+                    custom_id,
+                    disabled: false,
+                    kind: SelectMenuType::Text,
+                    max_values: Some(1),
+                    min_values: Some(1),
+                    options: Some(chunk.iter()
+                        .map(|option| SelectMenuOption {
+                            default: false,
+                            description: None,
+                            emoji: None,
+                            label: option.clone(),
+                            value: option.clone(),
+                        })
+                        .collect()),
+                    placeholder: Some(placeholder.to_owned()),
+                })],
+            }))
+        })
+        .collect()
+}
+
+/// Renders the given page of the browser for the given user. Returns [`None`] if the page no
+/// longer exists, such as a command that was disabled.
+fn render(
+    state: &State,
+    user_id: Id<UserMarker>,
+    prefix: Option<&str>,
+    view: &View,
+) -> Result<Option<(Embed, Vec<Component>)>, Box<dyn Error + Send + Sync>> {
+    let user = user_id.to_string();
+    Ok(Some(match view {
+        View::Categories => {
+            let categories = categories(state).into_iter().map(str::to_owned).collect::<Vec<_>>();
+            let embed = EmbedBuilder::new()
+                .title("Command browser")
+                .color(0x66d2e8)
+                .description("Choose a category to see its commands.")
+                .build();
+            (embed, select_menus(user_id, "category", "Choose a category", &categories)?)
+        },
+        View::Category(category, page) => {
+            let commands = category_commands(state, category);
+            let pages = page_count(commands.len());
+            let Some(shown) = commands.chunks(COMMANDS_PER_PAGE).nth(*page) else {
+                return Ok(None);
+            };
+
+            let title = match pages {
+                1 => format!("{} commands", category),
+                _ => format!("{} commands (page {} of {})", category, page + 1, pages),
+            };
+            let embed = EmbedBuilder::new()
+                .title(title)
+                .color(0x66d2e8)
+                .description(format!("Choose a command to see its help.\n\n`{}`", shown.join("`, `")))
+                .build();
+            let mut components = select_menus(user_id, "command", "Choose a command", shown)?;
+            components.push(category_navigation(user_id, category, *page, pages)?);
+            (embed, components)
+        },
+        View::Command(path) => {
+            let mut input = path.split_whitespace().peekable();
+            let (Some(command), Some(category)) = (state.commands.find_command(&mut input), category_of(state, path)) else {
+                return Ok(None);
+            };
+            if state.is_disabled(&path.split_whitespace().collect::<Vec<_>>()) {
+                return Ok(None);
+            }
+
+            // go back to the page of the category that lists the command
+            let page = category_commands(state, category)
+                .iter()
+                .position(|command| command == path)
+                .map_or(0, |i| i / COMMANDS_PER_PAGE);
+            let back = CustomId::new("help", "category").with_payload(&format!("{}:{}:{}", user, category, page));
+            let components = vec![back_button(back)?];
+            (command.info().build_embed(prefix), components)
+        },
+    }))
+}
+
+/// Determines the page of the browser to show after a click on a component with the given action and
+/// custom ID payload (after the user ID), and the option selected in it, if any. Returns [`None`]
+/// if the click cannot be understood.
+fn next_view(action: &str, extra: Option<&str>, selected: Option<String>) -> Option<View> {
+    match (action, selected) {
+        ("home", _) => Some(View::Categories),
+        ("category", Some(category)) => Some(View::Category(category, 0)),
+        // the back and page buttons store the category and page in the payload
+        ("category", None) => {
+            let (category, page) = extra?.rsplit_once(':')?;
+            Some(View::Category(category.to_owned(), page.parse().ok()?))
+        },
+        ("command", Some(path)) => Some(View::Command(path)),
+        _ => None,
+    }
+}
+
+/// Interaction handler for the `help` namespace, used by the command browser.
+///
+/// The browser is stateless: the page to show is determined entirely by the custom ID and the
+/// selected option. The payload of each custom ID starts with the ID of the user who opened the
+/// browser, and only that user can navigate it. Once the browser has gone unused for [`EXPIRY`],
+/// its menus are disabled instead.
+pub fn handle(
+    state: Arc<State>,
+    database: Arc<Mutex<Database>>,
+    interaction: InteractionCreate,
+    custom_id: CustomId,
+) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>> {
+    Box::pin(async move {
+        if let Some(message) = interaction.message.as_ref().filter(|message| idle_time(message) >= EXPIRY) {
+            state.http.interaction(state.application_id)
+                .create_response(
+                    interaction.id,
+                    &interaction.token,
+                    &InteractionResponse {
+                        kind: InteractionResponseType::UpdateMessage,
+                        data: Some(InteractionResponseDataBuilder::new()
+                            .components(disable_all(message.components.clone()))
+                            .build()),
+                    },
+                )
+                .await?;
+            return Ok(());
+        }
+
+        let payload = custom_id.payload.as_deref().unwrap_or_default();
+        let (user, extra) = match payload.split_once(':') {
+            Some((user, extra)) => (user, Some(extra)),
+            None => (payload, None),
+        };
+        let Some(user_id) = interaction.author_id().filter(|author_id| author_id.to_string() == user) else {
+            return unauthorized(&state, &interaction).await;
+        };
+
+        let selected = match &interaction.data {
+            Some(InteractionData::MessageComponent(data)) => data.values.first().cloned(),
+            _ => None,
+        };
+        let Some(view) = next_view(&custom_id.action, extra, selected) else {
+            return expired(&state, &interaction).await;
+        };
+
+        let prefix = match interaction.guild_id {
+            Some(guild_id) => Some(database.lock().await.get_server(guild_id).await.prefix.clone()),
+            None => None,
+        };
+        let Some((embed, components)) = render(&state, user_id, prefix.as_deref(), &view)? else {
+            return expired(&state, &interaction).await;
+        };
+
+        state.http.interaction(state.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::UpdateMessage,
+                    data: Some(InteractionResponseDataBuilder::new()
+                        .embeds(vec![embed])
+                        .components(components)
+                        .build()),
+                },
+            )
+            .await?;
+
+        Ok(())
+    })
+}
+
+/// Browse CalcBot's commands by category, using select menus. Only you can use the menus on the
+/// browser you open, and they stop working after 5 minutes without use.
+#[derive(Clone, Info)]
+#[info(aliases = ["browse", "br"])]
+pub struct Browse;
+
+#[async_trait]
+impl Command for Browse {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (embed, components) = render(state, ctxt.trigger.author_id(), ctxt.prefix, &View::Categories)?
+            .expect("the category list always exists");
+
+        let message = ctxt.trigger.reply(&state.http)
+            .embeds(&[embed])?
+            .components(&components)?
+            .await?
+            .model()
+            .await?;
+
+        let task_state = Arc::clone(state);
+        state.tasks.spawn(TaskCategory::Browser, expire(task_state, message.channel_id, message.id));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_of_commands() {
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(COMMANDS_PER_PAGE), 1);
+        assert_eq!(page_count(COMMANDS_PER_PAGE + 1), 2);
+    }
+
+    #[test]
+    fn navigate_category_and_back() {
+        assert_eq!(next_view("home", None, None), Some(View::Categories));
+        assert_eq!(
+            next_view("category", Some("0"), Some("Text".to_owned())),
+            Some(View::Category("Text".to_owned(), 0)),
+        );
+        assert_eq!(
+            next_view("command", Some("1"), Some("notmath case".to_owned())),
+            Some(View::Command("notmath case".to_owned())),
+        );
+        assert_eq!(next_view("category", Some("Text:1"), None), Some(View::Category("Text".to_owned(), 1)));
+    }
+
+    #[test]
+    fn malformed_clicks_are_rejected() {
+        assert_eq!(next_view("category", None, None), None);
+        assert_eq!(next_view("category", Some("Text"), None), None);
+        assert_eq!(next_view("command", None, None), None);
+        assert_eq!(next_view("unknown", None, None), None);
+    }
+
+    #[test]
+    fn page_buttons_have_unique_ids() {
+        let user_id = Id::new(1);
+        for page in 0..3 {
+            let Component::ActionRow(row) = category_navigation(user_id, "Text", page, 3).unwrap() else {
+                panic!("navigation should be an action row");
+            };
+            let buttons = row.components.iter()
+                .map(|component| match component {
+                    Component::Button(button) => button.clone(),
+                    _ => panic!("navigation should only have buttons"),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(buttons.len(), 3);
+            assert_ne!(buttons[1].custom_id, buttons[2].custom_id);
+            assert_eq!(buttons[1].disabled, page == 0);
+            assert_eq!(buttons[2].disabled, page == 2);
+        }
+    }
+
+    #[test]
+    fn single_page_has_only_back_button() {
+        let Component::ActionRow(row) = category_navigation(Id::new(1), "Text", 0, 1).unwrap() else {
+            panic!("navigation should be an action row");
+        };
+        assert_eq!(row.components.len(), 1);
+    }
+
+    #[test]
+    fn expired_components_are_disabled() {
+        let components = disable_all(vec![
+            back_button(CustomId::new("help", "home")).unwrap(),
+            category_navigation(Id::new(1), "Text", 0, 2).unwrap(),
+        ]);
+        for component in components {
+            let Component::ActionRow(row) = component else {
+                panic!("components should be action rows");
+            };
+            for component in row.components {
+                let Component::Button(button) = component else {
+                    panic!("rows should only have buttons");
+                };
+                assert!(button.disabled);
+            }
+        }
+    }
+}
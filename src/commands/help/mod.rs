@@ -1,3 +1,4 @@
+pub mod browse;
 pub mod commands;
 
 use async_trait::async_trait;
@@ -31,12 +32,16 @@ A command's help embed contains the following information:
 - **Aliases**: A list of alternative (usually shorter) names for the command which you can use to trigger the command if you'd prefer.
 - **Children commands**: If the command has subcommands, they are listed here.
 
-For a list of all commands, run `{prefix}help commands`.",
+For a list of all commands, run `{prefix}help commands`, or browse them by category with `{prefix}help browse`.",
             category: Some("Resources"),
             aliases: Some(&["help", "h"]),
             syntax: Some(&["[command]"]),
             examples: Some(&["calculate stats"]),
-            children: vec![Box::new(commands::Commands) as Box<dyn Command>].into(),
+            children: vec![
+                Box::new(browse::Browse) as Box<dyn Command>,
+                Box::new(commands::Commands),
+            ].into(),
+            deprecated: None,
         }
     }
 }
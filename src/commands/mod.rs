@@ -1,19 +1,35 @@
 pub mod about;
+pub mod admin;
 pub mod calculate;
+pub mod components;
 pub mod dictionary;
 pub mod help;
 pub mod link;
 pub mod not_math;
+pub mod ping;
+pub mod prefix;
 pub mod remind;
+pub mod settings;
 pub mod unit_convert;
 
 use super::{database::Database, error::Error, global::State};
 use async_trait::async_trait;
-use std::{iter::Peekable, sync::Arc};
+use chrono::{DateTime, TimeZone, Utc};
+use std::{future::IntoFuture, iter::Peekable, sync::Arc};
 use tokio::sync::Mutex;
-use twilight_http::{request::channel::message::CreateMessage, Client};
-use twilight_model::{channel::message::{Embed, Message}, id::{marker::{ChannelMarker, UserMarker}, Id}};
-use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+use twilight_http::{
+    request::{application::interaction::CreateFollowup, channel::message::CreateMessage},
+    response::{Response, ResponseFuture},
+    Client,
+};
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::message::{Component, Embed, Message},
+    http::attachment::Attachment,
+    id::{marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker}, Id},
+};
+use twilight_validate::message::{content as validate_content, embeds as validate_embeds, MessageValidationError};
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
 
 /// Formats a list of commands into a code block. Each string is displayed on a separate line,
 /// prepended with the given prefix.
@@ -82,6 +98,86 @@ impl CommandGroup {
         }
     }
 
+    /// Resolves the given input aliases to the path of the command they refer to, as the default
+    /// aliases of the command and its parents, in order. Only the aliases that are part of the
+    /// path are consumed, like in [`CommandGroup::find_command`].
+    pub fn find_path<'a, T>(&self, input: &mut Peekable<T>) -> Vec<&'static str>
+    where
+        T: Iterator<Item = &'a str>,
+    {
+        let Some(alias) = input.peek() else {
+            return Vec::new();
+        };
+        let Some(command) = self.commands.iter().find(|command| command.info().is_alias(alias)) else {
+            return Vec::new();
+        };
+        input.next();
+
+        let info = command.info();
+        let mut path = vec![info.default_alias()];
+        path.extend(info.children.find_path(input));
+        path
+    }
+
+    /// Resolves a space-separated command path, like `nm unscramble`, to the default aliases of
+    /// the command and its parents, joined by spaces. Returns [`None`] if the path does not refer
+    /// to a command.
+    pub fn resolve_path(&self, path: &str) -> Option<String> {
+        let mut input = path.split_whitespace().peekable();
+        let resolved = self.find_path(&mut input);
+        if resolved.is_empty() || input.next().is_some() {
+            None
+        } else {
+            Some(resolved.join(" "))
+        }
+    }
+
+    /// Walks the command tree depth-first, returning the path to each command (the default
+    /// aliases of the command and its parents, in order) along with its metadata.
+    pub fn walk(&self) -> Vec<(Vec<&'static str>, CommandInfo)> {
+        let mut result = Vec::new();
+
+        for command in &self.commands {
+            let info = command.info();
+            let alias = info.default_alias();
+            let children = info.children.walk();
+
+            result.push((vec![alias], info));
+            result.extend(children.into_iter().map(|(mut path, info)| {
+                path.insert(0, alias);
+                (path, info)
+            }));
+        }
+
+        result
+    }
+
+    /// Renders a snapshot of the command tree as sorted text, with one line per command describing
+    /// its path, aliases, and category. The output is stable, so two snapshots can be diffed to
+    /// find commands that were added or removed.
+    pub fn snapshot(&self) -> String {
+        let mut lines = self.walk()
+            .into_iter()
+            .map(|(path, info)| format!(
+                "{} | aliases: {} | category: {}",
+                path.join(" "),
+                info.aliases.map(|aliases| aliases.join(", ")).unwrap_or_else(|| info.name.to_owned()),
+                info.category.unwrap_or("-"),
+            ))
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines.join("\n")
+    }
+
+    /// Returns the category of the root command of the given command path.
+    pub fn category(&self, path: &[&str]) -> Option<&'static str> {
+        let root = path.first()?;
+        self.commands.iter()
+            .map(|command| command.info())
+            .find(|info| info.default_alias() == *root)?
+            .category
+    }
+
     /// Count the number of commands in this group.
     pub fn count(&self) -> usize {
         self.commands.len()
@@ -117,6 +213,11 @@ pub struct CommandInfo {
 
     /// The children of this command. This will be displayed in the help embed.
     pub children: CommandGroup,
+
+    /// If the command is deprecated, a note on what to use instead, which can contain the
+    /// `{prefix}` tag. Deprecated commands still run normally, but their help embed shows a
+    /// warning, and users are occasionally told about the deprecation after running them.
+    pub deprecated: Option<&'static str>,
 }
 
 impl CommandInfo {
@@ -152,6 +253,13 @@ impl CommandInfo {
                     self.description.replace("{prefix}", prefix),
                 ));
 
+        if let Some(deprecated) = self.deprecated {
+            embed = embed.field(EmbedFieldBuilder::new(
+                "⚠️ Deprecated",
+                deprecated.replace("{prefix}", prefix),
+            ));
+        }
+
         if let Some(syntax) = self
             .syntax
             .map(|syntax| format_code_block(prefix, self.default_alias(), syntax))
@@ -194,46 +302,358 @@ impl CommandInfo {
     }
 }
 
-/// Some event within Discord that triggered a command.
-///
-/// TODO: this will later be extended with slash command support
+/// Returns the time a Discord object was created, from its snowflake ID.
+fn snowflake_time(id: u64) -> Option<DateTime<Utc>> {
+    /// The first millisecond of 2015, which Discord snowflakes count from.
+    const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+    Utc.timestamp_millis_opt(((id >> 22) + DISCORD_EPOCH) as i64).single()
+}
+
+/// Appends a subdued line with the given attribution to the content of a reply. The content is
+/// truncated if needed so that the attribution fits.
+fn attribute_content(content: &str, attribution: &str) -> String {
+    if content.is_empty() {
+        return format!("_{}_", attribution);
+    }
+
+    let footer = format!("\n_{}_", attribution);
+    let budget = Context::MAX_CONTENT_LENGTH.saturating_sub(footer.chars().count());
+    if content.chars().count() > budget {
+        let truncated = content.chars().take(budget.saturating_sub(1)).collect::<String>();
+        format!("{}…{}", truncated, footer)
+    } else {
+        format!("{}{}", content, footer)
+    }
+}
+
+/// Adds the given attribution to the footer of an embed, after any existing footer text.
+fn attribute_embed(mut embed: Embed, attribution: &str) -> Embed {
+    let footer = match embed.footer.take() {
+        Some(footer) if !footer.text.is_empty() => EmbedFooterBuilder::new(format!("{} • {}", footer.text, attribution)),
+        _ => EmbedFooterBuilder::new(attribution),
+    };
+    embed.footer = Some(footer.build());
+    embed
+}
+
+/// The Discord event that triggered a command.
 #[derive(Clone, Copy, Debug)]
-pub enum Trigger<'a> {
+pub enum TriggerEvent<'a> {
     /// A message was sent in a channel.
     Message(&'a Message),
+
+    /// A slash command was used. The interaction must already have been deferred, so that replies
+    /// can be sent as followups.
+    Interaction(&'a Interaction),
+}
+
+/// Some event within Discord that triggered a command, and how replies to it are sent.
+#[derive(Clone, Copy, Debug)]
+pub struct Trigger<'a> {
+    /// The event that triggered the command.
+    pub event: TriggerEvent<'a>,
+
+    /// Whether replies to the event are attributed to its author, because the server has asked
+    /// for replies to calculation commands to be attributed. See [`Trigger::attribution`].
+    pub attribution_footer: bool,
 }
 
 impl<'a> From<&'a Message> for Trigger<'a> {
     fn from(msg: &'a Message) -> Self {
-        Trigger::Message(msg)
+        Trigger { event: TriggerEvent::Message(msg), attribution_footer: false }
+    }
+}
+
+impl<'a> From<&'a Interaction> for Trigger<'a> {
+    fn from(interaction: &'a Interaction) -> Self {
+        Trigger { event: TriggerEvent::Interaction(interaction), attribution_footer: false }
     }
 }
 
 impl<'a> Trigger<'a> {
+    /// Returns a copy of this trigger whose replies are attributed to its author if
+    /// `attribution_footer` is true.
+    pub fn with_attribution_footer(self, attribution_footer: bool) -> Self {
+        Self { attribution_footer, ..self }
+    }
+
+    /// Returns a copy of this trigger whose replies are never attributed. This should be used for
+    /// error messages and help embeds.
+    pub fn unattributed(self) -> Self {
+        self.with_attribution_footer(false)
+    }
+
     /// Returns the ID of the author who triggered this event.
     pub fn author_id(&self) -> Id<UserMarker> {
-        match self {
-            Trigger::Message(msg) => msg.author.id,
+        match self.event {
+            TriggerEvent::Message(msg) => msg.author.id,
+            TriggerEvent::Interaction(interaction) => interaction.author_id()
+                .expect("slash commands always have an author"),
+        }
+    }
+
+    /// Returns the ID of the guild where this event was triggered, or [`None`] if it was
+    /// triggered in a DM channel.
+    pub fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        match self.event {
+            TriggerEvent::Message(msg) => msg.guild_id,
+            TriggerEvent::Interaction(interaction) => interaction.guild_id,
         }
     }
 
     /// Returns the ID of the channel where this event was triggered.
-    ///
-    /// TODO: this is only used for sending paged messages
     pub fn channel_id(&self) -> Id<ChannelMarker> {
-        match self {
-            Trigger::Message(msg) => msg.channel_id,
+        match self.event {
+            TriggerEvent::Message(msg) => msg.channel_id,
+            TriggerEvent::Interaction(interaction) => interaction.channel.as_ref()
+                .map(|channel| channel.id)
+                .expect("slash commands are always used in a channel"),
         }
     }
 
-    /// Create a reply to this event trigger.
-    pub fn reply<'c>(&self, http: &'c Client) -> CreateMessage<'c> {
-        match self {
-            Trigger::Message(msg) => http.create_message(msg.channel_id),
+    /// Returns a short line attributing this event to its author, like `requested by @user •
+    /// 14:03 UTC`. The time is when the event was created, so that edits to a reply keep the
+    /// same attribution.
+    pub fn attribution(&self) -> String {
+        let (name, time) = match self.event {
+            TriggerEvent::Message(msg) => (
+                msg.author.name.as_str(),
+                Utc.timestamp_opt(msg.timestamp.as_secs(), 0).single(),
+            ),
+            TriggerEvent::Interaction(interaction) => (
+                interaction.author().map(|author| author.name.as_str()).unwrap_or_default(),
+                snowflake_time(interaction.id.get()),
+            ),
+        };
+        let time = time.map(|time| time.format("%H:%M UTC").to_string()).unwrap_or_default();
+        format!("requested by @{} • {}", name, time)
+    }
+
+    /// Create a reply to this event trigger. If [`Trigger::attribution_footer`] is set, the
+    /// reply is attributed to the author of the event when it is sent.
+    pub fn reply<'c>(&self, http: &'c Client) -> Reply<'c> {
+        let request = match self.event {
+            TriggerEvent::Message(msg) => ReplyRequest::Message(http.create_message(msg.channel_id)),
+            TriggerEvent::Interaction(interaction) => ReplyRequest::Followup(
+                http.interaction(interaction.application_id).create_followup(&interaction.token),
+            ),
+        };
+        Reply {
+            request,
+            attribution: self.attribution_footer.then(|| self.attribution()),
+            content: None,
+            embeds: None,
+        }
+    }
+
+    /// Sends a placeholder reply to this event trigger, like `Calculating…`, returning a handle
+    /// that can be used to replace it with the result once it is ready.
+    pub async fn reply_placeholder(
+        &self,
+        http: &Client,
+        content: &str,
+    ) -> Result<SentReply<'a>, Box<dyn Error + Send + Sync>> {
+        let message = self.reply(http)
+            .content(content)?
+            .await?
+            .model()
+            .await?;
+        Ok(self.sent_reply(message.id))
+    }
+
+    /// Returns a handle to a reply to this event trigger that has already been sent, so that it
+    /// can be edited.
+    pub fn sent_reply(&self, message_id: Id<MessageMarker>) -> SentReply<'a> {
+        SentReply {
+            trigger: *self,
+            message_id,
         }
     }
 }
 
+/// The request that sends a [`Reply`].
+enum ReplyRequest<'a> {
+    /// A reply to a message.
+    Message(CreateMessage<'a>),
+
+    /// A reply to a slash command.
+    Followup(CreateFollowup<'a>),
+}
+
+/// A reply to a [`Trigger`], which is sent when awaited.
+///
+/// Replies to messages are sent as new messages in the same channel. Replies to slash commands
+/// are sent as followups to the deferred interaction response; the first followup replaces the
+/// loading state of the response.
+///
+/// If the trigger asked for attribution, the embeds of the reply are attributed in their footers.
+/// Replies without embeds are attributed with a subdued last line of content instead.
+pub struct Reply<'a> {
+    /// The request that sends the reply.
+    request: ReplyRequest<'a>,
+
+    /// The line attributing the reply to its author, if the reply is attributed.
+    attribution: Option<String>,
+
+    /// The content of an attributed reply, which is attributed when it is sent.
+    content: Option<String>,
+
+    /// The embeds of an attributed reply, already attributed.
+    embeds: Option<Vec<Embed>>,
+}
+
+impl<'a> Reply<'a> {
+    /// Set the content of the reply.
+    pub fn content(mut self, content: &'a str) -> Result<Self, MessageValidationError> {
+        if self.attribution.is_some() {
+            // the attributed content is truncated to fit, so it is valid if the content is
+            validate_content(content)?;
+            self.content = Some(content.to_owned());
+            return Ok(self);
+        }
+
+        self.request = match self.request {
+            ReplyRequest::Message(request) => ReplyRequest::Message(request.content(content)?),
+            ReplyRequest::Followup(request) => ReplyRequest::Followup(request.content(content)?),
+        };
+        Ok(self)
+    }
+
+    /// Set the embeds of the reply.
+    pub fn embeds(mut self, embeds: &'a [Embed]) -> Result<Self, MessageValidationError> {
+        if let Some(attribution) = &self.attribution {
+            let embeds = embeds.iter()
+                .map(|embed| attribute_embed(embed.clone(), attribution))
+                .collect::<Vec<_>>();
+            validate_embeds(&embeds)?;
+            self.embeds = Some(embeds);
+            return Ok(self);
+        }
+
+        self.request = match self.request {
+            ReplyRequest::Message(request) => ReplyRequest::Message(request.embeds(embeds)?),
+            ReplyRequest::Followup(request) => ReplyRequest::Followup(request.embeds(embeds)?),
+        };
+        Ok(self)
+    }
+
+    /// Set the message components of the reply.
+    pub fn components(mut self, components: &'a [Component]) -> Result<Self, MessageValidationError> {
+        self.request = match self.request {
+            ReplyRequest::Message(request) => ReplyRequest::Message(request.components(components)?),
+            ReplyRequest::Followup(request) => ReplyRequest::Followup(request.components(components)?),
+        };
+        Ok(self)
+    }
+
+    /// Attach files to the reply.
+    pub fn attachments(mut self, attachments: &'a [Attachment]) -> Result<Self, MessageValidationError> {
+        self.request = match self.request {
+            ReplyRequest::Message(request) => ReplyRequest::Message(request.attachments(attachments)?),
+            ReplyRequest::Followup(request) => ReplyRequest::Followup(request.attachments(attachments)?),
+        };
+        Ok(self)
+    }
+}
+
+impl IntoFuture for Reply<'_> {
+    type Output = Result<Response<Message>, twilight_http::Error>;
+    type IntoFuture = ResponseFuture<Message>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let Reply { mut request, attribution, content, embeds } = self;
+
+        // the attribution goes in the embed footers if there are any, and in the content if not
+        let content = match (&attribution, &embeds) {
+            (Some(attribution), None) => Some(attribute_content(content.as_deref().unwrap_or_default(), attribution)),
+            _ => content,
+        };
+        if let Some(content) = &content {
+            request = match request {
+                ReplyRequest::Message(request) => ReplyRequest::Message(request.content(content)
+                    .expect("attributed content is validated when it is set")),
+                ReplyRequest::Followup(request) => ReplyRequest::Followup(request.content(content)
+                    .expect("attributed content is validated when it is set")),
+            };
+        }
+        if let Some(embeds) = &embeds {
+            request = match request {
+                ReplyRequest::Message(request) => ReplyRequest::Message(request.embeds(embeds)
+                    .expect("attributed embeds are validated when they are set")),
+                ReplyRequest::Followup(request) => ReplyRequest::Followup(request.embeds(embeds)
+                    .expect("attributed embeds are validated when they are set")),
+            };
+        }
+
+        match request {
+            ReplyRequest::Message(request) => request.into_future(),
+            ReplyRequest::Followup(request) => request.into_future(),
+        }
+    }
+}
+
+/// A reply that has already been sent, which can be edited. See [`Trigger::reply_placeholder`].
+#[derive(Clone, Copy, Debug)]
+pub struct SentReply<'a> {
+    /// The event the reply was sent in response to.
+    trigger: Trigger<'a>,
+
+    /// The ID of the reply.
+    pub message_id: Id<MessageMarker>,
+}
+
+impl SentReply<'_> {
+    /// Returns a copy of this handle whose edits are never attributed. See
+    /// [`Trigger::unattributed`].
+    pub fn unattributed(self) -> Self {
+        Self { trigger: self.trigger.unattributed(), ..self }
+    }
+
+    /// Replaces the content of this reply. The new content is attributed like the reply was.
+    pub async fn edit_content(&self, http: &Client, content: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let content = match self.trigger.attribution_footer {
+            true => attribute_content(content, &self.trigger.attribution()),
+            false => content.to_owned(),
+        };
+        match self.trigger.event {
+            TriggerEvent::Message(msg) => {
+                http.update_message(msg.channel_id, self.message_id)
+                    .content(Some(&content))?
+                    .await?;
+            },
+            TriggerEvent::Interaction(interaction) => {
+                http.interaction(interaction.application_id)
+                    .update_followup(&interaction.token, self.message_id)
+                    .content(Some(&content))?
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Replaces the message components of this reply.
+    pub async fn edit_components(
+        &self,
+        http: &Client,
+        components: &[Component],
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        match self.trigger.event {
+            TriggerEvent::Message(msg) => {
+                http.update_message(msg.channel_id, self.message_id)
+                    .components(Some(components))?
+                    .await?;
+            },
+            TriggerEvent::Interaction(interaction) => {
+                http.interaction(interaction.application_id)
+                    .update_followup(&interaction.token, self.message_id)
+                    .components(Some(components))?
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+}
+
 /// The context passed to a command's [`Command::execute`] method. This wraps various fields needed
 /// by most commands in one convenient struct.
 #[derive(Clone, Copy, Debug)]
@@ -247,10 +667,51 @@ pub struct Context<'a> {
 
     /// The user's raw input to the command. This includes only the arguments passed to the command
     /// and does not include the prefix, command name, or any whitespace at the start or end of the
-    /// string.
+    /// string. Newlines in the arguments are preserved.
     pub raw_input: &'a str,
 }
 
+impl<'a> Context<'a> {
+    /// The maximum length of a message's content, in characters.
+    pub const MAX_CONTENT_LENGTH: usize = 2000;
+
+    /// Splits the raw input into the arguments on the same line as the command, and the lines
+    /// that follow it. This is useful for commands that accept pasted multi-line data, like
+    /// `{prefix}nm sort` followed by one item per line.
+    ///
+    /// If the command is alone on its line, the first element is empty and every line of the raw
+    /// input is returned in the second element. Blank lines are skipped.
+    pub fn raw_input_lines(&self) -> (&'a str, Vec<&'a str>) {
+        let starts_on_command_line = match self.trigger.event {
+            TriggerEvent::Message(msg) => starts_on_command_line(&msg.content, self.raw_input),
+            // slash command options have no command line to share
+            TriggerEvent::Interaction(_) => true,
+        };
+        split_input_lines(self.raw_input, starts_on_command_line)
+    }
+}
+
+/// Returns true if the raw input, which is a view into the message content, starts on the same
+/// line as the command.
+fn starts_on_command_line(content: &str, raw_input: &str) -> bool {
+    let offset = (raw_input.as_ptr() as usize)
+        .checked_sub(content.as_ptr() as usize)
+        .filter(|offset| *offset <= content.len())
+        .unwrap_or(0);
+    !content[..offset].contains('\n')
+}
+
+/// Splits the raw input as described in [`Context::raw_input_lines`].
+fn split_input_lines(raw_input: &str, starts_on_command_line: bool) -> (&str, Vec<&str>) {
+    let mut lines = raw_input.lines().map(str::trim);
+    let first = if starts_on_command_line {
+        lines.next().unwrap_or_default()
+    } else {
+        ""
+    };
+    (first, lines.filter(|line| !line.is_empty()).collect())
+}
+
 /// Represents any command that can be executed by a user (accounting for permissions and other
 /// factors).
 #[async_trait]
@@ -292,13 +753,124 @@ pub fn root() -> CommandGroup {
     CommandGroup {
         commands: vec![
             Box::new(about::About),
+            Box::new(admin::Admin),
             Box::new(calculate::Calculate),
             Box::new(dictionary::Dictionary),
             Box::new(help::Help),
             Box::new(link::Link),
             Box::new(not_math::NotMath),
+            Box::new(ping::Ping),
+            Box::new(prefix::Prefix),
             Box::new(remind::Remind),
+            Box::new(settings::Settings),
             Box::new(unit_convert::UnitConvert),
         ],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Splits the input following the 7-byte command `c-sort ` or `c-sort\n` in the content.
+    fn lines(content: &str) -> (&str, Vec<&str>) {
+        let raw_input = content[7..].trim();
+        split_input_lines(raw_input, starts_on_command_line(content, raw_input))
+    }
+
+    #[test]
+    fn input_on_command_line() {
+        assert_eq!(lines("c-sort 9 5 28"), ("9 5 28", vec![]));
+    }
+
+    #[test]
+    fn input_on_following_lines() {
+        assert_eq!(lines("c-sort\n9\n\n5\n28"), ("", vec!["9", "5", "28"]));
+        assert_eq!(lines("c-sort by length\napple\nfig"), ("by length", vec!["apple", "fig"]));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        assert_eq!(lines("c-sort\r\n9\r\n5\r\n"), ("", vec!["9", "5"]));
+        assert_eq!(lines("c-sort desc\r\n9\r\n5"), ("desc", vec!["9", "5"]));
+    }
+
+    const ATTRIBUTION: &str = "requested by @user • 14:03 UTC";
+
+    #[test]
+    fn content_gets_attribution_line() {
+        assert_eq!(
+            attribute_content("**Calculation**\n2", ATTRIBUTION),
+            "**Calculation**\n2\n_requested by @user • 14:03 UTC_",
+        );
+    }
+
+    #[test]
+    fn empty_content_is_only_attribution() {
+        assert_eq!(attribute_content("", ATTRIBUTION), "_requested by @user • 14:03 UTC_");
+    }
+
+    #[test]
+    fn attributed_content_fits_in_message() {
+        for length in [1990, 2000] {
+            let content = "x".repeat(length);
+            let attributed = attribute_content(&content, ATTRIBUTION);
+            assert_eq!(attributed.chars().count(), Context::MAX_CONTENT_LENGTH);
+            assert!(attributed.ends_with(&format!("…\n_{}_", ATTRIBUTION)));
+            assert!(validate_content(&attributed).is_ok());
+        }
+    }
+
+    #[test]
+    fn embed_gets_attribution_footer() {
+        let embed = attribute_embed(EmbedBuilder::new().title("Result").build(), ATTRIBUTION);
+        assert_eq!(embed.footer.unwrap().text, ATTRIBUTION);
+    }
+
+    #[test]
+    fn embed_footer_is_kept() {
+        let embed = EmbedBuilder::new()
+            .footer(EmbedFooterBuilder::new("Page 1 of 2"))
+            .build();
+        let embed = attribute_embed(embed, ATTRIBUTION);
+        assert_eq!(embed.footer.unwrap().text, format!("Page 1 of 2 • {}", ATTRIBUTION));
+    }
+
+    #[test]
+    fn snowflake_creation_time() {
+        // the example from Discord's documentation
+        let time = snowflake_time(175928847299117063).unwrap();
+        assert_eq!(time.timestamp_millis(), 1462015105796);
+    }
+
+    /// The checked-in snapshot of the command tree. Run the tests with `UPDATE_SNAPSHOT=1` to
+    /// regenerate it after adding, removing, or renaming a command.
+    const SNAPSHOT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/command_tree.snap");
+
+    #[test]
+    fn command_tree_matches_snapshot() {
+        let snapshot = root().snapshot();
+        if std::env::var_os("UPDATE_SNAPSHOT").is_some() {
+            std::fs::write(SNAPSHOT_PATH, &snapshot).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(SNAPSHOT_PATH).unwrap();
+        let expected = expected.trim_end();
+        if snapshot == expected {
+            return;
+        }
+
+        let removed = expected.lines()
+            .filter(|line| !snapshot.lines().any(|other| other == *line))
+            .map(|line| format!("- {}", line));
+        let added = snapshot.lines()
+            .filter(|line| !expected.lines().any(|other| other == *line))
+            .map(|line| format!("+ {}", line));
+        panic!(
+            "the command tree no longer matches {} (rerun with UPDATE_SNAPSHOT=1 if this is intended):\n{}",
+            SNAPSHOT_PATH,
+            removed.chain(added).collect::<Vec<_>>().join("\n"),
+        );
+    }
+}
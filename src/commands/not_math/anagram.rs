@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::words::{count_letters, WORDS};
+use tokio::sync::Mutex;
+
+/// The maximum number of anagrams listed.
+const MAX_ANAGRAMS: usize = 100;
+
+/// Finds the words that use exactly the same letters as the given word, excluding the word itself.
+fn anagrams(word: &str) -> Vec<&'static str> {
+    let word = word.to_lowercase();
+    let letters = count_letters(&word);
+    let length = letters.values().sum::<usize>();
+
+    WORDS.iter()
+        .filter(|candidate| candidate.chars().count() == length && **candidate != word)
+        .filter(|candidate| count_letters(candidate) == letters)
+        .take(MAX_ANAGRAMS)
+        .copied()
+        .collect()
+}
+
+/// Finds words (up to 100) that are anagrams of the provided word, using all of its letters in a
+/// different order.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["anagram", "anagrams", "ana"],
+    syntax = ["<word>"],
+    examples = ["listen"],
+)]
+pub struct Anagram;
+
+#[async_trait]
+impl Command for Anagram {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(word) = ctxt.raw_input.split_whitespace().next() else {
+            return Err("**You must provide a word to find the anagrams of.**".into());
+        };
+
+        let words = anagrams(word);
+        let output = if words.is_empty() {
+            "_no anagrams found_".to_string()
+        } else {
+            words.join(", ")
+        };
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("**Anagrams of** `{}`\n{}", word, output))?
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_anagrams() {
+        assert_eq!(anagrams("Listen"), vec!["elints", "enlist", "inlets", "intels", "silent", "tinsel"]);
+    }
+
+    #[test]
+    fn word_without_anagrams() {
+        assert!(anagrams("xyzzyq").is_empty());
+    }
+}
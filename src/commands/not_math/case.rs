@@ -0,0 +1,238 @@
+use async_trait::async_trait;
+use calcbot_attrs::{Command, Info};
+use crate::{
+    commands::{Command, Context, Info},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::title::title_case;
+use tokio::sync::Mutex;
+
+/// Splits text into lowercase words, treating anything that is not a letter or digit as a
+/// separator, and starting a new word where an uppercase letter follows a lowercase one (as in
+/// `camelCase`).
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lowercase = false;
+
+    for c in text.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lowercase = false;
+            continue;
+        }
+
+        if c.is_uppercase() && previous_lowercase {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(c.to_lowercase());
+        previous_lowercase = c.is_lowercase();
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Converts text to `camelCase`.
+fn camel(text: &str) -> String {
+    split_words(text)
+        .into_iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) if i > 0 => first.to_uppercase().chain(chars).collect(),
+                _ => word,
+            }
+        })
+        .collect()
+}
+
+/// Replies with the transformed text, or an error if there is no text to transform.
+async fn respond(
+    state: &State,
+    ctxt: &Context<'_>,
+    transformed: String,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if transformed.is_empty() {
+        return Err("**You must provide some text to transform.**".into());
+    }
+
+    ctxt.trigger.reply(&state.http)
+        .content(&transformed)?
+        .await?;
+    Ok(())
+}
+
+/// Change the case of text: UPPERCASE, lowercase, Title Case, snake_case, camelCase, or
+/// kebab-case.
+#[derive(Clone, Command, Info)]
+#[info(
+    aliases = ["case"],
+    syntax = [""],
+    children = [Upper, Lower, Title, Snake, Camel, Kebab],
+)]
+pub struct Case;
+
+/// Converts text to UPPERCASE.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["upper", "up"],
+    syntax = ["<string>"],
+    examples = ["crème brûlée"],
+)]
+pub struct Upper;
+
+#[async_trait]
+impl Command for Upper {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, ctxt.raw_input.to_uppercase()).await
+    }
+}
+
+/// Converts text to lowercase.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["lower", "low"],
+    syntax = ["<string>"],
+    examples = ["CRÈME BRÛLÉE"],
+)]
+pub struct Lower;
+
+#[async_trait]
+impl Command for Lower {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, ctxt.raw_input.to_lowercase()).await
+    }
+}
+
+/// Converts text to Title Case. Prepositions and similar words will automatically be ignored.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["title", "t"],
+    syntax = ["<string>"],
+    examples = ["the great escape", "what you should do in the event of apocalypse"],
+)]
+pub struct Title;
+
+#[async_trait]
+impl Command for Title {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, title_case(ctxt.raw_input)).await
+    }
+}
+
+/// Converts text to snake_case. Punctuation is removed, and words are separated by underscores.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["snake"],
+    syntax = ["<string>"],
+    examples = ["Crème Brûlée recipe", "someVariableName"],
+)]
+pub struct Snake;
+
+#[async_trait]
+impl Command for Snake {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, split_words(ctxt.raw_input).join("_")).await
+    }
+}
+
+/// Converts text to camelCase. Punctuation is removed, and every word after the first is
+/// capitalized.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["camel"],
+    syntax = ["<string>"],
+    examples = ["crème brûlée recipe", "some_variable_name"],
+)]
+pub struct Camel;
+
+#[async_trait]
+impl Command for Camel {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, camel(ctxt.raw_input)).await
+    }
+}
+
+/// Converts text to kebab-case. Punctuation is removed, and words are separated by hyphens.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["kebab"],
+    syntax = ["<string>"],
+    examples = ["Crème Brûlée recipe", "someVariableName"],
+)]
+pub struct Kebab;
+
+#[async_trait]
+impl Command for Kebab {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        respond(state, &ctxt, split_words(ctxt.raw_input).join("-")).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_on_separators() {
+        assert_eq!(split_words("Hello, world!  again"), vec!["hello", "world", "again"]);
+        assert_eq!(split_words("snake_case-and-kebab"), vec!["snake", "case", "and", "kebab"]);
+    }
+
+    #[test]
+    fn split_camel_case() {
+        assert_eq!(split_words("camelCaseText"), vec!["camel", "case", "text"]);
+        assert_eq!(split_words("HTTPServer"), vec!["httpserver"]);
+    }
+
+    #[test]
+    fn split_nothing() {
+        assert!(split_words(" _-!").is_empty());
+    }
+
+    #[test]
+    fn camel_case() {
+        assert_eq!(camel("hello big world"), "helloBigWorld");
+        assert_eq!(camel("Snake_case_TEXT"), "snakeCaseText");
+        assert_eq!(camel(""), "");
+    }
+}
@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use calcbot_attrs::{Command, Info};
+use crate::{
+    commands::{Command, Context, Info},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use super::words::{is_word, WORDS_BY_FIRST_LETTER};
+use tokio::sync::Mutex;
+
+/// The maximum number of words that can be given to a chain command.
+const MAX_WORDS: usize = 50;
+
+/// The number of words suggested by `chain next`.
+const SUGGESTIONS: usize = 10;
+
+/// Lowercases the word and removes any punctuation from it.
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Checks that the given words form a valid word chain, returning a description of the first
+/// violation if they do not.
+fn check_chain(words: &[String]) -> Result<(), String> {
+    for (i, word) in words.iter().enumerate() {
+        if !is_word(word) {
+            return Err(format!("`{}` (word {}) is not a valid word.", word, i + 1));
+        }
+
+        if i > 0 {
+            let previous = &words[i - 1];
+            let last = previous.chars().last().unwrap();
+            if !word.starts_with(last) {
+                return Err(format!(
+                    "`{}` (word {}) does not start with `{}`, the last letter of `{}`.",
+                    word, i + 1, last, previous,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Suggests up to [`SUGGESTIONS`] words that can follow the given word in a word chain, optionally
+/// of the given length.
+///
+/// The suggestions are deterministic for a given word and seed, so the same suggestions are
+/// shown throughout the day, while still varying between days.
+fn suggest_next(word: &str, length: Option<usize>, seed: u64) -> Vec<&'static str> {
+    let Some(candidates) = word.chars()
+        .last()
+        .and_then(|last| WORDS_BY_FIRST_LETTER.get(&last))
+    else {
+        return Vec::new();
+    };
+
+    let key = |candidate: &&str| {
+        let mut hasher = DefaultHasher::new();
+        (seed, candidate).hash(&mut hasher);
+        hasher.finish()
+    };
+    let mut suggestions = candidates.iter()
+        .copied()
+        .filter(|candidate| *candidate != word)
+        .filter(|candidate| length.map_or(true, |length| candidate.chars().count() == length))
+        .collect::<Vec<_>>();
+    suggestions.sort_by_cached_key(key);
+    suggestions.truncate(SUGGESTIONS);
+    suggestions
+}
+
+/// Check or play word chains, where each word must start with the last letter of the previous
+/// word.
+#[derive(Clone, Command, Info)]
+#[info(
+    aliases = ["chain", "ch"],
+    syntax = [""],
+    children = [Check, Next],
+)]
+pub struct Chain;
+
+/// Checks that each word starts with the last letter of the previous word, and that every word
+/// is a real word. Up to 50 words can be checked at once.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["check", "c"],
+    syntax = ["<word> <word>..."],
+    examples = ["apple egg giraffe elephant"],
+)]
+pub struct Check;
+
+#[async_trait]
+impl Command for Check {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let words = ctxt.raw_input
+            .split_whitespace()
+            .map(normalize)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>();
+        if words.is_empty() {
+            return Err("**You must provide at least one word to check.**".into());
+        }
+        if words.len() > MAX_WORDS {
+            return Err("**You can check at most 50 words at a time.**".into());
+        }
+
+        let output = match check_chain(&words) {
+            Ok(()) => format!("**Valid chain** of {} words!", words.len()),
+            Err(reason) => format!("**Invalid chain**\n{}", reason),
+        };
+        ctxt.trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+        Ok(())
+    }
+}
+
+/// Suggests up to 10 words that could come next in a word chain, optionally of a given length.
+/// The suggestions change every day.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["next", "n"],
+    syntax = ["<word> [word length]"],
+    examples = ["apple", "apple 5"],
+    args = [&str, Option<usize>],
+)]
+pub struct Next;
+
+#[async_trait]
+impl Command for Next {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (word, length) = parse_args(ctxt.raw_input.split_whitespace().collect::<Vec<_>>())?;
+        let word = normalize(word);
+        if word.is_empty() {
+            return Err("**You must provide a word to continue the chain from.**".into());
+        }
+
+        let day = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() / 86_400;
+        let suggestions = suggest_next(&word, length, day);
+        let output = if suggestions.is_empty() {
+            "_no words found_".to_string()
+        } else {
+            suggestions.join(", ")
+        };
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("**Words that can follow** `{}`\n{}", word, output))?
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Normalizes each of the given words.
+    fn words(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| normalize(word)).collect()
+    }
+
+    #[test]
+    fn normalize_words() {
+        assert_eq!(normalize("Apple,"), "apple");
+        assert_eq!(normalize("don't"), "dont");
+    }
+
+    #[test]
+    fn valid_chain() {
+        assert_eq!(check_chain(&words(&["apple", "Egg", "giraffe", "elephant"])), Ok(()));
+    }
+
+    #[test]
+    fn broken_chain() {
+        assert_eq!(
+            check_chain(&words(&["apple", "giraffe"])),
+            Err("`giraffe` (word 2) does not start with `e`, the last letter of `apple`.".to_owned()),
+        );
+    }
+
+    #[test]
+    fn unknown_word() {
+        assert_eq!(
+            check_chain(&words(&["apple", "xyzzyq"])),
+            Err("`xyzzyq` (word 2) is not a valid word.".to_owned()),
+        );
+    }
+
+    #[test]
+    fn suggestions_follow_the_word() {
+        let suggestions = suggest_next("apple", Some(5), 1);
+        assert_eq!(suggestions.len(), SUGGESTIONS);
+        for suggestion in &suggestions {
+            assert!(suggestion.starts_with('e'));
+            assert_eq!(suggestion.chars().count(), 5);
+        }
+    }
+
+    #[test]
+    fn suggestions_are_deterministic() {
+        assert_eq!(suggest_next("apple", None, 1), suggest_next("apple", None, 1));
+    }
+
+    #[test]
+    fn suggestions_exclude_the_word() {
+        assert!(!suggest_next("eve", Some(3), 1).contains(&"eve"));
+    }
+}
@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The counts of a piece of text.
+#[derive(Debug, PartialEq, Eq)]
+struct Counts {
+    /// The number of characters as they appear on screen, or extended grapheme clusters.
+    characters: usize,
+
+    /// The number of words, separated by whitespace.
+    words: usize,
+
+    /// The number of lines.
+    lines: usize,
+
+    /// The number of bytes of the UTF-8 encoded text.
+    bytes: usize,
+}
+
+/// Counts the characters, words, lines, and bytes of the given text.
+fn count(text: &str) -> Counts {
+    Counts {
+        characters: text.graphemes(true).count(),
+        words: text.split_whitespace().count(),
+        lines: text.lines().count(),
+        bytes: text.len(),
+    }
+}
+
+/// Counts the characters, words, lines, and bytes of text. Characters are counted as they appear
+/// on screen, so an emoji or an accented letter counts as one character.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["count", "wc"],
+    syntax = ["<string>"],
+    examples = ["hello world"],
+)]
+pub struct Count;
+
+#[async_trait]
+impl Command for Count {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if ctxt.raw_input.is_empty() {
+            return Err("**You must provide some text to count.**".into());
+        }
+
+        let Counts { characters, words, lines, bytes } = count(ctxt.raw_input);
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "**Characters**: {}\n**Words**: {}\n**Lines**: {}\n**Bytes**: {}",
+                characters, words, lines, bytes
+            ))?
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_ascii_text() {
+        assert_eq!(count("hello world"), Counts { characters: 11, words: 2, lines: 1, bytes: 11 });
+    }
+
+    #[test]
+    fn counts_graphemes_as_characters() {
+        let counts = count("café 👍🏽\nok");
+        assert_eq!(counts, Counts { characters: 9, words: 3, lines: 2, bytes: 17 });
+    }
+}
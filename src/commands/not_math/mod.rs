@@ -1,7 +1,12 @@
 pub mod aegyo;
+pub mod anagram;
+pub mod case;
+pub mod chain;
+pub mod count;
 pub mod random;
 pub mod registered_trademark;
 pub mod reverse;
+pub mod roll;
 pub mod scramble;
 pub mod sort;
 pub mod spacer;
@@ -10,6 +15,7 @@ pub mod trademark;
 pub mod trademarkinator;
 pub mod uglify;
 pub mod unscramble;
+pub mod words;
 
 use calcbot_attrs::{Command, Info};
 use crate::commands::Info;
@@ -22,9 +28,14 @@ use crate::commands::Info;
     syntax = [""],
     children = [
         aegyo::Aegyo,
+        anagram::Anagram,
+        case::Case,
+        chain::Chain,
+        count::Count,
         random::Random,
         registered_trademark::RegisteredTrademark,
         reverse::Reverse,
+        roll::Roll,
         scramble::Scramble,
         sort::Sort,
         spacer::Spacer,
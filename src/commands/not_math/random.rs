@@ -10,15 +10,32 @@ use getrandom::getrandom;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-/// Generates the random integer.
-fn random(min: u32, max: u32) -> u32 {
+/// Returns a uniformly random `u32`.
+fn random_u32() -> u32 {
     let mut buf = [0u8; 4];
     getrandom(&mut buf).unwrap();
+    u32::from_le_bytes(buf)
+}
+
+/// Generates a random integer between `min` and `max`, inclusive. `min` must not be greater than
+/// `max`.
+///
+/// Numbers from the top of the `u32` range that would favor some results over others when reduced
+/// into the range are rejected and drawn again.
+pub fn random(min: u32, max: u32) -> u32 {
+    let span = (max - min) as u64 + 1;
+    if span == 1 {
+        return min;
+    }
 
-    let mut num = u32::from_le_bytes(buf);
-    num %= max - min; // limit number to range
-    num += min; // offset number to range
-    num
+    // the largest multiple of `span` that fits in a u32, so every result is equally likely
+    let limit = (1u64 << 32) - (1u64 << 32) % span;
+    loop {
+        let num = random_u32() as u64;
+        if num < limit {
+            return min + (num % span) as u32;
+        }
+    }
 }
 
 /// Generate a random integer; boundaries are inclusive.
@@ -43,7 +60,9 @@ impl Command for Random {
             (a, Some(b)) => (a, b),
             (a, None) => (0, a),
         };
-        let num = random(min, max + 1);
+        // the boundaries can be given in either order
+        let (min, max) = (min.min(max), min.max(max));
+        let num = random(min, max);
         ctxt.trigger.reply(&state.http)
             .content(&format!(
                 "**Random number** from {} to {}\n{}",
@@ -53,3 +72,26 @@ impl Command for Random {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_value_range() {
+        assert_eq!(random(7, 7), 7);
+    }
+
+    #[test]
+    fn results_are_in_range() {
+        for _ in 0..1000 {
+            assert!((1..=6).contains(&random(1, 6)));
+        }
+    }
+
+    #[test]
+    fn full_range_does_not_overflow() {
+        random(0, u32::MAX);
+        random(u32::MAX - 1, u32::MAX);
+    }
+}
@@ -8,13 +8,28 @@ use crate::{
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Reverses the characters of the text, keeping each grapheme cluster intact.
+fn reverse(text: &str) -> String {
+    text.graphemes(true).rev().collect()
+}
+
+/// Reverses the order of the words of the text, joining them with single spaces.
+fn reverse_words(text: &str) -> String {
+    text.split_whitespace().rev().collect::<Vec<_>>().join(" ")
+}
 
 /// No one will ever figure out your password now!
+///
+/// Emoji and accented letters are kept intact. Use `words` to reverse the order of the words
+/// instead.
 #[derive(Clone, Info)]
 #[info(
     aliases = ["reverse", "rev"],
     syntax = ["<string>"],
     examples = ["!yadhtrib yppah"],
+    children = [Words],
 )]
 pub struct Reverse;
 
@@ -27,8 +42,49 @@ impl Command for Reverse {
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         ctxt.trigger.reply(&state.http)
-            .content(&ctxt.raw_input.chars().rev().collect::<String>())?
+            .content(&reverse(ctxt.raw_input))?
+            .await?;
+        Ok(())
+    }
+}
+
+/// Reverses the order of the words in a string, keeping each word intact.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["words"],
+    syntax = ["<string>"],
+    examples = ["fox brown quick the"],
+)]
+pub struct Words;
+
+#[async_trait]
+impl Command for Words {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        ctxt.trigger.reply(&state.http)
+            .content(&reverse_words(ctxt.raw_input))?
             .await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn graphemes_are_kept_intact() {
+        assert_eq!(reverse("!yadhtrib yppah"), "happy birthday!");
+        assert_eq!(reverse("cafe\u{301} 👍🏽"), "👍🏽 e\u{301}fac");
+    }
+
+    #[test]
+    fn word_order_is_reversed() {
+        assert_eq!(reverse_words("fox brown quick the"), "the quick brown fox");
+        assert_eq!(reverse_words("  one\ttwo\nthree  "), "three two one");
+    }
+}
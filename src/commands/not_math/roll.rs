@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use super::random::random;
+use tokio::sync::Mutex;
+
+/// The maximum number of dice that can be rolled at once.
+const MAX_DICE: u32 = 100;
+
+/// The maximum number of sides a die can have.
+const MAX_SIDES: u32 = 1000;
+
+/// Parses dice notation, such as `2d6+3` or `d20`, into the number of dice, the number of sides of
+/// each die, and the modifier added to the total. Returns [`None`] if the notation is invalid.
+fn parse_dice(notation: &str) -> Option<(u32, u32, i32)> {
+    let notation = notation.to_lowercase();
+    let (count, rest) = notation.split_once('d')?;
+    let count = match count {
+        "" => 1,
+        count => count.parse().ok()?,
+    };
+
+    let (sides, modifier) = match rest.find(['+', '-']) {
+        Some(i) => {
+            let (sides, modifier) = rest.split_at(i);
+            let modifier = modifier.strip_prefix('+').unwrap_or(modifier);
+            (sides, modifier.parse().ok()?)
+        },
+        None => (rest, 0),
+    };
+    Some((count, sides.parse().ok()?, modifier))
+}
+
+/// Rolls dice given in dice notation: `NdS+M` rolls `N` dice with `S` sides each, and adds `M` to
+/// the total. The number of dice and the modifier can be left out. At most 100 dice with at most
+/// 1000 sides can be rolled at once.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["roll", "dice"],
+    syntax = ["<dice notation>"],
+    examples = ["d20", "2d6+3", "4d8-2"],
+)]
+pub struct Roll;
+
+#[async_trait]
+impl Command for Roll {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let notation = ctxt.raw_input.split_whitespace().collect::<String>();
+        if notation.is_empty() {
+            return Err("**You must provide the dice to roll, such as `2d6+3`.**".into());
+        }
+        let Some((count, sides, modifier)) = parse_dice(&notation) else {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**`{}` is not valid dice notation.** Try something like `2d6+3` or `d20`.", notation))?
+                .await?;
+            return Ok(());
+        };
+        if count == 0 || count > MAX_DICE {
+            return Err("**You can roll between 1 and 100 dice at once.**".into());
+        }
+        if sides == 0 || sides > MAX_SIDES {
+            return Err("**Dice must have between 1 and 1000 sides.**".into());
+        }
+
+        let rolls = (0..count).map(|_| random(1, sides)).collect::<Vec<_>>();
+        let total = rolls.iter().map(|&roll| roll as i64).sum::<i64>() + modifier as i64;
+        let rolls = rolls.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+        let modifier = match modifier {
+            0 => String::new(),
+            m if m > 0 => format!(" + {}", m),
+            m => format!(" - {}", -(m as i64)),
+        };
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "**Rolling** `{}`\n[{}]{} = **{}**",
+                notation, rolls, modifier, total
+            ))?
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_full_notation() {
+        assert_eq!(parse_dice("2d6+3"), Some((2, 6, 3)));
+        assert_eq!(parse_dice("4D8-2"), Some((4, 8, -2)));
+    }
+
+    #[test]
+    fn count_and_modifier_are_optional() {
+        assert_eq!(parse_dice("d20"), Some((1, 20, 0)));
+        assert_eq!(parse_dice("3d4"), Some((3, 4, 0)));
+    }
+
+    #[test]
+    fn parse_invalid_notation() {
+        for notation in ["", "20", "d", "2d", "xd6", "2d6+", "2d6*2"] {
+            assert_eq!(parse_dice(notation), None, "{}", notation);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use calcbot_attrs::Info;
 use crate::{
-    commands::{Command, Context},
+    commands::{Command, Context, Info},
     database::Database,
     error::Error,
     global::State,
@@ -9,13 +9,57 @@ use crate::{
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Sorts the given items, numbers first, in descending order if the first item is `-`. Returns the
+/// output of the command, or [`None`] if there is nothing to sort.
+fn sort(mut args: Vec<&str>) -> Option<String> {
+    let descending = args.first() == Some(&"-");
+    if descending {
+        args.remove(0);
+    }
+    if args.is_empty() {
+        return None;
+    }
+
+    let mut numbers = Vec::new();
+    let mut words = Vec::new();
+    for arg in args {
+        if let Ok(number) = arg.parse::<f64>() {
+            numbers.push(number);
+        } else {
+            words.push(arg);
+        }
+    }
+
+    let mut output = String::new();
+    if descending {
+        output.push_str("**Sorting** in descending order\n");
+        numbers.sort_by(|a, b| b.total_cmp(a));
+        words.sort_by(|a, b| b.cmp(a));
+    } else {
+        output.push_str("**Sorting** in ascending order\n");
+        numbers.sort_by(f64::total_cmp);
+        words.sort();
+    }
+
+    let values = numbers
+        .into_iter()
+        .map(|n| n.to_string())
+        .chain(words.into_iter().map(|w| w.to_string()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    output.push_str(&values);
+    Some(output)
+}
+
 /// Sorts a list of numbers / words in ascending / alphabetical order, numbers first. If a minus
 /// symbol (`-`) is provided for the first argument, the list will be sorted in descending order
 /// instead.
+///
+/// If the list is pasted on the lines after the command, each line is sorted as a single item.
 #[derive(Clone, Info)]
 #[info(
     aliases = ["sort", "so"],
-    syntax = ["<number | word>...", "- <number | word>..."],
+    syntax = ["<number | word>...", "- <number | word>...", "[-]\n<item>\n<item>..."],
     examples = ["9 5 28 12", "5 11 this sentence is not in alphabetical order 2"],
 )]
 pub struct Sort;
@@ -28,40 +72,25 @@ impl Command for Sort {
         _: &Arc<Mutex<Database>>,
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let mut args = ctxt.raw_input.split_whitespace().collect::<Vec<_>>();
-        let descending = args[0] == "-";
-        if descending {
-            args.remove(0);
-        }
-
-        let mut numbers = Vec::new();
-        let mut words = Vec::new();
-        for arg in args {
-            if let Ok(number) = arg.parse::<f64>() {
-                numbers.push(number);
-            } else {
-                words.push(arg);
+        // in lines mode, the first line can only contain the descending flag
+        let (first, lines) = ctxt.raw_input_lines();
+        let args = if !lines.is_empty() && matches!(first, "" | "-") {
+            let mut args = lines;
+            if first == "-" {
+                args.insert(0, first);
             }
-        }
-
-        let mut output = String::new();
-        if descending {
-            output.push_str("**Sorting** in descending order\n");
-            numbers.sort_by(|a, b| b.partial_cmp(a).unwrap());
-            words.sort_by(|a, b| b.partial_cmp(a).unwrap());
+            args
         } else {
-            output.push_str("**Sorting** in ascending order\n");
-            numbers.sort_by(|a, b| a.partial_cmp(b).unwrap());
-            words.sort();
-        }
+            ctxt.raw_input.split_whitespace().collect::<Vec<_>>()
+        };
 
-        let values = numbers
-            .into_iter()
-            .map(|n| n.to_string())
-            .chain(words.into_iter().map(|w| w.to_string()))
-            .collect::<Vec<_>>()
-            .join(", ");
-        output.push_str(&values);
+        // with nothing to sort, show how to use the command instead
+        let Some(output) = sort(args) else {
+            ctxt.trigger.reply(&state.http)
+                .embeds(&[self.info().build_embed(ctxt.prefix)])?
+                .await?;
+            return Ok(());
+        };
 
         ctxt.trigger.reply(&state.http)
             .content(&output)?
@@ -69,3 +98,28 @@ impl Command for Sort {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nothing_to_sort() {
+        assert_eq!(sort(vec![]), None);
+        assert_eq!(sort(vec!["-"]), None);
+    }
+
+    #[test]
+    fn numbers_are_sorted_by_value() {
+        assert_eq!(sort(vec!["9", "5", "28"]).unwrap(), "**Sorting** in ascending order\n5, 9, 28");
+        assert_eq!(sort(vec!["-", "9", "5", "28"]).unwrap(), "**Sorting** in descending order\n28, 9, 5");
+    }
+
+    #[test]
+    fn numbers_come_before_words() {
+        assert_eq!(
+            sort(vec!["pear", "2", "apple", "-1.5"]).unwrap(),
+            "**Sorting** in ascending order\n-1.5, 2, apple, pear",
+        );
+    }
+}
@@ -25,12 +25,30 @@ fn capitalize(string: &str) -> String {
     }
 }
 
+/// Converts text to title case, capitalizing every word except short prepositions and similar
+/// words after the first.
+pub fn title_case(text: &str) -> String {
+    text.split_whitespace()
+        .enumerate()
+        .map(|(i, word)| {
+            let lowercase = word.to_lowercase();
+            if i == 0 || word.len() >= 5 || !IGNORE.contains(&lowercase.as_str()) {
+                capitalize(word)
+            } else {
+                lowercase
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 /// Converts text to title case. Prepositions and similar words will automatically be ignored.
 #[derive(Clone, Info)]
 #[info(
     aliases = ["title", "t"],
     syntax = ["<string>"],
     examples = ["the great escape", "what you should do in the event of apocalypse"],
+    deprecated = "Use `{prefix}notmath case title` instead.",
 )]
 pub struct Title;
 
@@ -42,24 +60,28 @@ impl Command for Title {
         _: &Arc<Mutex<Database>>,
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let content = ctxt.raw_input
-            .split_whitespace()
-            .into_iter()
-            .enumerate()
-            .map(|(i, word)| {
-                let lowercase = word.to_lowercase();
-                if i == 0 || word.len() >= 5 || !IGNORE.contains(&lowercase.as_str()) {
-                    capitalize(word)
-                } else {
-                    lowercase
-                }
-            })
-            .collect::<Vec<String>>()
-            .join(" ");
-
         ctxt.trigger.reply(&state.http)
-            .content(&content)?
+            .content(&title_case(ctxt.raw_input))?
             .await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_words_stay_lowercase() {
+        assert_eq!(title_case("the great escape"), "The Great Escape");
+        assert_eq!(
+            title_case("what you should do in the event of apocalypse"),
+            "What You Should Do in the Event of Apocalypse",
+        );
+    }
+
+    #[test]
+    fn long_prepositions_are_capitalized() {
+        assert_eq!(title_case("life without parole"), "Life Without Parole");
+    }
+}
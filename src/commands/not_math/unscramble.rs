@@ -6,30 +6,10 @@ use crate::{
     error::Error,
     global::State,
 };
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
+use super::words::{count_letters, WORDS};
 use tokio::sync::Mutex;
 
-lazy_static::lazy_static! {
-    /// The list of words to search through (~250K words).
-    static ref WORDS: Vec<&'static str> = {
-        let words = include_str!("./words.json");
-        serde_json::from_str(words).unwrap()
-    };
-}
-
-/// Count the number of times each letter appears in a string.
-fn count_letters(string: &str) -> HashMap<char, usize> {
-    let mut letters = HashMap::new();
-
-    for letter in string.to_lowercase().chars() {
-        if letter.is_alphabetic() {
-            *letters.entry(letter).or_insert(0) += 1;
-        }
-    }
-
-    letters
-}
-
 /// Finds the words that can be spelt using the provided letters.
 fn unscramble(letters: &str, length: usize) -> Vec<&'static str> {
     let mut words = Vec::new();
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+lazy_static::lazy_static! {
+    /// The list of words used by the word-based commands (~250K words). The words are lowercase
+    /// and sorted alphabetically.
+    pub static ref WORDS: Vec<&'static str> = {
+        let words = include_str!("./words.json");
+        serde_json::from_str(words).unwrap()
+    };
+
+    /// The words in [`WORDS`], bucketed by their first letter. Each bucket is sorted.
+    pub static ref WORDS_BY_FIRST_LETTER: HashMap<char, Vec<&'static str>> = {
+        let mut buckets = HashMap::<char, Vec<&'static str>>::new();
+        for word in WORDS.iter() {
+            if let Some(first) = word.chars().next() {
+                buckets.entry(first).or_default().push(word);
+            }
+        }
+        buckets
+    };
+}
+
+/// Returns true if the given lowercase word is in [`WORDS`].
+pub fn is_word(word: &str) -> bool {
+    WORDS.binary_search(&word).is_ok()
+}
+
+/// Count the number of times each letter appears in a string, ignoring case and non-letters.
+pub fn count_letters(string: &str) -> HashMap<char, usize> {
+    let mut letters = HashMap::new();
+
+    for letter in string.to_lowercase().chars() {
+        if letter.is_alphabetic() {
+            *letters.entry(letter).or_insert(0) += 1;
+        }
+    }
+
+    letters
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::{sync::Arc, time::Instant};
+use tokio::sync::Mutex;
+
+/// Check how quickly CalcBot is responding. The REST latency is the time taken to send a message,
+/// and the gateway latency is the average time taken for Discord to acknowledge CalcBot's
+/// heartbeats.
+#[derive(Clone, Info)]
+#[info(category = "Miscellaneous", aliases = ["ping", "latency"])]
+pub struct Ping;
+
+#[async_trait]
+impl Command for Ping {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        _: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let start = Instant::now();
+        let reply = ctxt.trigger.reply_placeholder(&state.http, "**Pinging…**").await?;
+        let rest_latency = start.elapsed();
+
+        let gateway_latency = match *state.gateway_latency.lock().unwrap() {
+            Some(latency) => format!("{} ms", latency.as_millis()),
+            None => "not measured yet".to_owned(),
+        };
+        reply.edit_content(&state.http, &format!(
+            "**Pong!**\nREST latency: {} ms\nGateway latency: {}",
+            rest_latency.as_millis(),
+            gateway_latency,
+        )).await?;
+
+        Ok(())
+    }
+}
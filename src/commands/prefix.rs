@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context, Trigger, TriggerEvent},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use twilight_model::guild::Permissions;
+
+/// The maximum length of a server prefix, in characters.
+const MAX_PREFIX_LENGTH: usize = 5;
+
+/// Returns true if the user who triggered the command has the Manage Server permission in the
+/// server it was triggered in.
+pub(crate) fn can_manage_server(state: &State, trigger: Trigger<'_>) -> bool {
+    let permissions = match trigger.event {
+        TriggerEvent::Message(msg) => msg.guild_id.and_then(|guild_id| {
+            state.cache.permissions()
+                .root(msg.author.id, guild_id)
+                .map_err(|err| log::warn!("could not compute permissions of user {}: {}", msg.author.id, err))
+                .ok()
+        }),
+        TriggerEvent::Interaction(interaction) => interaction.member
+            .as_ref()
+            .and_then(|member| member.permissions),
+    };
+    permissions.map_or(false, |permissions| permissions.contains(Permissions::MANAGE_GUILD))
+}
+
+/// View or change CalcBot's prefix in this server. Changing the prefix requires the Manage Server
+/// permission. The prefix can be at most 5 characters long, and cannot contain spaces.
+#[derive(Clone, Info)]
+#[info(
+    category = "Settings",
+    aliases = ["prefix"],
+    syntax = ["[new prefix]"],
+    examples = ["", "!"],
+)]
+pub struct Prefix;
+
+#[async_trait]
+impl Command for Prefix {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(guild_id) = ctxt.trigger.guild_id() else {
+            return Err("**The prefix can only be changed in a server.** No prefix is needed in DMs.".into());
+        };
+
+        let prefix = ctxt.raw_input;
+        if prefix.is_empty() {
+            let current = database.lock().await
+                .get_server(guild_id).await
+                .prefix
+                .clone();
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**The prefix in this server is** `{}`", current))?
+                .await?;
+            return Ok(());
+        }
+
+        if !can_manage_server(state, ctxt.trigger) {
+            return Err("**You need the Manage Server permission to change the prefix.**".into());
+        }
+        if prefix.contains(char::is_whitespace) {
+            return Err("**The prefix cannot contain spaces.**".into());
+        }
+        if prefix.chars().count() > MAX_PREFIX_LENGTH {
+            return Err("**The prefix can be at most 5 characters long.**".into());
+        }
+
+        let mut database = database.lock().await;
+        database.set_server_prefix(guild_id, prefix.to_owned()).await;
+
+        // messages are filtered by the prefix before reaching the handler
+        let inline_math = database.get_server(guild_id).await.inline_math;
+        state.set_prefix_hint(guild_id, prefix, inline_math);
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("**Changed the prefix in this server to** `{}`", prefix))?
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserField, Database},
+    error::Error,
+    global::State,
+    timer::Timer,
+    util::{confirm, pluralize, ConfirmResult},
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use super::pending_count;
+use tokio::sync::Mutex;
+
+/// How long the user has to confirm the deletion.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Describes the given reminders, like `3 reminders and 1 sent reminder`, counting reminders that
+/// have yet to be sent the same way as [`State::max_timers`]. Returns [`None`] if there are no
+/// reminders.
+fn describe_all(timers: &HashMap<String, Timer>) -> Option<String> {
+    let pending = pending_count(timers.values());
+    let sent = timers.len() - pending;
+    match (pending, sent) {
+        (0, 0) => None,
+        (_, 0) => Some(pluralize(pending, "reminder")),
+        (0, _) => Some(pluralize(sent, "sent reminder")),
+        _ => Some(format!("{} and {}", pluralize(pending, "reminder"), pluralize(sent, "sent reminder"))),
+    }
+}
+
+/// Deletes all of the user's reminders, after asking them to confirm.
+async fn delete_all(
+    state: &Arc<State>,
+    database: &Arc<Mutex<Database>>,
+    ctxt: Context<'_>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let description = describe_all(&database.lock().await.get_user(ctxt.trigger.author_id()).await.timers);
+    let Some(description) = description else {
+        return Err("**You have no reminders to delete.**".into());
+    };
+
+    let result = confirm(
+        state,
+        database,
+        ctxt.trigger,
+        &format!("**Delete all {}?**", description),
+        CONFIRM_TIMEOUT,
+    ).await?;
+
+    let output = match result {
+        ConfirmResult::Confirmed => {
+            // count again, since reminders may have been added or deleted while waiting
+            // dropping the timers aborts their tasks
+            let mut database = database.lock().await;
+            let removed = describe_all(&database.get_user(ctxt.trigger.author_id()).await.timers);
+            database.set_user_field(ctxt.trigger.author_id(), UserField::Timers(HashMap::new())).await;
+            match removed {
+                Some(removed) => format!("**Deleted {}.**", removed),
+                None => "**Your reminders were already deleted.**".to_owned(),
+            }
+        },
+        ConfirmResult::Cancelled => "**Your reminders were not deleted.**".to_owned(),
+        ConfirmResult::TimedOut => "**You did not respond in time, so your reminders were not deleted.**".to_owned(),
+    };
+
+    ctxt.trigger.reply(&state.http)
+        .content(&output)?
+        .await?;
+
+    Ok(())
+}
+
+/// Delete one of your reminders by its ID, or all of them with `all`. You will be asked to confirm
+/// the deletion.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["delete", "del", "remove", "rm"],
+    syntax = ["<reminder id>", "all"],
+    examples = ["abcd", "all"],
+)]
+pub struct Delete;
+
+#[async_trait]
+impl Command for Delete {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let id = ctxt.raw_input.trim().to_lowercase();
+        if id.is_empty() {
+            return Err("**You must provide the ID of the reminder to delete.**".into());
+        }
+
+        if id == "all" {
+            return delete_all(state, database, ctxt).await;
+        }
+
+        let message = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .timers
+            .get(&id)
+            .map(|timer| timer.message.clone());
+        let Some(message) = message else {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**You have no reminder with the ID `{}`.**", id))?
+                .await?;
+            return Ok(());
+        };
+
+        let prompt = match message.len() {
+            0 => format!("**Delete reminder `{}`?**", id),
+            _ => format!("**Delete reminder `{}`** ({})**?**", id, message),
+        };
+        let result = confirm(
+            state,
+            database,
+            ctxt.trigger,
+            &prompt,
+            CONFIRM_TIMEOUT,
+        ).await?;
+
+        let output = match result {
+            ConfirmResult::Confirmed => {
+                let mut database = database.lock().await;
+                let mut timers = database.get_user(ctxt.trigger.author_id())
+                    .await
+                    .timers
+                    .clone();
+
+                // the reminder may have fired or been deleted while waiting for confirmation
+                // dropping the timer aborts its task
+                match timers.remove(&id) {
+                    Some(_) => {
+                        database.set_user_field(ctxt.trigger.author_id(), UserField::Timers(timers)).await;
+                        format!("**Deleted reminder `{}`.**", id)
+                    },
+                    None => format!("**Reminder `{}` no longer exists.**", id),
+                }
+            },
+            ConfirmResult::Cancelled => format!("**Reminder `{}` was not deleted.**", id),
+            ConfirmResult::TimedOut => format!("**You did not respond in time, so reminder `{}` was not deleted.**", id),
+        };
+
+        ctxt.trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::time::SystemTime;
+
+    /// Creates reminders with the given IDs that end the given number of seconds from now, or are
+    /// paused if [`None`].
+    fn reminders(ends: &[(&str, Option<i64>)]) -> HashMap<String, Timer> {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs() as i64;
+        ends.iter()
+            .map(|(id, end)| {
+                let state = match end {
+                    Some(end) => json!({ "Running": { "end_time": { "secs_since_epoch": now + end, "nanos_since_epoch": 0 } } }),
+                    None => json!({ "Paused": { "remaining": { "secs": 60, "nanos": 0 } } }),
+                };
+                let timer = serde_json::from_value(json!({
+                    "id": id,
+                    "user_id": "1",
+                    "channel_id": "2",
+                    "state": state,
+                    "message": "",
+                })).unwrap();
+                (id.to_string(), timer)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_reminders_to_delete() {
+        assert_eq!(describe_all(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn pending_reminders_are_counted_like_the_limit() {
+        let timers = reminders(&[("abcd", Some(60)), ("efgh", None)]);
+        assert_eq!(pending_count(timers.values()), 2);
+        assert_eq!(describe_all(&timers).as_deref(), Some("2 reminders"));
+    }
+
+    #[test]
+    fn sent_reminders_are_counted_separately() {
+        let timers = reminders(&[("abcd", Some(60)), ("efgh", Some(-60)), ("ijkl", Some(-120))]);
+        assert_eq!(pending_count(timers.values()), 1);
+        assert_eq!(describe_all(&timers).as_deref(), Some("1 reminder and 2 sent reminders"));
+
+        let timers = reminders(&[("efgh", Some(-60))]);
+        assert_eq!(describe_all(&timers).as_deref(), Some("1 sent reminder"));
+    }
+}
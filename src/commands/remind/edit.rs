@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserField, Database},
+    error::Error,
+    global::State,
+    timer::TimerState,
+    util::format_duration_approx,
+};
+use std::{sync::Arc, time::{Duration, SystemTime}};
+use super::{check_message, to_duration, TOO_FAR_IN_FUTURE};
+use tokio::sync::Mutex;
+
+/// The change to make to a reminder.
+#[derive(Debug, PartialEq)]
+enum Change {
+    /// Change only the message.
+    Message(String),
+
+    /// Change when the reminder is sent, and the message if one is given.
+    Time {
+        /// The new interval, measured from now.
+        interval: Duration,
+
+        /// The quantity and unit of the interval, as given by the user.
+        given: String,
+
+        /// The new message, if one is given.
+        message: Option<String>,
+    },
+}
+
+/// Parses the ID of the reminder to edit and the change to make to it.
+fn parse_edit(input: &str) -> Result<(String, Change), Box<dyn Error + Send + Sync>> {
+    let mut words = input.split_whitespace();
+    let Some(id) = words.next().map(str::to_lowercase) else {
+        return Err("**You must provide the ID of the reminder to edit.**".into());
+    };
+
+    // a number starts a new interval; `message` starts a new message
+    let change = match words.next() {
+        Some(word) if word.eq_ignore_ascii_case("message") || word.eq_ignore_ascii_case("msg") => {
+            let message = words.collect::<Vec<_>>().join(" ");
+            if message.is_empty() {
+                return Err("**You must provide the new message of the reminder.**".into());
+            }
+            Change::Message(message)
+        },
+        Some(word) => {
+            let Ok(quantity) = word.parse::<f64>() else {
+                return Err("**Give `message` followed by the new message, or a new interval like `10 minutes`.**".into());
+            };
+            if quantity <= 0.0 || !quantity.is_finite() {
+                return Err("**The new interval must be positive.**".into());
+            }
+            let Some(unit) = words.next() else {
+                return Err("**You must provide the time unit of the new interval.**".into());
+            };
+            let interval = to_duration(quantity, unit)?;
+            let message = words.collect::<Vec<_>>().join(" ");
+            Change::Time {
+                interval,
+                given: format!("{} {}", quantity, unit),
+                message: (!message.is_empty()).then_some(message),
+            }
+        },
+        None => return Err("**Give `message` followed by the new message, or a new interval like `10 minutes`.**".into()),
+    };
+    if let Change::Message(message) | Change::Time { message: Some(message), .. } = &change {
+        check_message(message)?;
+    }
+
+    Ok((id, change))
+}
+
+/// Edit one of your reminders. Use `message` to change only the message, or give a new interval
+/// (measured from now) to change only when you are reminded. If you give both an interval and a
+/// message, both are changed.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["edit", "e"],
+    syntax = ["<reminder id> message <new message>", "<reminder id> <quantity> <time unit> [new message]"],
+    examples = ["abcd message stop watching tv", "abcd 10 minutes", "abcd 10 minutes stop watching tv"],
+)]
+pub struct Edit;
+
+#[async_trait]
+impl Command for Edit {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (id, change) = parse_edit(ctxt.raw_input)?;
+
+        let mut database = database.lock().await;
+        let mut timers = database.get_user(ctxt.trigger.author_id())
+            .await
+            .timers
+            .clone();
+        let Some(timer) = timers.remove(&id) else {
+            drop(database);
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**You have no reminder with the ID `{}`.**", id))?
+                .await?;
+            return Ok(());
+        };
+
+        let (timer, output) = match change {
+            Change::Message(message) => (
+                timer.with_message(state, message),
+                format!("**Changed the message of reminder `{}`.**", id),
+            ),
+            Change::Time { interval, given, message } => {
+                let timer = match message {
+                    Some(message) => timer.with_message(state, message),
+                    None => timer,
+                };
+                match timer.state {
+                    TimerState::Running { .. } => (
+                        timer.with_end_time(state, SystemTime::now().checked_add(interval).ok_or(TOO_FAR_IN_FUTURE)?),
+                        format!(
+                            "**Reminder `{}` will now mention you in {} (`{}`).**",
+                            id,
+                            format_duration_approx(interval),
+                            given,
+                        ),
+                    ),
+                    TimerState::Paused { .. } => {
+                        let mut timer = timer;
+                        timer.state = TimerState::Paused { remaining: interval };
+                        (timer, format!(
+                            "**Reminder `{}` now has {} remaining (`{}`).** It is still paused.",
+                            id,
+                            format_duration_approx(interval),
+                            given,
+                        ))
+                    },
+                }
+            },
+        };
+        timers.insert(id, timer);
+        database.set_user_field(ctxt.trigger.author_id(), UserField::Timers(timers)).await;
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&output)?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_only() {
+        let (id, change) = parse_edit("ABCD message stop watching tv").unwrap();
+        assert_eq!(id, "abcd");
+        assert_eq!(change, Change::Message("stop watching tv".to_owned()));
+    }
+
+    #[test]
+    fn time_only() {
+        let (id, change) = parse_edit("abcd 10 minutes").unwrap();
+        assert_eq!(id, "abcd");
+        assert_eq!(change, Change::Time {
+            interval: Duration::from_secs(600),
+            given: "10 minutes".to_owned(),
+            message: None,
+        });
+    }
+
+    #[test]
+    fn time_and_message() {
+        let (_, change) = parse_edit("abcd 10 minutes stop watching tv").unwrap();
+        assert_eq!(change, Change::Time {
+            interval: Duration::from_secs(600),
+            given: "10 minutes".to_owned(),
+            message: Some("stop watching tv".to_owned()),
+        });
+    }
+
+    #[test]
+    fn missing_change() {
+        assert!(parse_edit("abcd").is_err());
+        assert!(parse_edit("abcd message").is_err());
+        assert!(parse_edit("abcd -10 minutes").is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+    ics::{uid, Calendar, Event},
+    timer::TimerState,
+    util::pluralize,
+};
+use std::{sync::Arc, time::SystemTime};
+use tokio::sync::Mutex;
+use twilight_model::http::attachment::Attachment;
+
+/// Export your reminders as an iCalendar (`.ics`) file, which can be imported into most calendar
+/// apps. Each reminder becomes an event at the time it fires, in UTC.
+///
+/// Paused reminders are not exported, since they have no time to fire at until they are resumed.
+#[derive(Clone, Info)]
+#[info(aliases = ["ics", "ical", "calendar"])]
+pub struct Ics;
+
+#[async_trait]
+impl Command for Ics {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let user_id = ctxt.trigger.author_id();
+        let timers = database.lock().await
+            .get_user(user_id).await
+            .timers
+            .clone();
+        if timers.is_empty() {
+            return Err("**You have no reminders to export.**".into());
+        }
+
+        let mut calendar = Calendar::default();
+        let mut paused = 0;
+        for timer in timers.values() {
+            let TimerState::Running { end_time } = timer.state else {
+                paused += 1;
+                continue;
+            };
+
+            let summary = timer.message.lines().next().unwrap_or_default();
+            calendar.events.push(Event {
+                uid: uid(user_id, &timer.id),
+                start: end_time,
+                summary: match summary.is_empty() {
+                    true => "CalcBot reminder".to_owned(),
+                    false => summary.to_owned(),
+                },
+                // the summary only shows the first line of the message
+                description: match timer.message.lines().count() {
+                    0 | 1 => None,
+                    _ => Some(timer.message.clone()),
+                },
+            });
+        }
+        if calendar.events.is_empty() {
+            return Err("**All of your reminders are paused, so there is nothing to export.**".into());
+        }
+
+        // sort by time, so the file reads in the same order as a calendar
+        calendar.events.sort_by_key(|event| event.start);
+
+        let mut content = format!("**Exported {}.**", pluralize(calendar.events.len(), "reminder"));
+        if paused > 0 {
+            content.push_str(&format!(
+                " Skipped {}, since paused reminders have no time to fire at.",
+                pluralize(paused, "paused reminder"),
+            ));
+        }
+
+        let file = Attachment::from_bytes(
+            "reminders.ics".to_owned(),
+            calendar.build(SystemTime::now()).into_bytes(),
+            0,
+        );
+        ctxt.trigger.reply(&state.http)
+            .content(&content)?
+            .attachments(&[file])?
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+pub mod ics;
+
+use calcbot_attrs::{Command, Info};
+use crate::commands::Info;
+
+/// Export your reminders to a file, so that you can use them in other apps. See the **children
+/// commands** field for the available formats.
+#[derive(Clone, Command, Info)]
+#[info(
+    aliases = ["export", "exp"],
+    syntax = ["<format>"],
+    examples = ["ics"],
+    children = [ics::Ics],
+)]
+pub struct Export;
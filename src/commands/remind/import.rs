@@ -0,0 +1,467 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use chrono::{DateTime, NaiveDateTime};
+use crate::{
+    commands::{Command, Context, TriggerEvent},
+    database::{user::{UserField, UserTimeZone}, Database},
+    error::Error,
+    global::State,
+    timer::{Timer, TimerState},
+    util::pluralize,
+};
+use serde::Deserialize;
+use std::{collections::{BTreeMap, HashMap}, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+use super::{check_message, pending_count, to_duration, MAX_MESSAGE_LENGTH};
+use tokio::sync::Mutex;
+
+/// The maximum number of reminders that can be imported in a single invocation.
+const MAX_IMPORTS: usize = 25;
+
+/// The maximum size of an attached import file, in bytes.
+const MAX_FILE_SIZE: u64 = 64 * 1024;
+
+/// A reminder parsed from an import file.
+struct ImportRow {
+    /// The time the reminder should fire.
+    end_time: SystemTime,
+
+    /// The reminder's message.
+    message: String,
+}
+
+/// The reason a row of an import file was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum RejectReason {
+    /// The row could not be parsed.
+    Malformed,
+
+    /// The reminder would have fired in the past.
+    InPast,
+
+    /// The reminder's message is too long to be sent.
+    MessageTooLong,
+
+    /// The import already contained the maximum number of reminders.
+    OverLimit,
+
+    /// The user already had the maximum number of reminders.
+    OverUserLimit,
+}
+
+impl RejectReason {
+    /// Returns a description of the reason, used in the import report.
+    fn description(&self) -> String {
+        match self {
+            RejectReason::Malformed => "could not be read".to_owned(),
+            RejectReason::InPast => "are in the past".to_owned(),
+            RejectReason::MessageTooLong => format!("have messages longer than {} characters", MAX_MESSAGE_LENGTH),
+            RejectReason::OverLimit => format!("exceeded the limit of {} reminders per import", MAX_IMPORTS),
+            RejectReason::OverUserLimit => "exceeded your limit of active reminders".to_owned(),
+        }
+    }
+}
+
+/// A file format that reminders can be imported from.
+trait ImportFormat: Sync {
+    /// The name of the format, shown in the import report.
+    fn name(&self) -> &'static str;
+
+    /// Returns true if the file contents look like they are in this format.
+    fn detect(&self, contents: &str) -> bool;
+
+    /// Parses the file contents into rows. Each row is either a reminder, or the reason it was
+    /// rejected. Dates and times without an offset are interpreted in the given time zone.
+    fn parse(&self, contents: &str, time_zone: &UserTimeZone) -> Vec<Result<ImportRow, RejectReason>>;
+}
+
+/// Creates an [`ImportRow`], rejecting it if the end time is in the past.
+fn row(end_time: SystemTime, message: String) -> Result<ImportRow, RejectReason> {
+    if end_time <= SystemTime::now() {
+        Err(RejectReason::InPast)
+    } else {
+        Ok(ImportRow { end_time, message })
+    }
+}
+
+/// Parses an absolute timestamp. RFC 3339 timestamps use their own offset, while ISO 8601
+/// timestamps without an offset (like `2025-12-25 09:00`) are interpreted in the given time zone.
+fn parse_timestamp(s: &str, time_zone: &UserTimeZone) -> Option<SystemTime> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(s) {
+        return Some(date_time.into());
+    }
+
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"]
+        .into_iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .and_then(|naive| time_zone.resolve_local(naive))
+        .map(SystemTime::from)
+}
+
+/// Parses a Unix timestamp in seconds. Returns [`None`] if the timestamp is negative, or too large
+/// to be represented.
+fn parse_unix_timestamp(seconds: f64) -> Option<SystemTime> {
+    UNIX_EPOCH.checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+}
+
+/// Parses a time that is either an absolute timestamp (see [`parse_timestamp`]) or a duration
+/// relative to now, like `10 minutes`.
+fn parse_when(s: &str, time_zone: &UserTimeZone) -> Option<SystemTime> {
+    parse_timestamp(s, time_zone).or_else(|| {
+        let (quantity, unit) = s.split_once(char::is_whitespace)?;
+        let quantity = quantity.parse::<f64>().ok()?;
+        SystemTime::now().checked_add(to_duration(quantity, unit.trim()).ok()?)
+    })
+}
+
+/// CalcBot's own format: the JSON object of timers keyed by ID, as stored in the database.
+struct Native;
+
+impl ImportFormat for Native {
+    fn name(&self) -> &'static str {
+        "CalcBot"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        serde_json::from_str::<HashMap<String, Timer>>(contents).is_ok()
+    }
+
+    fn parse(&self, contents: &str, _: &UserTimeZone) -> Vec<Result<ImportRow, RejectReason>> {
+        let Ok(timers) = serde_json::from_str::<HashMap<String, Timer>>(contents) else {
+            return vec![Err(RejectReason::Malformed)];
+        };
+
+        timers.into_values()
+            .map(|timer| {
+                let end_time = match timer.state {
+                    TimerState::Running { end_time } => end_time,
+                    TimerState::Paused { remaining } => SystemTime::now()
+                        .checked_add(remaining)
+                        .ok_or(RejectReason::Malformed)?,
+                };
+                row(end_time, timer.message.clone())
+            })
+            .collect()
+    }
+}
+
+/// A single entry of a [`Json`] import file.
+#[derive(Deserialize)]
+struct JsonEntry {
+    /// A Unix timestamp in seconds, or an absolute timestamp string.
+    time: serde_json::Value,
+
+    /// The reminder's message.
+    #[serde(alias = "content", default)]
+    message: String,
+}
+
+/// A generic JSON format, used by many other reminder bots' exports: an array of objects, each
+/// with a `time` field (a Unix timestamp in seconds, or an ISO 8601 string) and a `message` or
+/// `content` field.
+struct Json;
+
+impl ImportFormat for Json {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    fn detect(&self, contents: &str) -> bool {
+        serde_json::from_str::<Vec<serde_json::Value>>(contents)
+            .map(|entries| entries.iter().all(|entry| entry.get("time").is_some()))
+            .unwrap_or(false)
+    }
+
+    fn parse(&self, contents: &str, time_zone: &UserTimeZone) -> Vec<Result<ImportRow, RejectReason>> {
+        let Ok(entries) = serde_json::from_str::<Vec<serde_json::Value>>(contents) else {
+            return vec![Err(RejectReason::Malformed)];
+        };
+
+        entries.into_iter()
+            .map(|entry| {
+                let entry = serde_json::from_value::<JsonEntry>(entry)
+                    .map_err(|_| RejectReason::Malformed)?;
+                let end_time = match entry.time {
+                    serde_json::Value::Number(seconds) => seconds.as_f64()
+                        .and_then(parse_unix_timestamp),
+                    serde_json::Value::String(timestamp) => parse_timestamp(&timestamp, time_zone),
+                    _ => None,
+                }.ok_or(RejectReason::Malformed)?;
+                row(end_time, entry.message)
+            })
+            .collect()
+    }
+}
+
+/// A simple CSV format with one `when,message` row per reminder. `when` is either an absolute
+/// timestamp or a relative duration like `10 minutes`. The header row is optional.
+struct Csv;
+
+impl ImportFormat for Csv {
+    fn name(&self) -> &'static str {
+        "CSV"
+    }
+
+    fn detect(&self, _: &str) -> bool {
+        // used as the fallback format
+        true
+    }
+
+    fn parse(&self, contents: &str, time_zone: &UserTimeZone) -> Vec<Result<ImportRow, RejectReason>> {
+        contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.eq_ignore_ascii_case("when,message"))
+            .map(|line| {
+                let (when, message) = line.split_once(',').unwrap_or((line, ""));
+                let end_time = parse_when(when.trim().trim_matches('"'), time_zone)
+                    .ok_or(RejectReason::Malformed)?;
+                row(end_time, message.trim().trim_matches('"').to_owned())
+            })
+            .collect()
+    }
+}
+
+/// The supported import formats, in the order they are detected.
+const FORMATS: [&dyn ImportFormat; 3] = [&Native, &Json, &Csv];
+
+/// Removes the code block surrounding pasted file contents, if there is one.
+fn strip_code_block(contents: &str) -> &str {
+    let contents = contents.trim();
+    match contents.strip_prefix("```").and_then(|inner| inner.strip_suffix("```")) {
+        // skip the language tag on the first line, if any
+        Some(inner) => inner.split_once('\n').map_or(inner, |(_, rest)| rest).trim(),
+        None => contents,
+    }
+}
+
+/// Import reminders from a file exported by CalcBot or another reminder bot. Attach the file to
+/// your message, or paste its contents after the command.
+///
+/// The following formats are supported:
+/// - **CalcBot**: CalcBot's own reminder data.
+/// - **JSON**: an array of objects with a `time` field (a Unix timestamp, or an ISO 8601 date and
+/// time) and a `message` or `content` field.
+/// - **CSV**: one `when,message` row per reminder, where `when` is an ISO 8601 date and time, or a
+/// duration like `10 minutes`.
+///
+/// Dates and times without a time zone are interpreted in your time zone, set with
+/// `{prefix}settings timezone`. Up to 25 reminders can be imported at once.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["import", "imp"],
+    syntax = ["<attached file>", "<file contents>"],
+    examples = ["2025-12-25T09:00:00Z,open presents"],
+)]
+pub struct Import;
+
+#[async_trait]
+impl Command for Import {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let attachment = match ctxt.trigger.event {
+            TriggerEvent::Message(msg) => msg.attachments.first(),
+            TriggerEvent::Interaction(_) => None,
+        };
+        let downloaded;
+        let contents = match attachment {
+            Some(attachment) => {
+                if attachment.size > MAX_FILE_SIZE {
+                    return Err("**The attached file is too large to import.**".into());
+                }
+
+                downloaded = reqwest::get(&attachment.url)
+                    .await
+                    .map_err(|_| "**Could not download the attached file. Please try again in a few seconds.**")?
+                    .text()
+                    .await
+                    .map_err(|_| "**The attached file is not a text file.**")?;
+                downloaded.trim()
+            },
+            None => strip_code_block(ctxt.raw_input),
+        };
+
+        if contents.is_empty() {
+            return Err("**You must attach or paste a file to import reminders from.**".into());
+        }
+
+        let format = FORMATS.into_iter()
+            .find(|format| format.detect(contents))
+            .expect("the CSV format accepts anything");
+
+        let (existing, time_zone) = {
+            let mut database = database.lock().await;
+            let user = database.get_user(ctxt.trigger.author_id()).await;
+            let existing = pending_count(user.timers.values());
+            (existing, user.settings.time_zone)
+        };
+        let user_limit = state.max_timers.saturating_sub(existing);
+
+        let mut timers = Vec::new();
+        let mut rejected = BTreeMap::<RejectReason, Vec<usize>>::new();
+        for (i, row) in format.parse(contents, &time_zone).into_iter().enumerate() {
+            let row = row
+                .and_then(|row| match check_message(&row.message) {
+                    Ok(()) => Ok(row),
+                    Err(_) => Err(RejectReason::MessageTooLong),
+                })
+                .and_then(|row| match timers.len() {
+                    MAX_IMPORTS => Err(RejectReason::OverLimit),
+                    len if len >= user_limit => Err(RejectReason::OverUserLimit),
+                    _ => Ok(row),
+                });
+            match row {
+                Ok(row) => timers.push(Timer::running(
+                    state,
+                    ctxt.trigger.author_id(),
+                    ctxt.trigger.channel_id(),
+                    row.end_time,
+                    row.message,
+                )),
+                Err(reason) => rejected.entry(reason).or_default().push(i + 1),
+            }
+        }
+
+        let imported = timers.len();
+        if imported > 0 {
+            // add all timers at once, rather than committing them one by one
+            let mut database = database.lock().await;
+            let mut user_timers = database.get_user(ctxt.trigger.author_id())
+                .await
+                .timers
+                .clone();
+            user_timers.extend(timers.into_iter().map(|timer| (timer.id.clone(), timer)));
+            database.set_user_field(ctxt.trigger.author_id(), UserField::Timers(user_timers)).await;
+        }
+
+        let mut report = vec![format!(
+            "**Imported {} from a {} file.**",
+            pluralize(imported, "reminder"),
+            format.name(),
+        )];
+        for (reason, rows) in rejected {
+            let mut row_list = rows.iter()
+                .take(20)
+                .map(|row| row.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if rows.len() > 20 {
+                row_list.push_str(", ...");
+            }
+            report.push(format!("- {} {}: {}", pluralize(rows.len(), "row"), reason.description(), row_list));
+        }
+
+        ctxt.trigger.reply(&state.http)
+            .content(&report.join("\n"))?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use super::*;
+
+    const NATIVE: &str = include_str!("../../../tests/fixtures/remind_import/calcbot.json");
+    const JSON: &str = include_str!("../../../tests/fixtures/remind_import/reminders.json");
+    const CSV: &str = include_str!("../../../tests/fixtures/remind_import/reminders.csv");
+
+    /// Returns the time of the given date and time in UTC.
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> SystemTime {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap().into()
+    }
+
+    /// Detects the format of the given contents and returns its name.
+    fn detect(contents: &str) -> &'static str {
+        FORMATS.into_iter().find(|format| format.detect(contents)).unwrap().name()
+    }
+
+    /// Returns the messages of the imported rows, and the reasons the rest were rejected.
+    fn split(rows: Vec<Result<ImportRow, RejectReason>>) -> (Vec<String>, Vec<RejectReason>) {
+        let mut messages = Vec::new();
+        let mut reasons = Vec::new();
+        for row in rows {
+            match row {
+                Ok(row) => messages.push(row.message),
+                Err(reason) => reasons.push(reason),
+            }
+        }
+        (messages, reasons)
+    }
+
+    #[test]
+    fn detect_formats() {
+        assert_eq!(detect(NATIVE), "CalcBot");
+        assert_eq!(detect(JSON), "JSON");
+        assert_eq!(detect(CSV), "CSV");
+    }
+
+    #[test]
+    fn parse_native() {
+        let (mut messages, reasons) = split(Native.parse(NATIVE, &UserTimeZone::default()));
+        messages.sort();
+        assert_eq!(messages, ["renew passport", "stretch"]);
+        assert_eq!(reasons, [RejectReason::InPast]);
+    }
+
+    #[test]
+    fn parse_json() {
+        let rows = Json.parse(JSON, &UserTimeZone::default());
+        assert_eq!(rows[0].as_ref().unwrap().end_time, utc(2100, 1, 1, 0, 0));
+        assert_eq!(rows[1].as_ref().unwrap().end_time, utc(2099, 12, 25, 9, 0));
+
+        let (messages, reasons) = split(rows);
+        assert_eq!(messages, ["happy new year", "open presents", "fireworks"]);
+        assert_eq!(reasons, [
+            RejectReason::InPast,
+            RejectReason::Malformed,
+            RejectReason::Malformed,
+            RejectReason::Malformed,
+        ]);
+    }
+
+    #[test]
+    fn parse_csv() {
+        let rows = Csv.parse(CSV, &UserTimeZone::default());
+        assert_eq!(rows[0].as_ref().unwrap().end_time, utc(2099, 12, 25, 9, 0));
+
+        let (messages, reasons) = split(rows);
+        assert_eq!(messages, ["open presents", "fireworks", "check the oven"]);
+        assert_eq!(reasons, [RejectReason::InPast, RejectReason::Malformed, RejectReason::Malformed]);
+    }
+
+    #[test]
+    fn times_without_offset_use_time_zone() {
+        let eastern = UserTimeZone::Named(chrono_tz::America::New_York);
+        let rows = Csv.parse(CSV, &eastern);
+
+        // explicit offsets are kept
+        assert_eq!(rows[0].as_ref().unwrap().end_time, utc(2099, 12, 25, 9, 0));
+
+        // 2099-07-04 12:00 in New York is during daylight saving time
+        assert_eq!(rows[1].as_ref().unwrap().end_time, utc(2099, 7, 4, 16, 0));
+        assert_eq!(
+            parse_timestamp("2099-07-04 12:00", &UserTimeZone::Offset(60)),
+            Some(utc(2099, 7, 4, 11, 0)),
+        );
+    }
+
+    #[test]
+    fn out_of_range_unix_timestamps() {
+        assert_eq!(parse_unix_timestamp(0.0), Some(UNIX_EPOCH));
+        assert_eq!(parse_unix_timestamp(-1.0), None);
+        assert_eq!(parse_unix_timestamp(f64::NAN), None);
+        assert_eq!(parse_unix_timestamp(1e300), None);
+    }
+
+    #[test]
+    fn strip_pasted_code_block() {
+        assert_eq!(strip_code_block("```csv\n10 minutes,tea\n```"), "10 minutes,tea");
+        assert_eq!(strip_code_block("```10 minutes,tea```"), "10 minutes,tea");
+        assert_eq!(strip_code_block("10 minutes,tea"), "10 minutes,tea");
+    }
+}
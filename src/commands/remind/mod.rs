@@ -1,12 +1,16 @@
 // pub mod at;
 pub mod delete;
-// pub mod edit;
+pub mod edit;
 // pub mod every;
+pub mod export;
+pub mod import;
 // pub mod increment;
+pub mod on;
 // pub mod pause;
 // pub mod recur;
 // pub mod resume;
-// pub mod view;
+pub mod snooze;
+pub mod view;
 
 use async_trait::async_trait;
 use calcbot_attrs::Info;
@@ -14,12 +18,14 @@ use cas_math::unit_conversion::{unit::Time, Measurement, Quantity, Unit};
 use crate::{
     commands::{Command, Context},
     database::Database,
-    error::Error,
+    error::{Error, InvalidDuration, TooManyTimers},
     global::State,
     timer::Timer,
+    util::format_duration_approx,
 };
 use std::{sync::Arc, time::{Duration, SystemTime}};
 use tokio::sync::Mutex;
+use twilight_model::id::{marker::UserMarker, Id};
 
 /// Set a reminder with an optional message for a specified interval. You can find the available
 /// time units with `{prefix}unitconvert units`. You can view your reminders and their IDs with
@@ -37,6 +43,12 @@ use tokio::sync::Mutex;
     args = [f64, String, Unlimited],
     children = [
         delete::Delete,
+        edit::Edit,
+        export::Export,
+        import::Import,
+        on::On,
+        snooze::Snooze,
+        view::View,
     ],
 )]
 pub struct Remind;
@@ -52,6 +64,62 @@ pub struct Remind;
     //     view::View,
     // ],
 
+/// The maximum length of a reminder message, in characters. This leaves room for the mention and
+/// quoting added when the reminder is sent, which must fit in a Discord message.
+pub const MAX_MESSAGE_LENGTH: usize = 1500;
+
+/// The default maximum number of reminders a user can have at once, since every running reminder
+/// holds a task. This can be changed with the `MAX_TIMERS_PER_USER` environment variable.
+pub const MAX_TIMERS_PER_USER: usize = 100;
+
+/// The error shown when a reminder would end too far in the future for its end time to be
+/// represented.
+pub const TOO_FAR_IN_FUTURE: &str = "**That reminder would end too far in the future.**";
+
+/// Returns the number of the given reminders that have yet to be sent. Reminders that were sent are
+/// kept so that they can be snoozed, but do not count towards [`State::max_timers`].
+pub fn pending_count<'a>(timers: impl IntoIterator<Item = &'a Timer>) -> usize {
+    timers.into_iter()
+        .filter(|timer| timer.is_pending())
+        .count()
+}
+
+/// Returns an error if the given user cannot create another reminder, because they already have
+/// [`State::max_timers`] reminders that have yet to be sent.
+pub async fn check_timer_limit(
+    state: &State,
+    database: &Mutex<Database>,
+    user_id: Id<UserMarker>,
+) -> Result<(), TooManyTimers> {
+    let count = pending_count(database.lock().await.get_user(user_id).await.timers.values());
+    match count >= state.max_timers {
+        true => Err(TooManyTimers { limit: state.max_timers }),
+        false => Ok(()),
+    }
+}
+
+/// Returns an error if the given reminder message is too long to be sent.
+pub fn check_message(message: &str) -> Result<(), &'static str> {
+    match message.chars().count() > MAX_MESSAGE_LENGTH {
+        true => Err("**Reminder messages can be at most 1500 characters long.**"),
+        false => Ok(()),
+    }
+}
+
+/// Converts the given quantity of the given time unit to a [`Duration`]. Returns an error
+/// describing whether the unit is not a valid time unit, or the quantity is negative or too large.
+pub fn to_duration(quantity: f64, unit: &str) -> Result<Duration, InvalidDuration> {
+    let time_unit = Time::try_from(unit).map_err(|_| InvalidDuration::Unit(unit.to_string()))?;
+    if quantity.is_nan() || quantity < 0.0 {
+        return Err(InvalidDuration::Quantity(quantity));
+    }
+    let seconds = *Measurement::new(quantity, Unit::new(Quantity::Time(time_unit)))
+        .convert(Unit::new(Quantity::Time(Time::Second)))
+        .unwrap()
+        .value();
+    Duration::try_from_secs_f64(seconds).map_err(|_| InvalidDuration::TooLarge)
+}
+
 #[async_trait]
 impl Command for Remind {
     async fn execute<'c>(
@@ -61,19 +129,12 @@ impl Command for Remind {
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let (quantity, unit, message) = parse_args(ctxt.raw_input.split_whitespace().collect::<Vec<_>>())?;
+        check_message(&message)?;
+        check_timer_limit(state, database, ctxt.trigger.author_id()).await?;
+
+        let time_amount = to_duration(quantity, &unit)?;
 
-        let Ok(unit) = (&*unit).try_into() else {
-            ctxt.trigger.reply(&state.http)
-                .content(&format!("**`{unit}` is not a valid time unit.**"))?
-                .await?;
-            return Ok(());
-        };
-        let time_amount = Duration::from_secs_f64(*Measurement::new(quantity, Unit::new(Quantity::Time(unit)))
-            .convert(Unit::new(Quantity::Time(Time::Second)))
-            .unwrap()
-            .value());
-
-        let end_time = SystemTime::now() + time_amount;
+        let end_time = SystemTime::now().checked_add(time_amount).ok_or(TOO_FAR_IN_FUTURE)?;
         let timer = Timer::running(
             state,
             ctxt.trigger.author_id(),
@@ -87,9 +148,39 @@ impl Command for Remind {
         database.lock().await.add_timer(timer).await;
 
         ctxt.trigger.reply(&state.http)
-            .content(&format!("**You will be mentioned in this channel in `{quantity} {unit}`.** This reminder's ID is `{id}`."))?
+            .content(&format!(
+                "**You will be mentioned in this channel in {} (`{quantity} {unit}`).** This reminder's ID is `{id}`.",
+                format_duration_approx(time_amount),
+            ))?
             .await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_time_units() {
+        assert_eq!(to_duration(10.0, "minutes"), Ok(Duration::from_secs(600)));
+        assert_eq!(to_duration(0.0, "sec"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn invalid_unit() {
+        assert_eq!(to_duration(10.0, "parsecs"), Err(InvalidDuration::Unit("parsecs".to_string())));
+    }
+
+    #[test]
+    fn negative_quantity_is_not_an_invalid_unit() {
+        assert_eq!(to_duration(-5.0, "minutes"), Err(InvalidDuration::Quantity(-5.0)));
+    }
+
+    #[test]
+    fn quantity_too_large() {
+        assert_eq!(to_duration(f64::INFINITY, "minutes"), Err(InvalidDuration::TooLarge));
+        assert_eq!(to_duration(1e300, "sec"), Err(InvalidDuration::TooLarge));
+    }
+}
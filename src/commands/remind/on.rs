@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone};
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserTimeZone, Database},
+    error::Error,
+    global::State,
+    timer::Timer,
+    util::{format_duration_approx, parse_utc_offset},
+};
+use std::{sync::Arc, time::{Duration, SystemTime}};
+use super::{check_message, check_timer_limit};
+use tokio::sync::Mutex;
+
+/// A reminder parsed from the input of [`On`].
+#[derive(Debug, PartialEq)]
+struct Request {
+    /// The date and time the reminder ends, in the given or the user's UTC offset.
+    date_time: DateTime<FixedOffset>,
+
+    /// How long from now the reminder ends.
+    time_amount: Duration,
+
+    /// The message to send when the reminder ends.
+    message: String,
+}
+
+/// Parses the date, optional time and UTC offset, and message of a reminder. Local times without
+/// a UTC offset are resolved in the given time zone. Returns an error if the date and time does
+/// not exist or is before `now`.
+fn parse_request(input: &str, time_zone: UserTimeZone, now: SystemTime) -> Result<Request, &'static str> {
+    let mut words = input.split_whitespace().peekable();
+    let Some(date) = words.next().and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()) else {
+        return Err("**You must provide a date like `2025-12-25`.**");
+    };
+
+    // the time and offset are optional, so only consume them if they parse
+    let time = words.peek()
+        .and_then(|time| NaiveTime::parse_from_str(time, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+            .ok());
+    if time.is_some() {
+        words.next();
+    }
+    let offset = words.peek().and_then(|offset| parse_utc_offset(offset));
+    if offset.is_some() {
+        words.next();
+    }
+    let message = words.collect::<Vec<_>>().join(" ");
+    check_message(&message)?;
+
+    // named time zones are resolved with the offset in effect on the given date, so that
+    // daylight saving time is accounted for
+    let local = date.and_time(time.unwrap_or_else(|| NaiveTime::from_hms_opt(0, 0, 0).unwrap()));
+    let date_time = match offset {
+        Some(offset) => offset.from_local_datetime(&local).single(),
+        None => time_zone.resolve_local(local),
+    }.ok_or("**That date and time does not exist.**")?;
+    let Ok(time_amount) = SystemTime::from(date_time).duration_since(now) else {
+        return Err("**That date and time is in the past.**");
+    };
+
+    Ok(Request { date_time, time_amount, message })
+}
+
+/// Set a reminder for a specific date, and optionally a time (default midnight) and UTC offset
+/// (default your time zone, set with `{prefix}settings timezone`).
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["on"],
+    syntax = ["<yyyy-mm-dd> [hh:mm] [utc offset] [message]"],
+    examples = ["2025-12-25 09:00 open presents", "2025-12-25 09:00 -05:00 open presents"],
+)]
+pub struct On;
+
+#[async_trait]
+impl Command for On {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let time_zone = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .time_zone;
+        let Request { date_time, time_amount, message } = parse_request(ctxt.raw_input, time_zone, SystemTime::now())?;
+        check_timer_limit(state, database, ctxt.trigger.author_id()).await?;
+
+        let timer = Timer::running(
+            state,
+            ctxt.trigger.author_id(),
+            ctxt.trigger.channel_id(),
+            SystemTime::from(date_time),
+            message,
+        );
+        let id = timer.id.clone();
+        database.lock().await.add_timer(timer).await;
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "**You will be mentioned in this channel on {}** (in {}). This reminder's ID is `{}`.",
+                date_time.format("%Y-%m-%d %H:%M %:z"),
+                format_duration_approx(time_amount),
+                id,
+            ))?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    /// Returns the time 2025-01-01 00:00 UTC.
+    fn new_year() -> SystemTime {
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap().into()
+    }
+
+    #[test]
+    fn future_date() {
+        let request = parse_request("2025-12-25 09:00 -05:00 open presents", UserTimeZone::Offset(0), new_year()).unwrap();
+        assert_eq!(request.date_time.to_rfc3339(), "2025-12-25T09:00:00-05:00");
+        assert_eq!(request.time_amount, Duration::from_secs((358 * 24 + 14) * 3600));
+        assert_eq!(request.message, "open presents");
+    }
+
+    #[test]
+    fn future_date_in_time_zone() {
+        let request = parse_request("2025-01-02", UserTimeZone::Offset(60), new_year()).unwrap();
+        assert_eq!(request.date_time.to_rfc3339(), "2025-01-02T00:00:00+01:00");
+        assert_eq!(request.time_amount, Duration::from_secs(23 * 3600));
+        assert_eq!(request.message, "");
+    }
+
+    #[test]
+    fn past_date() {
+        assert_eq!(
+            parse_request("2024-12-25 09:00 open presents", UserTimeZone::Offset(0), new_year()),
+            Err("**That date and time is in the past.**"),
+        );
+    }
+
+    #[test]
+    fn missing_date() {
+        assert_eq!(
+            parse_request("tomorrow", UserTimeZone::Offset(0), new_year()),
+            Err("**You must provide a date like `2025-12-25`.**"),
+        );
+    }
+}
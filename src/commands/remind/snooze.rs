@@ -0,0 +1,115 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserField, Database},
+    error::Error,
+    global::State,
+    timer::TimerState,
+    util::format_duration_approx,
+};
+use std::{sync::Arc, time::{Duration, SystemTime}};
+use super::{to_duration, TOO_FAR_IN_FUTURE};
+use tokio::sync::Mutex;
+
+/// Returns when a reminder in the given state ends after being snoozed by the given interval. A
+/// reminder that was already sent is snoozed from now.
+fn snoozed_end_time(state: &TimerState, now: SystemTime, interval: Duration) -> Result<SystemTime, &'static str> {
+    match state {
+        TimerState::Running { end_time } => (*end_time).max(now).checked_add(interval).ok_or(TOO_FAR_IN_FUTURE),
+        TimerState::Paused { .. } => Err("**Paused reminders cannot be snoozed.**"),
+    }
+}
+
+/// Delay one of your reminders by the given interval. If the reminder has already been sent, it
+/// is sent again after the interval.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["snooze", "sz"],
+    syntax = ["<reminder id> <quantity> <time unit>"],
+    examples = ["abcd 10 minutes"],
+    args = [String, f64, String],
+)]
+pub struct Snooze;
+
+#[async_trait]
+impl Command for Snooze {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let (id, quantity, unit) = parse_args(ctxt.raw_input.split_whitespace().collect())?;
+        let id = id.to_lowercase();
+        if quantity <= 0.0 || !quantity.is_finite() {
+            return Err("**The interval to snooze by must be positive.**".into());
+        }
+        let interval = to_duration(quantity, &unit)?;
+
+        let mut database = database.lock().await;
+        let mut timers = database.get_user(ctxt.trigger.author_id())
+            .await
+            .timers
+            .clone();
+        let Some(timer) = timers.remove(&id) else {
+            drop(database);
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("**Reminder `{}` no longer exists.**", id))?
+                .await?;
+            return Ok(());
+        };
+
+        let now = SystemTime::now();
+        let end_time = snoozed_end_time(&timer.state, now, interval)?;
+        timers.insert(id.clone(), timer.with_end_time(state, end_time));
+        database.set_user_field(ctxt.trigger.author_id(), UserField::Timers(timers)).await;
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "**Snoozed reminder `{}`.** You will be mentioned in {}.",
+                id,
+                format_duration_approx(end_time.duration_since(now).unwrap_or_default()),
+            ))?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn pending_reminder_is_delayed() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let state = TimerState::Running { end_time: now + MINUTE };
+        assert_eq!(snoozed_end_time(&state, now, MINUTE), Ok(now + 2 * MINUTE));
+    }
+
+    #[test]
+    fn sent_reminder_is_snoozed_from_now() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000);
+        let state = TimerState::Running { end_time: now - MINUTE };
+        assert_eq!(snoozed_end_time(&state, now, MINUTE), Ok(now + MINUTE));
+    }
+
+    #[test]
+    fn paused_reminder_is_not_snoozed() {
+        let state = TimerState::Paused { remaining: MINUTE };
+        assert!(snoozed_end_time(&state, SystemTime::now(), MINUTE).is_err());
+    }
+
+    #[test]
+    fn huge_interval_is_rejected() {
+        let now = SystemTime::now();
+        let state = TimerState::Running { end_time: now };
+        let interval = to_duration(500_000_000_000.0, "yr").unwrap();
+        assert_eq!(snoozed_end_time(&state, now, interval), Err(TOO_FAR_IN_FUTURE));
+    }
+}
@@ -0,0 +1,234 @@
+use async_trait::async_trait;
+use calcbot_attrs::{Info, ParseArg};
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+    timer::{Timer, TimerState},
+    util::format_duration_approx,
+};
+use std::{sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
+use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
+
+/// The maximum number of reminders shown, as Discord allows at most 25 fields in an embed.
+const MAX_FIELDS: usize = 25;
+
+/// The maximum length of a reminder message shown in the list, in characters. Embed field values
+/// can be at most 1024 characters long.
+const MAX_MESSAGE_PREVIEW: usize = 200;
+
+/// Which reminders to show, as given by the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ParseArg)]
+enum Filter {
+    #[arg(aliases = ["all"])]
+    All,
+
+    #[arg(aliases = ["running", "active"])]
+    Running,
+
+    #[arg(aliases = ["paused"])]
+    Paused,
+
+    #[arg(aliases = ["sent", "done"])]
+    Sent,
+}
+
+impl Filter {
+    /// Returns true if the given timer should be shown.
+    fn matches(self, timer: &Timer) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Running => timer.is_running() && timer.is_pending(),
+            Filter::Paused => !timer.is_running(),
+            Filter::Sent => !timer.is_pending(),
+        }
+    }
+}
+
+/// Returns the key the given timer is sorted by in the list.
+///
+/// Reminders are shown soonest first, with paused reminders after all running ones, and sent
+/// reminders last. Reminders that fire at the same time are ordered by when they were set, so the
+/// order is the same every time.
+fn sort_key(timer: &Timer) -> ((u8, Duration), SystemTime, String) {
+    let due = match timer.state {
+        TimerState::Running { end_time } => (
+            if timer.is_pending() { 0 } else { 2 },
+            end_time.duration_since(UNIX_EPOCH).unwrap_or_default(),
+        ),
+        TimerState::Paused { remaining } => (1, remaining),
+    };
+    (due, timer.created_at, timer.id.clone())
+}
+
+/// Builds the embed field describing the given timer.
+fn timer_field(timer: &Timer) -> EmbedFieldBuilder {
+    let when = match timer.state {
+        TimerState::Running { end_time } if !timer.is_pending() => {
+            let unix = end_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            format!("Sent <t:{}:R>", unix)
+        },
+        TimerState::Running { end_time } => {
+            let unix = end_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            let remaining = end_time.duration_since(SystemTime::now()).unwrap_or_default();
+            format!("<t:{}:f> (in {})", unix, format_duration_approx(remaining))
+        },
+        TimerState::Paused { remaining } => format!("Paused, {} remaining", format_duration_approx(remaining)),
+    };
+
+    let message = match timer.message.chars().count() {
+        0 => "_no message provided_".to_owned(),
+        len if len > MAX_MESSAGE_PREVIEW => {
+            format!("{}…", timer.message.chars().take(MAX_MESSAGE_PREVIEW).collect::<String>())
+        },
+        _ => timer.message.clone(),
+    };
+
+    let created = match timer.created_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() {
+        0 => String::new(),
+        unix => format!(" • set <t:{}:R>", unix),
+    };
+
+    EmbedFieldBuilder::new(format!("`{}`", timer.id), format!("{}{}\n{}", when, created, message))
+}
+
+/// View your reminders and their IDs. Give `running`, `paused`, or `sent` to only show reminders
+/// that are running, paused, or have already been sent. Sent reminders can still be snoozed.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["view", "list", "ls"],
+    syntax = ["[all | running | paused | sent]"],
+    examples = ["", "running", "paused", "sent"],
+)]
+pub struct View;
+
+#[async_trait]
+impl Command for View {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let filter = match ctxt.raw_input.split_whitespace().next().map(str::parse::<Filter>) {
+            Some(Ok(filter)) => filter,
+            Some(Err(err)) => return Err(err.into()),
+            None => Filter::All,
+        };
+
+        let timers = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .timers
+            .clone();
+        let running = timers.values().filter(|timer| Filter::Running.matches(timer)).count();
+        let paused = timers.values().filter(|timer| Filter::Paused.matches(timer)).count();
+        let sent = timers.len() - running - paused;
+
+        let mut shown = timers.values()
+            .filter(|timer| filter.matches(timer))
+            .collect::<Vec<_>>();
+        shown.sort_by_key(|timer| sort_key(timer));
+
+        if shown.is_empty() {
+            let content = match filter {
+                Filter::All => "**You have no reminders.**",
+                Filter::Running => "**You have no running reminders.**",
+                Filter::Paused => "**You have no paused reminders.**",
+                Filter::Sent => "**You have no sent reminders.**",
+            };
+            return Err(content.into());
+        }
+
+        let mut footer = match filter {
+            Filter::All => format!("{} running, {} paused, {} sent", running, paused, sent),
+            Filter::Running => format!("{} running ({} paused, {} sent not shown)", running, paused, sent),
+            Filter::Paused => format!("{} paused ({} running, {} sent not shown)", paused, running, sent),
+            Filter::Sent => format!("{} sent ({} running, {} paused not shown)", sent, running, paused),
+        };
+        if shown.len() > MAX_FIELDS {
+            footer.push_str(&format!(" • showing the first {}", MAX_FIELDS));
+        }
+
+        let embed = shown.into_iter()
+            .take(MAX_FIELDS)
+            .fold(
+                EmbedBuilder::new()
+                    .title("Your reminders")
+                    .color(0x66d2e8),
+                |embed, timer| embed.field(timer_field(timer)),
+            )
+            .footer(EmbedFooterBuilder::new(footer))
+            .build();
+        ctxt.trigger.reply(&state.http)
+            .embeds(&[embed])?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Creates a reminder with the given ID that ends the given number of seconds from now, or is
+    /// paused with that many seconds remaining if `paused` is true.
+    fn reminder(id: &str, seconds: i64, paused: bool) -> Timer {
+        let state = match paused {
+            true => json!({ "Paused": { "remaining": { "secs": seconds, "nanos": 0 } } }),
+            false => {
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+                json!({ "Running": { "end_time": { "secs_since_epoch": now + seconds, "nanos_since_epoch": 0 } } })
+            },
+        };
+        serde_json::from_value(json!({
+            "id": id,
+            "user_id": "1",
+            "channel_id": "2",
+            "state": state,
+            "message": "",
+        })).unwrap()
+    }
+
+    #[test]
+    fn running_and_paused_reminders_are_filtered() {
+        let running = reminder("abcd", 60, false);
+        let paused = reminder("efgh", 60, true);
+
+        assert!(Filter::All.matches(&running));
+        assert!(Filter::Running.matches(&running));
+        assert!(!Filter::Paused.matches(&running));
+        assert!(!Filter::Sent.matches(&running));
+
+        assert!(Filter::All.matches(&paused));
+        assert!(!Filter::Running.matches(&paused));
+        assert!(Filter::Paused.matches(&paused));
+        assert!(!Filter::Sent.matches(&paused));
+    }
+
+    #[test]
+    fn sent_reminders_are_filtered() {
+        let sent = reminder("abcd", -60, false);
+        assert!(!Filter::Running.matches(&sent));
+        assert!(!Filter::Paused.matches(&sent));
+        assert!(Filter::Sent.matches(&sent));
+    }
+
+    #[test]
+    fn reminders_are_sorted_chronologically() {
+        let timers = [
+            reminder("sent", -60, false),
+            reminder("paused", 10, true),
+            reminder("later", 120, false),
+            reminder("sooner", 60, false),
+            reminder("also sooner", 60, false),
+        ];
+        let mut sorted = timers.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|timer| sort_key(timer));
+        let ids = sorted.iter().map(|timer| timer.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["also sooner", "sooner", "later", "paused", "sent"]);
+    }
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserField, Database},
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// View or set whether the digits of long numbers are grouped with commas, like `1,234,567`.
+/// (default **off**)
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["groupdigits", "group", "gd"],
+    syntax = ["", "[on | off]"],
+    examples = ["on"],
+)]
+pub struct GroupDigits;
+
+#[async_trait]
+impl Command for GroupDigits {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut settings = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .clone();
+
+        let group_digits = match ctxt.raw_input.to_lowercase().as_str() {
+            "on" | "true" | "yes" => true,
+            "off" | "false" | "no" => false,
+            _ => {
+                ctxt.trigger.reply(&state.http)
+                    .content(&format!(
+                        "Digit grouping is currently **{}**",
+                        if settings.group_digits { "on" } else { "off" },
+                    ))?
+                    .await?;
+                return Ok(());
+            },
+        };
+
+        settings.group_digits = group_digits;
+        database.lock().await
+            .set_user_field(ctxt.trigger.author_id(), UserField::Settings(settings)).await;
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "Turned digit grouping **{}**",
+                if group_digits { "on" } else { "off" },
+            ))?
+            .await?;
+
+        Ok(())
+    }
+}
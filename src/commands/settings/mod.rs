@@ -0,0 +1,66 @@
+pub mod group_digits;
+pub mod notation;
+pub mod server;
+pub mod timezone;
+
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::Database,
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// View your personal CalcBot settings. To change a setting, run the corresponding child command,
+/// like `{prefix}settings notation engineering`.
+#[derive(Clone, Info)]
+#[info(
+    category = "Settings",
+    aliases = ["settings", "setting", "set"],
+    children = [
+        group_digits::GroupDigits,
+        notation::Notation,
+        server::Server,
+        timezone::Timezone,
+    ],
+)]
+pub struct Settings;
+
+#[async_trait]
+impl Command for Settings {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let settings = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .clone();
+
+        let embed = EmbedBuilder::new()
+            .title("Your settings")
+            .color(0x8f8f8f)
+            .field(EmbedFieldBuilder::new("Number notation (`notation`)", settings.notation.to_string()).inline())
+            .field(EmbedFieldBuilder::new(
+                "Digit grouping (`groupdigits`)",
+                if settings.group_digits { "on" } else { "off" },
+            ).inline())
+            .field(EmbedFieldBuilder::new(
+                "Time zone (`timezone`)",
+                settings.time_zone.to_string(),
+            ).inline())
+            .build();
+
+        ctxt.trigger.reply(&state.http)
+            .embeds(&[embed])?
+            .await?;
+
+        Ok(())
+    }
+}
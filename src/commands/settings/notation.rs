@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use crate::{
+    commands::{Command, Context},
+    database::{user::UserField, Database},
+    error::Error,
+    global::State,
+    util,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// View or set how numbers are displayed in the output of calculation commands. (default
+/// **auto**)
+///
+/// - **auto**: numbers are displayed as the command normally displays them.
+/// - **scientific**: numbers are displayed in scientific notation, like `1.25e4`.
+/// - **engineering**: the exponent is always a multiple of three, like `12.5e3`.
+/// - **plain**: numbers are displayed in full, without an exponent.
+#[derive(Clone, Info)]
+#[info(
+    syntax = ["", "[auto | scientific | engineering | plain]"],
+    examples = ["engineering"],
+)]
+pub struct Notation;
+
+#[async_trait]
+impl Command for Notation {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut settings = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .clone();
+
+        if ctxt.raw_input.is_empty() {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!("Current number notation: **{}**", settings.notation))?
+                .await?;
+            return Ok(());
+        }
+
+        let Ok(notation) = ctxt.raw_input.parse::<util::Notation>() else {
+            return Err("**The notation must be one of `auto`, `scientific`, `engineering`, or `plain`.**".into());
+        };
+
+        settings.notation = notation;
+        database.lock().await
+            .set_user_field(ctxt.trigger.author_id(), UserField::Settings(settings)).await;
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("Set number notation to **{}**", notation))?
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use async_trait::async_trait;
+use calcbot_attrs::{Info, ParseArg};
+use crate::{
+    commands::{prefix::can_manage_server, Command, Context},
+    database::{server::{ServerData, ServerField}, Database},
+    error::Error,
+    global::State,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+
+/// A server setting that can be turned on or off, as given by the user.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ParseArg)]
+enum ServerSetting {
+    #[arg(aliases = ["inlinemath", "inline", "im"])]
+    InlineMath,
+
+    #[arg(aliases = ["attribution", "attributionfooter", "attribute"])]
+    AttributionFooter,
+
+    #[arg(aliases = ["dictionarythreads", "threads", "dt"])]
+    DictionaryThreads,
+}
+
+impl ServerSetting {
+    /// Returns a human-readable name for the setting.
+    fn name(self) -> &'static str {
+        match self {
+            ServerSetting::InlineMath => "inline math",
+            ServerSetting::AttributionFooter => "attribution",
+            ServerSetting::DictionaryThreads => "dictionary threads",
+        }
+    }
+
+    /// Returns the current value of the setting in the given server data.
+    fn get(self, data: &ServerData) -> bool {
+        match self {
+            ServerSetting::InlineMath => data.inline_math,
+            ServerSetting::AttributionFooter => data.attribution_footer,
+            ServerSetting::DictionaryThreads => data.dictionary_threads,
+        }
+    }
+
+    /// Returns the field that sets the setting to the given value.
+    fn field(self, value: bool) -> ServerField {
+        match self {
+            ServerSetting::InlineMath => ServerField::InlineMath(value),
+            ServerSetting::AttributionFooter => ServerField::AttributionFooter(value),
+            ServerSetting::DictionaryThreads => ServerField::DictionaryThreads(value),
+        }
+    }
+}
+
+/// Parses the value of a setting that can be turned on or off.
+fn parse_switch(input: &str) -> Option<bool> {
+    match input.to_lowercase().as_str() {
+        "on" | "true" | "yes" => Some(true),
+        "off" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Formats the value of a setting that can be turned on or off.
+fn format_switch(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+/// View or change CalcBot's settings in this server. Changing a setting requires the Manage Server
+/// permission.
+///
+/// `inlinemath`: evaluate `{{expression}}` spans in any message. (default **off**)
+///
+/// `attribution`: end replies to calculation commands with the name of the user who ran the
+/// command and the time, so that doctored screenshots are easier to spot. (default **off**)
+///
+/// `dictionarythreads`: post dictionary entries in a new thread. (default **off**)
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["server", "guild"],
+    syntax = ["", "[setting]", "[setting] [on | off]"],
+    examples = ["", "inlinemath on"],
+)]
+pub struct Server;
+
+#[async_trait]
+impl Command for Server {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let Some(guild_id) = ctxt.trigger.guild_id() else {
+            return Err("**Server settings can only be changed in a server.**".into());
+        };
+
+        let data = database.lock().await
+            .get_server(guild_id).await
+            .clone();
+
+        let mut args = ctxt.raw_input.split_whitespace();
+        let setting = match args.next().map(str::parse::<ServerSetting>) {
+            Some(Ok(setting)) => setting,
+            Some(Err(err)) => return Err(err.into()),
+            None => {
+                let embed = EmbedBuilder::new()
+                    .title("Server settings")
+                    .color(0x8f8f8f)
+                    .field(EmbedFieldBuilder::new(
+                        "Inline math (`inlinemath`)",
+                        format_switch(data.inline_math),
+                    ).inline())
+                    .field(EmbedFieldBuilder::new(
+                        "Attribution (`attribution`)",
+                        format_switch(data.attribution_footer),
+                    ).inline())
+                    .field(EmbedFieldBuilder::new(
+                        "Dictionary threads (`dictionarythreads`)",
+                        format_switch(data.dictionary_threads),
+                    ).inline())
+                    .build();
+                ctxt.trigger.reply(&state.http)
+                    .embeds(&[embed])?
+                    .await?;
+                return Ok(());
+            },
+        };
+
+        let Some(value) = args.next().and_then(parse_switch) else {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!(
+                    "The {} setting is currently **{}** in this server",
+                    setting.name(),
+                    format_switch(setting.get(&data)),
+                ))?
+                .await?;
+            return Ok(());
+        };
+
+        if !can_manage_server(state, ctxt.trigger) {
+            return Err("**You need the Manage Server permission to change server settings.**".into());
+        }
+
+        let mut database = database.lock().await;
+        database.set_server_field(guild_id, setting.field(value)).await;
+
+        // messages are filtered by the prefix hint before reaching the handler
+        let data = database.get_server(guild_id).await;
+        state.set_prefix_hint(guild_id, &data.prefix, data.inline_math);
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("Turned {} **{}** in this server", setting.name(), format_switch(value)))?
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Takes a server setting as its argument.
+    #[derive(Clone, Info)]
+    #[info(args = [ServerSetting])]
+    struct TakesSetting;
+
+    #[test]
+    fn parse_setting() {
+        assert_eq!("inlinemath".parse::<ServerSetting>().ok(), Some(ServerSetting::InlineMath));
+        assert_eq!("Inline".parse::<ServerSetting>().ok(), Some(ServerSetting::InlineMath));
+        assert!("prefix".parse::<ServerSetting>().is_err());
+    }
+
+    #[test]
+    fn invalid_setting_lists_options() {
+        let err = "prefix".parse::<ServerSetting>().unwrap_err();
+        assert_eq!(err.value, "prefix");
+        assert_eq!(err.expected(), "one of: `inlinemath`, `attribution`, `dictionarythreads`");
+    }
+
+    #[test]
+    fn invalid_argument_lists_options() {
+        let err = parse_args(vec!["prefix"]).unwrap_err();
+        assert!(format!("{:?}", err).contains("one of: `inlinemath`, `attribution`, `dictionarythreads`"));
+    }
+
+    #[test]
+    fn parse_other_settings() {
+        assert_eq!("attribution".parse::<ServerSetting>().ok(), Some(ServerSetting::AttributionFooter));
+        assert_eq!("threads".parse::<ServerSetting>().ok(), Some(ServerSetting::DictionaryThreads));
+    }
+
+    #[test]
+    fn parse_switch_values() {
+        assert_eq!(parse_switch("on"), Some(true));
+        assert_eq!(parse_switch("YES"), Some(true));
+        assert_eq!(parse_switch("off"), Some(false));
+        assert_eq!(parse_switch("false"), Some(false));
+        assert_eq!(parse_switch("maybe"), None);
+    }
+
+    #[test]
+    fn field_round_trips() {
+        for setting in [ServerSetting::InlineMath, ServerSetting::AttributionFooter, ServerSetting::DictionaryThreads] {
+            for value in [true, false] {
+                let mut data = ServerData::default();
+                setting.field(value).apply(&mut data);
+                assert_eq!(setting.get(&data), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use chrono::Utc;
+use chrono_tz::Tz;
+use crate::{
+    commands::{Command, Context},
+    database::{user::{UserField, UserTimeZone}, Database},
+    error::Error,
+    global::State,
+    util::parse_utc_offset,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Returns the current time in the given time zone, like `14:05`.
+fn local_time(time_zone: UserTimeZone) -> String {
+    let now = Utc::now();
+    now.with_timezone(&time_zone.offset_at(now)).format("%H:%M").to_string()
+}
+
+/// View or set your time zone, either as a [time zone
+/// name](https://en.wikipedia.org/wiki/List_of_tz_database_time_zones) like `America/New_York`,
+/// which follows daylight saving time, or as a fixed offset from UTC like `-5` or `+5:30`. This is
+/// used by commands that take a date and time, like `{prefix}remind on`, when no offset is given.
+/// (default **UTC**)
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["timezone", "tz"],
+    syntax = ["", "[time zone name | utc offset]"],
+    examples = ["America/New_York", "-5", "+5:30"],
+)]
+pub struct Timezone;
+
+#[async_trait]
+impl Command for Timezone {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut settings = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .settings
+            .clone();
+
+        if ctxt.raw_input.is_empty() {
+            ctxt.trigger.reply(&state.http)
+                .content(&format!(
+                    "Your time zone is currently **{}** (it is {} for you)",
+                    settings.time_zone,
+                    local_time(settings.time_zone),
+                ))?
+                .await?;
+            return Ok(());
+        }
+
+        let time_zone = if let Some(offset) = parse_utc_offset(ctxt.raw_input) {
+            UserTimeZone::Offset((offset.local_minus_utc() / 60) as i16)
+        } else if let Ok(tz) = ctxt.raw_input.parse::<Tz>() {
+            UserTimeZone::Named(tz)
+        } else {
+            return Err("**The time zone must be a time zone name like `America/New_York`, or an offset from UTC like `-5` or `+5:30`.**".into());
+        };
+
+        settings.time_zone = time_zone;
+        database.lock().await
+            .set_user_field(ctxt.trigger.author_id(), UserField::Settings(settings)).await;
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!(
+                "Set your time zone to **{}** (it is {} for you)",
+                time_zone,
+                local_time(time_zone),
+            ))?
+            .await?;
+
+        Ok(())
+    }
+}
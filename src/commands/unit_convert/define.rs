@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use calcbot_attrs::Info;
+use cas_math::unit_conversion::Quantity;
+use crate::{
+    commands::{Command, Context},
+    database::{user::{CustomUnit, UserField}, Database},
+    error::Error,
+    global::State,
+    util::Number,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// The maximum number of units a user can define.
+const MAX_CUSTOM_UNITS: usize = 50;
+
+/// Define your own unit in terms of a built-in unit, which you can then use with
+/// `{prefix}unitconvert`. Your units are only available to you, and override built-in units with
+/// the same name.
+///
+/// Defining a unit that already exists replaces it.
+#[derive(Clone, Info)]
+#[info(
+    aliases = ["define", "def"],
+    syntax = ["<quantity> <name> = <quantity> <built-in unit>"],
+    examples = ["1 cupcake = 80 g", "3 steps = 2 m"],
+)]
+pub struct Define;
+
+#[async_trait]
+impl Command for Define {
+    async fn execute<'c>(
+        &'c self,
+        state: &Arc<State>,
+        database: &Arc<Mutex<Database>>,
+        ctxt: Context<'c>,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let raw_args = ctxt.raw_input.split_whitespace().collect::<Vec<_>>();
+        let [custom_amount, name, "=", amount, unit] = raw_args.as_slice() else {
+            return Err("**Define a unit like `1 cupcake = 80 g`.**".into());
+        };
+
+        if name.contains(['/', '^']) || name.parse::<f64>().is_ok() {
+            return Err("**Unit names cannot be numbers, or contain `/` or `^`.**".into());
+        }
+        if Quantity::try_from(*unit).is_err() {
+            ctxt.trigger.unattributed().reply(&state.http)
+                .content(&format!("**`{}` is not a built-in unit.** Custom units must be defined in terms of a built-in unit.", unit))?
+                .await?;
+            return Ok(());
+        }
+
+        let custom_amount = custom_amount.parse::<Number>()?.0;
+        let amount = amount.parse::<Number>()?.0;
+        if custom_amount == 0.0 || !(amount / custom_amount).is_finite() {
+            return Err("**The quantities must be finite, and cannot be zero.**".into());
+        }
+
+        let mut database = database.lock().await;
+        let mut custom_units = database.get_user(ctxt.trigger.author_id())
+            .await
+            .custom_units
+            .clone();
+        if custom_units.len() >= MAX_CUSTOM_UNITS && !custom_units.contains_key(*name) {
+            return Err("**You have reached the limit of 50 custom units.**".into());
+        }
+
+        custom_units.insert(name.to_string(), CustomUnit {
+            amount: amount / custom_amount,
+            unit: unit.to_string(),
+        });
+        database.set_user_field(ctxt.trigger.author_id(), UserField::CustomUnits(custom_units)).await;
+        drop(database);
+
+        ctxt.trigger.reply(&state.http)
+            .content(&format!("**Defined `{}`** as `{} {}`.", name, amount / custom_amount, unit))?
+            .await?;
+
+        Ok(())
+    }
+}
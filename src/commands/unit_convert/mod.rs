@@ -1,3 +1,4 @@
+pub mod define;
 pub mod units;
 
 use async_trait::async_trait;
@@ -5,17 +6,183 @@ use calcbot_attrs::Info;
 use cas_math::unit_conversion::{Measurement, Quantity};
 use crate::{
     commands::{Command, Context},
-    database::Database,
+    database::{user::{CustomUnit, UserSettings}, Database},
     error::Error,
     global::State,
+    util::{format_number, Number},
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
+use units::dimension;
 
-/// Convert a quantity from one unit / ratio to another.
+/// A temperature scale.
 ///
-/// CalcBot supports server-unique and user-unique custom ratios; run `{prefix}unitconvert
-/// customratio` for more info. Run `{prefix}unitconvert units` to see a list of supported units.
+/// Converting between temperature scales needs an offset as well as a ratio, so temperatures are
+/// converted here instead of with [`Measurement::convert`], which only applies ratios.
+#[derive(Clone, Copy, Debug)]
+enum Temperature {
+    /// Degrees Celsius, `C`.
+    Celsius,
+
+    /// Degrees Fahrenheit, `F`.
+    Fahrenheit,
+
+    /// Kelvin, `K`.
+    Kelvin,
+
+    /// Degrees Rankine, `R`.
+    Rankine,
+}
+
+impl Temperature {
+    /// Returns the temperature scale with the given unit name, if any.
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim_start_matches('°') {
+            "C" | "degC" => Some(Temperature::Celsius),
+            "F" | "degF" => Some(Temperature::Fahrenheit),
+            "K" => Some(Temperature::Kelvin),
+            "R" | "degR" => Some(Temperature::Rankine),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the given unit is a compound unit containing a temperature, like `C/s`.
+    /// These cannot be converted, since the offset of the temperature makes the result ambiguous.
+    fn is_in_compound(name: &str) -> bool {
+        name.contains(['/', '*', '^'])
+            && name.split(['/', '*', '^']).any(|part| Temperature::parse(part).is_some())
+    }
+
+    /// Converts a temperature on this scale to kelvin.
+    fn to_kelvin(self, value: f64) -> f64 {
+        match self {
+            Temperature::Celsius => value + 273.15,
+            Temperature::Fahrenheit => (value + 459.67) * 5.0 / 9.0,
+            Temperature::Kelvin => value,
+            Temperature::Rankine => value * 5.0 / 9.0,
+        }
+    }
+
+    /// Converts a temperature in kelvin to this scale.
+    fn kelvin_to_scale(self, kelvin: f64) -> f64 {
+        match self {
+            Temperature::Celsius => kelvin - 273.15,
+            Temperature::Fahrenheit => kelvin * 9.0 / 5.0 - 459.67,
+            Temperature::Kelvin => kelvin,
+            Temperature::Rankine => kelvin * 9.0 / 5.0,
+        }
+    }
+}
+
+/// A unit that a quantity can be converted from or to.
+enum ResolvedUnit<'a> {
+    /// A temperature scale.
+    Temperature(Temperature),
+
+    /// An amount of a built-in unit, along with the built-in unit's name.
+    Unit(f64, Quantity, &'a str),
+}
+
+/// Resolves the name of a unit. The user's custom units take priority over temperature scales and
+/// built-in units.
+fn resolve_unit<'a>(name: &'a str, custom_units: &'a HashMap<String, CustomUnit>) -> Option<ResolvedUnit<'a>> {
+    match custom_units.get(name) {
+        Some(custom) => Some(ResolvedUnit::Unit(
+            custom.amount,
+            Quantity::try_from(custom.unit.as_str()).ok()?,
+            &custom.unit,
+        )),
+        None => match Temperature::parse(name) {
+            Some(scale) => Some(ResolvedUnit::Temperature(scale)),
+            None => Some(ResolvedUnit::Unit(1.0, Quantity::try_from(name).ok()?, name)),
+        },
+    }
+}
+
+/// Converts the quantity from one unit to another, or returns the message describing why it
+/// cannot be converted.
+fn convert(quantity: f64, unit: &ResolvedUnit, target: &ResolvedUnit) -> Result<f64, String> {
+    match (unit, target) {
+        (ResolvedUnit::Temperature(scale), ResolvedUnit::Temperature(target_scale)) => {
+            Ok(target_scale.kelvin_to_scale(scale.to_kelvin(quantity)))
+        },
+        (ResolvedUnit::Unit(amount, unit, name), ResolvedUnit::Unit(target_amount, target_unit, target_name)) => {
+            Measurement::<f64>::new(quantity * amount, *unit)
+                .convert(*target_unit)
+                .map(|end| *end.value() / target_amount)
+                .map_err(|_| mismatch_message(name, target_name))
+        },
+        (ResolvedUnit::Temperature(_), ResolvedUnit::Unit(_, _, name)) => {
+            Err(format!("**Can't convert a temperature to `{}`**: these measure different things.", name))
+        },
+        (ResolvedUnit::Unit(_, _, name), ResolvedUnit::Temperature(_)) => {
+            Err(format!("**Can't convert `{}` to a temperature**: these measure different things.", name))
+        },
+    }
+}
+
+/// Builds the message shown when there is no conversion between the two units. If both units'
+/// dimensions are known, they are named, and if the units differ only by a power of length, a
+/// corrected unit is suggested.
+fn mismatch_message(unit: &str, target_unit: &str) -> String {
+    let (Some(unit_dimension), Some(target_dimension)) = (dimension(unit), dimension(target_unit)) else {
+        return format!("**There is no conversion path from `{}` to `{}`.**", unit, target_unit);
+    };
+
+    let mut message = format!(
+        "**Can't convert `{}` ({}) to `{}` ({})**: these measure different things.",
+        unit, unit_dimension, target_unit, target_dimension,
+    );
+
+    let hint = match (unit_dimension.as_str(), target_dimension.as_str()) {
+        ("length", "area") => Some(format!("{}^2", unit)),
+        ("length", "volume") => Some(format!("{}^3", unit)),
+        ("area", "length") => Some(format!("{}^2", target_unit)),
+        ("volume", "length") => Some(format!("{}^3", target_unit)),
+        _ => None,
+    };
+    if let Some(hint) = hint {
+        message.push_str(&format!(" Did you mean `{}`?", hint));
+    }
+
+    message
+}
+
+/// Converts the quantity to each target unit, keeping the successful conversions even if some
+/// targets fail. Returns each converted target with its formatted value, and the messages
+/// describing the targets that could not be converted.
+fn convert_targets<'t>(
+    quantity: f64,
+    unit: &ResolvedUnit,
+    targets: &[&'t str],
+    custom_units: &HashMap<String, CustomUnit>,
+    settings: &UserSettings,
+) -> (Vec<(&'t str, String)>, Vec<String>) {
+    let mut converted = Vec::new();
+    let mut failed = Vec::new();
+    for &target in targets {
+        let Some(target_unit) = resolve_unit(target, custom_units) else {
+            failed.push(format!("**`{}` is not a supported unit.**", target));
+            continue;
+        };
+
+        match convert(quantity, unit, &target_unit) {
+            Ok(value) => converted.push((
+                target,
+                format_number(value, settings.notation, settings.group_digits),
+            )),
+            Err(message) => failed.push(message),
+        }
+    }
+    (converted, failed)
+}
+
+/// Convert a quantity from one unit / ratio to one or more others. The quantity can be a number, a
+/// percentage like `50%`, or a fraction like `3/4`. Temperatures can be converted between `C`,
+/// `F`, `K`, and `R`.
+///
+/// You can define your own units; run `{prefix}unitconvert define` for more info. Run
+/// `{prefix}unitconvert units` to see a list of supported units.
 ///
 /// **CalcBot uses the US customary measurement system.** You can read about its differences with
 /// the imperial system
@@ -24,9 +191,9 @@ use tokio::sync::Mutex;
 #[info(
     category = "Calculate",
     aliases = ["unitconvert", "uc"],
-    syntax = ["<quantity> <unit / ratio> <target unit / ratio>"],
-    examples = ["18 sec min", "14 mi/hr km/sec"],
-    children = [units::Units],
+    syntax = ["<quantity> <unit / ratio> <target unit / ratio>..."],
+    examples = ["18 sec min", "14 mi/hr km/sec", "3/4 hr min", "1 mi km ft", "100 C F K"],
+    children = [define::Define, units::Units],
 )]
 pub struct UnitConvert;
 
@@ -35,28 +202,174 @@ impl Command for UnitConvert {
     async fn execute<'c>(
         &'c self,
         state: &Arc<State>,
-        _: &Arc<Mutex<Database>>, // TODO: custom ratios
+        database: &Arc<Mutex<Database>>,
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
         let raw_args = ctxt.raw_input.split_whitespace().collect::<Vec<_>>();
-        let (quantity, unit, target_unit) = match raw_args.len() {
-            3 => (raw_args[0].parse().unwrap(), Quantity::try_from(raw_args[1]).unwrap(), Quantity::try_from(raw_args[2]).unwrap()),
-            _ => todo!(),
+        let [quantity, unit_name, targets @ ..] = raw_args.as_slice() else {
+            return Err("**You must provide a quantity, a unit, and a target unit.**".into());
         };
+        if targets.is_empty() {
+            return Err("**You must provide a quantity, a unit, and a target unit.**".into());
+        }
+
+        let quantity = quantity.parse::<Number>()?.0;
+        let user_data = database.lock().await
+            .get_user(ctxt.trigger.author_id()).await
+            .clone();
+        let (settings, custom_units) = (user_data.settings, user_data.custom_units);
 
-        let start = Measurement::<f64>::new(quantity, unit);
-        let out_msg = match start.convert(target_unit) {
-            Ok(end) => {
-                format!("**Converting** `{} {}` to `{}`\n{}", quantity, unit, target_unit, end.value())
-            },
-            Err(_) => {
-                format!("**There is no conversion path from `{}` to `{}`.**", unit, target_unit)
-            },
+        if std::iter::once(unit_name).chain(targets).any(|name| Temperature::is_in_compound(name)) {
+            return Err("**Temperatures cannot be part of a compound unit**, since their offsets make the conversion ambiguous.".into());
+        }
+
+        let Some(unit) = resolve_unit(unit_name, &custom_units) else {
+            ctxt.trigger.unattributed().reply(&state.http)
+                .content(&format!("**`{}` is not a supported unit.**", unit_name))?
+                .await?;
+            return Ok(());
+        };
+
+        let (converted, failed) = convert_targets(quantity, &unit, targets, &custom_units, &settings);
+
+        let mut lines = match converted.as_slice() {
+            [] => Vec::new(),
+            [(target, value)] => vec![format!(
+                "**Converting** `{} {}` to `{}`\n{}",
+                quantity,
+                unit_name,
+                target,
+                value,
+            )],
+            converted => std::iter::once(format!("**Converting** `{} {}`", quantity, unit_name))
+                .chain(converted.iter().map(|(target, value)| format!("`{}`: {}", target, value)))
+                .collect(),
+        };
+        lines.extend(failed);
+        // only successful conversions are attributed
+        let trigger = match converted.is_empty() {
+            true => ctxt.trigger.unattributed(),
+            false => ctxt.trigger,
         };
-        ctxt.trigger.reply(&state.http)
-            .content(&out_msg)?
+        trigger.reply(&state.http)
+            .content(&lines.join("\n"))?
             .await?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Converts a temperature between the two scales.
+    fn convert_temperature(value: f64, scale: &str, target_scale: &str) -> f64 {
+        let scale = ResolvedUnit::Temperature(Temperature::parse(scale).unwrap());
+        let target_scale = ResolvedUnit::Temperature(Temperature::parse(target_scale).unwrap());
+        convert(value, &scale, &target_scale).unwrap()
+    }
+
+    #[test]
+    fn parse_temperature_scales() {
+        assert!(matches!(Temperature::parse("°C"), Some(Temperature::Celsius)));
+        assert!(matches!(Temperature::parse("degF"), Some(Temperature::Fahrenheit)));
+        assert!(Temperature::parse("c").is_none());
+    }
+
+    #[test]
+    fn convert_temperatures_with_offsets() {
+        assert!((convert_temperature(100.0, "C", "F") - 212.0).abs() < 1e-9);
+        assert!((convert_temperature(32.0, "F", "C")).abs() < 1e-9);
+        assert!((convert_temperature(0.0, "C", "K") - 273.15).abs() < 1e-9);
+        assert!((convert_temperature(0.0, "F", "R") - 459.67).abs() < 1e-9);
+    }
+
+    #[test]
+    fn temperatures_in_compound_units() {
+        assert!(Temperature::is_in_compound("C/s"));
+        assert!(Temperature::is_in_compound("m*K"));
+        assert!(!Temperature::is_in_compound("C"));
+        assert!(!Temperature::is_in_compound("m/s"));
+    }
+
+    #[test]
+    fn mismatch_names_dimensions() {
+        assert_eq!(
+            mismatch_message("ft", "sec"),
+            "**Can't convert `ft` (length) to `sec` (time)**: these measure different things.",
+        );
+        assert_eq!(
+            mismatch_message("mi/hr", "hr"),
+            "**Can't convert `mi/hr` (speed) to `hr` (time)**: these measure different things.",
+        );
+    }
+
+    #[test]
+    fn mismatch_suggests_power_of_length() {
+        assert_eq!(
+            mismatch_message("ft", "ac"),
+            "**Can't convert `ft` (length) to `ac` (area)**: these measure different things. Did you mean `ft^2`?",
+        );
+    }
+
+    #[test]
+    fn mismatch_with_unknown_units() {
+        assert_eq!(mismatch_message("ft", "foo"), "**There is no conversion path from `ft` to `foo`.**");
+    }
+
+    /// Returns custom units with a `furlong` of 201.168 meters, and an `m` that is a mile.
+    fn custom_units() -> HashMap<String, CustomUnit> {
+        HashMap::from([
+            ("furlong".to_owned(), CustomUnit { amount: 201.168, unit: "m".to_owned() }),
+            ("m".to_owned(), CustomUnit { amount: 1.0, unit: "mi".to_owned() }),
+        ])
+    }
+
+    /// Converts the quantity between the units, resolving them with the given custom units.
+    fn convert_with(quantity: f64, unit: &str, target: &str, custom_units: &HashMap<String, CustomUnit>) -> f64 {
+        let unit = resolve_unit(unit, custom_units).unwrap();
+        let target = resolve_unit(target, custom_units).unwrap();
+        convert(quantity, &unit, &target).unwrap()
+    }
+
+    #[test]
+    fn custom_unit_round_trip() {
+        let custom_units = custom_units();
+        let kilometers = convert_with(5.0, "furlong", "km", &custom_units);
+        assert!((kilometers - 1.00584).abs() < 1e-9);
+        assert!((convert_with(kilometers, "km", "furlong", &custom_units) - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn custom_unit_overrides_builtin() {
+        assert!((convert_with(1.0, "m", "km", &HashMap::new()) - 0.001).abs() < 1e-12);
+        assert!((convert_with(1.0, "m", "km", &custom_units()) - 1.609344).abs() < 1e-9);
+    }
+
+    #[test]
+    fn custom_unit_of_unknown_unit() {
+        let custom_units = HashMap::from([
+            ("smoot".to_owned(), CustomUnit { amount: 1.7, unit: "parsnip".to_owned() }),
+        ]);
+        assert!(resolve_unit("smoot", &custom_units).is_none());
+    }
+
+    #[test]
+    fn targets_are_converted_independently() {
+        let custom_units = HashMap::new();
+        let unit = resolve_unit("kg", &custom_units).unwrap();
+        let (converted, failed) = convert_targets(5.0, &unit, &["g", "m"], &custom_units, &UserSettings::default());
+        assert_eq!(converted, [("g", "5000".to_owned())]);
+        assert_eq!(failed, [mismatch_message("kg", "m")]);
+    }
+
+    #[test]
+    fn unknown_targets_are_reported() {
+        let custom_units = HashMap::new();
+        let unit = resolve_unit("mi", &custom_units).unwrap();
+        let (converted, failed) = convert_targets(1.0, &unit, &["foo", "mi"], &custom_units, &UserSettings::default());
+        assert_eq!(converted, [("mi", "1".to_owned())]);
+        assert_eq!(failed, ["**`foo` is not a supported unit.**"]);
+    }
+}
@@ -5,18 +5,13 @@ use crate::{
     database::Database,
     error::Error,
     global::State,
-    util::Clamped,
+    util::send_paged_message,
 };
 use serde::{Deserialize, Serialize};
-use std::{future::IntoFuture, sync::Arc};
+use std::sync::Arc;
 use tokio::sync::Mutex;
-use twilight_model::{
-    application::interaction::InteractionData,
-    channel::message::{component::{ActionRow, Button, ButtonStyle}, Component, Embed, ReactionType},
-    http::interaction::{InteractionResponse, InteractionResponseType},
-    id::{marker::ChannelMarker, Id},
-};
-use twilight_util::builder::{embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder}, InteractionResponseDataBuilder};
+use twilight_model::channel::message::Embed;
+use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
 
 lazy_static::lazy_static! {
     /// List of all supported units.
@@ -46,101 +41,42 @@ struct Unit {
     name: String,
 }
 
-/// Sends a Discord message that has multiple pages split as embeds. A task is spawned to listen
-/// for button clicks and update the message accordingly.
-fn send_paged_message(
-    state: &Arc<State>,
-    database: &Arc<Mutex<Database>>,
-    channel_id: Id<ChannelMarker>,
-    pages: &[Embed],
-    index: usize,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // validate before sending
-    let component = Component::ActionRow(ActionRow {
-        components: vec![
-            Component::Button(Button {
-                custom_id: Some("prev".to_owned()),
-                disabled: false,
-                emoji: Some(ReactionType::Unicode {
-                    name: String::from("◀️"),
-                }),
-                label: Some(String::from("Previous")),
-                style: ButtonStyle::Primary,
-                url: None,
-            }),
-            Component::Button(Button {
-                custom_id: Some("next".to_owned()),
-                disabled: false,
-                emoji: Some(ReactionType::Unicode {
-                    name: String::from("▶️"),
-                }),
-                label: Some(String::from("Next")),
-                style: ButtonStyle::Primary,
-                url: None,
-            }),
-            Component::Button(Button {
-                custom_id: Some("delete".to_owned()),
-                disabled: false,
-                emoji: Some(ReactionType::Unicode {
-                    name: String::from("🗑️"),
-                }),
-                label: Some(String::from("Delete")),
-                style: ButtonStyle::Danger,
-                url: None,
-            }),
-        ],
-    });
-    let pages = pages.to_vec();
-    let msg = state.http.create_message(channel_id)
-        .embeds(&[pages[index].clone()])?
-        .components(&[component.clone()])?
-        .into_future();
-
-    let state = Arc::clone(state);
-    let database = Arc::clone(database);
-    tokio::task::spawn(async move {
-        let mut clamped = Clamped::new(index, pages.len());
-        let message = msg.await?.model().await?;
-        let mut receiver = database.lock().await.set_paged_message(channel_id, message.id);
-
-        // TODO: if the message is manually deleted (not through the delete button), the receiver
-        // and sender will not be dropped, which can cause wasted memory
-        //
-        // we need to listen for that message delete event
-        while let Some(mut interaction) = receiver.recv().await {
-            if let Some(InteractionData::MessageComponent(component_interaction)) = interaction.data.take() {
-                match component_interaction.custom_id.as_str() {
-                    "prev" => clamped -= 1,
-                    "next" => clamped += 1,
-                    "delete" => {
-                        state.http.delete_message(channel_id, message.id).await?;
-                        break;
-                    },
-                    _ => unreachable!(),
-                }
-                let new_embed = pages[*clamped].clone();
-                state.http.interaction(state.application_id)
-                    .create_response(
-                        interaction.id,
-                        &interaction.token,
-                        &InteractionResponse {
-                            kind: InteractionResponseType::UpdateMessage,
-                            data: Some(InteractionResponseDataBuilder::new()
-                                .components(Some(component.clone()))
-                                .embeds(vec![new_embed])
-                                .build()),
-                        },
-                    )
-                    .await?;
-            }
-        }
+/// Returns the dimension measured by a single unit, optionally raised to a power, like `length`
+/// for `mi`, or `area` for `mi^2`.
+fn base_dimension(unit: &str) -> Option<String> {
+    let (abbreviation, power) = match unit.split_once('^') {
+        Some((abbreviation, power)) => (abbreviation, power.parse::<u32>().ok()?),
+        None => (unit, 1),
+    };
+    let kind = UNITS.iter()
+        .find(|quantity| quantity.units.iter().any(|unit| unit.abbreviation == abbreviation))?
+        .kind
+        .to_lowercase();
 
-        log::info!("paged message task ended: delete interaction button clicked");
+    Some(match (kind.as_str(), power) {
+        (_, 1) => kind,
+        ("length", 2) => "area".to_owned(),
+        ("length", 3) => "volume".to_owned(),
+        (_, power) => format!("{}^{}", kind, power),
+    })
+}
 
-        Ok::<(), Box<dyn Error + Send + Sync>>(())
-    });
+/// Describes the dimension measured by the given unit, like `length` for `mi`, or `speed` for
+/// `mi/hr`. Returns [`None`] if the unit is not recognized.
+pub fn dimension(unit: &str) -> Option<String> {
+    let dimension = match unit.split_once('/') {
+        Some((numerator, denominator)) => format!(
+            "{} per {}",
+            base_dimension(numerator)?,
+            base_dimension(denominator)?,
+        ),
+        None => base_dimension(unit)?,
+    };
 
-    Ok(())
+    Some(match dimension.as_str() {
+        "length per time" => "speed".to_owned(),
+        _ => dimension,
+    })
 }
 
 /// Creates a embed builder with the common fields set.
@@ -176,9 +112,64 @@ fn generate_embeds() -> Vec<Embed> {
     vec
 }
 
-/// Show a list of units supported by the unit conversion command.
+/// Builds a single embed listing the units whose abbreviation or name contains the search term,
+/// ignoring case. Returns [`None`] if no units match.
+fn search_embed(term: &str) -> Option<Embed> {
+    let mut embed = EmbedBuilder::new()
+        .title(format!("Units matching \"{}\"", term))
+        .color(0xed9632);
+    let term = term.to_lowercase();
+    let mut matched = false;
+    for quantity in UNITS.iter() {
+        let units = quantity.units.iter()
+            .filter(|unit| unit.abbreviation.to_lowercase().contains(&term) || unit.name.to_lowercase().contains(&term))
+            .map(|unit| format!("`{}` - {}", unit.abbreviation, unit.name))
+            .collect::<Vec<_>>();
+        if !units.is_empty() {
+            matched = true;
+            embed = embed.field(EmbedFieldBuilder::new(&quantity.kind, units.join("\n")).inline());
+        }
+    }
+
+    matched.then(|| embed.build())
+}
+
+/// What the input of the units command asks for.
+#[derive(Debug, PartialEq, Eq)]
+enum Request<'a> {
+    /// The page at the given index, of a page number or quantity kind. If the page number given
+    /// does not exist, it is kept in `requested`, and the nearest page is shown instead.
+    Page { index: usize, requested: Option<usize> },
+
+    /// The units whose abbreviation or name contains the search term.
+    Search(&'a str),
+}
+
+/// Parses the input of the units command, given the number of pages.
+fn parse_request(input: &str, pages: usize) -> Request<'_> {
+    let page = match input {
+        "" => 1,
+        input => match input.parse::<usize>() {
+            Ok(page) => page,
+            Err(_) => return match UNITS.iter().position(|quantity| quantity.kind.eq_ignore_ascii_case(input)) {
+                Some(index) => Request::Page { index, requested: None },
+                None => Request::Search(input),
+            },
+        },
+    };
+
+    // show the nearest valid page, so that the note and the paged message agree
+    let clamped_page = page.clamp(1, pages);
+    Request::Page {
+        index: clamped_page - 1,
+        requested: (clamped_page != page).then_some(page),
+    }
+}
+
+/// Show a list of units supported by the unit conversion command. You can go to a page by its
+/// number or quantity kind, like `length`, or search for units by name.
 #[derive(Clone, Info)]
-#[info(aliases = ["units", "unit", "u"], syntax = ["[page number]"])]
+#[info(aliases = ["units", "unit", "u"], syntax = ["[page number]", "[quantity kind]", "[search term]"])]
 pub struct Units;
 
 #[async_trait]
@@ -189,9 +180,100 @@ impl Command for Units {
         database: &Arc<Mutex<Database>>,
         ctxt: Context<'c>,
     ) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let index = ctxt.raw_input.parse::<usize>().unwrap_or(1).saturating_sub(1);
         let embeds = generate_embeds();
-        send_paged_message(state, database, ctxt.trigger.channel_id(), &embeds, index)?;
+        let index = match parse_request(ctxt.raw_input, embeds.len()) {
+            Request::Page { index, requested } => {
+                if requested.is_some() {
+                    ctxt.trigger.unattributed().reply(&state.http)
+                        .content(&format!(
+                            "**There are only {} pages.** Showing page {} instead.",
+                            embeds.len(),
+                            index + 1,
+                        ))?
+                        .await?;
+                }
+                index
+            },
+            Request::Search(term) => {
+                match search_embed(term) {
+                    Some(embed) => {
+                        ctxt.trigger.reply(&state.http)
+                            .embeds(&[embed])?
+                            .await?;
+                    },
+                    None => {
+                        ctxt.trigger.unattributed().reply(&state.http)
+                            .content(&format!("**No units matched `{}`.**", term))?
+                            .await?;
+                    },
+                }
+                return Ok(());
+            },
+        };
+
+        send_paged_message(state, database, ctxt.trigger, &embeds, index)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_page_by_default() {
+        assert_eq!(parse_request("", UNITS.len()), Request::Page { index: 0, requested: None });
+        assert_eq!(parse_request("2", UNITS.len()), Request::Page { index: 1, requested: None });
+    }
+
+    #[test]
+    fn page_zero_shows_first_page() {
+        assert_eq!(parse_request("0", UNITS.len()), Request::Page { index: 0, requested: Some(0) });
+    }
+
+    #[test]
+    fn page_past_the_end_shows_last_page() {
+        let last = UNITS.len() - 1;
+        assert_eq!(parse_request("999", UNITS.len()), Request::Page { index: last, requested: Some(999) });
+    }
+
+    #[test]
+    fn quantity_kind_jumps_to_its_page() {
+        let index = UNITS.iter().position(|quantity| quantity.kind == "Time").unwrap();
+        assert_eq!(parse_request("time", UNITS.len()), Request::Page { index, requested: None });
+    }
+
+    #[test]
+    fn other_input_is_a_search() {
+        assert_eq!(parse_request("meter", UNITS.len()), Request::Search("meter"));
+        assert_eq!(parse_request("-1", UNITS.len()), Request::Search("-1"));
+    }
+
+    #[test]
+    fn search_ignores_case() {
+        // "time" is found inside "centimeter", whatever its case
+        for term in ["time", "TIME"] {
+            let embed = search_embed(term).unwrap();
+            assert_eq!(embed.title, Some(format!("Units matching \"{}\"", term)));
+            assert_eq!(embed.fields.len(), 1);
+            assert_eq!(embed.fields[0].name, "Length");
+            assert_eq!(embed.fields[0].value, "`cm` - centimeter");
+        }
+    }
+
+    #[test]
+    fn search_matches_names_and_abbreviations() {
+        let embed = search_embed("Min").unwrap();
+        assert_eq!(embed.fields.len(), 1);
+        assert_eq!(embed.fields[0].name, "Time");
+        assert_eq!(embed.fields[0].value, "`min` - minute");
+
+        let embed = search_embed("ha").unwrap();
+        assert!(embed.fields.iter().any(|field| field.value.contains("`ha` - hectare")));
+    }
+
+    #[test]
+    fn search_without_matches() {
+        assert!(search_embed("furlong").is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use std::{fmt::{self, Display, Formatter}, str::FromStr};
+
+/// The maximum length of a component's custom ID, as enforced by Discord.
+pub const MAX_LENGTH: usize = 100;
+
+/// An error that can occur when encoding or decoding a [`CustomId`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomIdError {
+    /// The namespace or action was empty or contained a `:`.
+    InvalidSegment(String),
+
+    /// The encoded custom ID is longer than [`MAX_LENGTH`].
+    TooLong(usize),
+
+    /// The custom ID did not have at least a namespace and an action.
+    Malformed(String),
+}
+
+/// A structured component custom ID, in the form `namespace:action[:payload]`.
+///
+/// The namespace determines which handler the interaction is routed to (see
+/// [`crate::handler::interaction_create`]), while the action and payload are interpreted by that
+/// handler. For example, the pager's "next page" button uses `pager:next`.
+///
+/// The payload is everything after the second `:`, so it may itself contain `:` without needing
+/// to be escaped. The namespace and action cannot contain `:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomId {
+    /// The namespace of the custom ID, identifying the feature that owns the component.
+    pub namespace: String,
+
+    /// The action to perform within the namespace.
+    pub action: String,
+
+    /// Optional extra data needed to perform the action, such as a reminder ID.
+    pub payload: Option<String>,
+}
+
+impl CustomId {
+    /// Creates a new [`CustomId`] with the given namespace and action, and no payload.
+    pub fn new(namespace: &str, action: &str) -> Self {
+        Self {
+            namespace: namespace.to_owned(),
+            action: action.to_owned(),
+            payload: None,
+        }
+    }
+
+    /// Attaches the given payload to the custom ID.
+    pub fn with_payload(mut self, payload: &str) -> Self {
+        self.payload = Some(payload.to_owned());
+        self
+    }
+
+    /// Encodes the custom ID into a string that can be given to a component, validating that the
+    /// result is accepted by Discord.
+    pub fn encode(&self) -> Result<String, CustomIdError> {
+        for segment in [&self.namespace, &self.action] {
+            if segment.is_empty() || segment.contains(':') {
+                return Err(CustomIdError::InvalidSegment(segment.clone()));
+            }
+        }
+
+        let encoded = self.to_string();
+        if encoded.len() > MAX_LENGTH {
+            return Err(CustomIdError::TooLong(encoded.len()));
+        }
+
+        Ok(encoded)
+    }
+}
+
+impl Display for CustomId {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.action)?;
+        if let Some(payload) = &self.payload {
+            write!(f, ":{}", payload)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CustomId {
+    type Err = CustomIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut segments = s.splitn(3, ':');
+        match (segments.next(), segments.next()) {
+            (Some(namespace), Some(action)) if !namespace.is_empty() && !action.is_empty() => {
+                Ok(Self {
+                    namespace: namespace.to_owned(),
+                    action: action.to_owned(),
+                    payload: segments.next().map(str::to_owned),
+                })
+            },
+            _ => Err(CustomIdError::Malformed(s.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_without_payload() {
+        assert_eq!(CustomId::new("pager", "next").encode(), Ok("pager:next".to_owned()));
+    }
+
+    #[test]
+    fn payload_may_contain_colons() {
+        let id = CustomId::new("remind", "delete").with_payload("a:b");
+        let encoded = id.encode().unwrap();
+        assert_eq!(encoded, "remind:delete:a:b");
+        assert_eq!(encoded.parse::<CustomId>(), Ok(id));
+    }
+
+    #[test]
+    fn invalid_segments_are_rejected() {
+        assert_eq!(
+            CustomId::new("", "next").encode(),
+            Err(CustomIdError::InvalidSegment(String::new())),
+        );
+        assert_eq!(
+            CustomId::new("pager", "a:b").encode(),
+            Err(CustomIdError::InvalidSegment("a:b".to_owned())),
+        );
+    }
+
+    #[test]
+    fn long_ids_are_rejected() {
+        let id = CustomId::new("pager", "next").with_payload(&"x".repeat(MAX_LENGTH));
+        assert_eq!(id.encode(), Err(CustomIdError::TooLong(MAX_LENGTH + 11)));
+    }
+
+    #[test]
+    fn decode_malformed_ids() {
+        for s in ["", "pager", "pager:", ":next"] {
+            assert_eq!(s.parse::<CustomId>(), Err(CustomIdError::Malformed(s.to_owned())));
+        }
+    }
+}
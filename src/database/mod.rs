@@ -1,3 +1,5 @@
+pub mod schema;
+pub mod server;
 pub mod user;
 
 use crate::timer::Timer;
@@ -7,28 +9,34 @@ use mysql_async::{
     OptsBuilder,
     Pool,
 };
-use serde_json::to_value;
 use std::collections::HashMap;
-use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    oneshot,
+};
 use twilight_model::{
     gateway::payload::incoming::InteractionCreate,
     id::{Id, marker::{ChannelMarker, GuildMarker, MessageMarker, UserMarker}},
 };
-use user::{UserData, UserField};
+use server::{ServerData, ServerField};
+use user::{store_ctxt, UserData, UserField};
 
 /// Helper struct to access and manage the database.
 pub struct Database {
     /// A connection pool to the database.
     pool: Pool,
 
-    /// The server cache. This stores the prefix of CalcBot on servers that have recently used it.
-    servers: HashMap<Id<GuildMarker>, String>,
+    /// The server cache. This stores the server data of servers that have recently used CalcBot.
+    servers: HashMap<Id<GuildMarker>, ServerData>,
 
     /// The user cache. This stores the user data of users that have recently used CalcBot.
     users: HashMap<Id<UserMarker>, UserData>,
 
     /// Paged messages that are currently being displayed.
     paged: HashMap<(Id<ChannelMarker>, Id<MessageMarker>), UnboundedSender<InteractionCreate>>,
+
+    /// Confirmation dialogs that are waiting for the user to click a button.
+    confirmations: HashMap<(Id<ChannelMarker>, Id<MessageMarker>), oneshot::Sender<InteractionCreate>>,
 }
 
 impl Default for Database {
@@ -52,9 +60,15 @@ impl Database {
             servers: HashMap::new(),
             users: HashMap::new(),
             paged: HashMap::new(),
+            confirmations: HashMap::new(),
         }
     }
 
+    /// Disconnects from the database, waiting for the queries in progress to complete.
+    pub async fn disconnect(&self) -> Result<(), mysql_async::Error> {
+        self.pool.clone().disconnect().await
+    }
+
     /// Sets the paged message sender for the given channel and message IDs. This is used to listen
     /// for interactions on messages with multiple pages.
     pub fn set_paged_message(
@@ -97,35 +111,113 @@ impl Database {
         self.paged.remove(&(channel_id, message_id)).is_some()
     }
 
+    /// Registers a confirmation dialog for the given channel and message IDs. The returned
+    /// receiver will receive the first authorized button click on the dialog.
+    pub fn set_confirmation(
+        &mut self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> oneshot::Receiver<InteractionCreate> {
+        let (sender, receiver) = oneshot::channel();
+        self.confirmations.insert((channel_id, message_id), sender);
+        receiver
+    }
+
+    /// Removes and returns the confirmation dialog sender for the given channel and message IDs,
+    /// if the dialog is still waiting for a response.
+    pub fn take_confirmation(
+        &mut self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Option<oneshot::Sender<InteractionCreate>> {
+        self.confirmations.remove(&(channel_id, message_id))
+            .filter(|sender| !sender.is_closed())
+    }
+
+    /// Adds the columns listed in [`schema::COLUMNS`] that are missing from the database. This
+    /// should be called once on startup, before any data is loaded.
+    pub async fn migrate(&self) -> Result<(), mysql_async::Error> {
+        let existing = "SELECT TABLE_NAME, COLUMN_NAME FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE()"
+            .fetch::<(String, String), _>(&self.pool)
+            .await?;
+        for column in schema::missing_columns(&existing) {
+            log::info!("adding missing column `{}.{}`", column.table, column.name);
+            column.add_statement().ignore(&self.pool).await?;
+        }
+        Ok(())
+    }
+
     /// Returns the data of the server with the given ID.
     ///
     /// If the data was cached previously, the cached value will be returned. Otherwise, the data
     /// will be fetched from the database, cached, then returned.
     ///
-    /// If the data does not exist anywhere, a default is created.
-    pub async fn get_server(&mut self, id: Id<GuildMarker>) -> &str {
-        if self.servers.contains_key(&id) {
+    /// If the data does not exist anywhere, a default is created. If the data could not be
+    /// fetched, a default is returned with [`ServerData::load_failed`] set, and the data is
+    /// fetched again on the next call.
+    pub async fn get_server(&mut self, id: Id<GuildMarker>) -> &ServerData {
+        if self.servers.get(&id).is_some_and(|data| !data.load_failed) {
             return &self.servers[&id];
         }
 
-        let prefix = match "SELECT prefix FROM servers WHERE id = ? LIMIT 1"
+        let data = match "SELECT prefix, inline_math, attribution_footer, dictionary_threads FROM servers WHERE id = ? LIMIT 1"
             .with((id.get(),))
-            .first::<String, _>(&self.pool)
+            .first::<ServerData, _>(&self.pool)
             .await
-            .unwrap()
         {
-            Some(prefix) => prefix,
-            None => {
-                "INSERT INTO servers (id, prefix) VALUES (?, 'c-')"
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                if let Err(err) = "INSERT INTO servers (id, prefix) VALUES (?, 'c-')"
                     .with((id.get(),))
                     .ignore(&self.pool)
                     .await
-                    .unwrap();
-                String::from("c-")
+                {
+                    log::error!("could not create data of server {}: {}", id, err);
+                }
+                ServerData::default()
+            },
+            Err(err) => {
+                log::error!("could not load data of server {}; using defaults: {}", id, err);
+                ServerData { load_failed: true, ..Default::default() }
             },
         };
 
-        self.servers.entry(id).or_insert(prefix)
+        self.servers.insert(id, data);
+        &self.servers[&id]
+    }
+
+    /// Sets the prefix of CalcBot in the server with the given ID.
+    ///
+    /// This will update the cached value and the database value.
+    pub async fn set_server_prefix(&mut self, id: Id<GuildMarker>, prefix: String) {
+        // make sure the server has a row to update
+        self.get_server(id).await;
+
+        if let Err(err) = "UPDATE servers SET prefix = ? WHERE id = ?"
+            .with((&prefix, id.get()))
+            .ignore(&self.pool)
+            .await
+        {
+            log::error!("could not store prefix of server {}: {}", id, err);
+        }
+        self.servers.get_mut(&id).unwrap().prefix = prefix;
+    }
+
+    /// Sets a toggleable field of the server data for the given server ID.
+    ///
+    /// This will update the cached value and the database value.
+    pub async fn set_server_field(&mut self, id: Id<GuildMarker>, field: ServerField) {
+        // make sure the server has a row to update
+        self.get_server(id).await;
+
+        if let Err(err) = format!("UPDATE servers SET {} = ? WHERE id = ?", field.column_name())
+            .with((field.value(), id.get()))
+            .ignore(&self.pool)
+            .await
+        {
+            log::error!("could not store `{}` of server {}: {}", field.column_name(), id, err);
+        }
+        field.apply(self.servers.get_mut(&id).unwrap());
     }
 
     /// Returns the user data for the given user ID.
@@ -133,49 +225,75 @@ impl Database {
     /// If the data was cached previously, the cached value will be returned. Otherwise, the data
     /// will be fetched from the database, cached, then returned.
     ///
-    /// If the data does not exist anywhere, a default is created.
+    /// If the data does not exist anywhere, a default is created. If the data could not be
+    /// fetched, a default is returned with [`UserData::load_failed`] set, and the data is fetched
+    /// again on the next call.
     pub async fn get_user(&mut self, id: Id<UserMarker>) -> &UserData {
-        if self.users.contains_key(&id) {
+        if self.users.get(&id).is_some_and(|data| !data.load_failed) {
             return &self.users[&id];
         }
 
-        let data = match "SELECT ctxt, timers FROM users WHERE id = ? LIMIT 1"
+        let columns = UserField::column_names();
+        let data = match format!("SELECT {} FROM users WHERE id = ? LIMIT 1", columns.join(", "))
             .with((id.get(),))
             .first::<UserData, _>(&self.pool)
             .await
-            .unwrap()
         {
-            Some(data) => data,
-            None => {
-                "INSERT INTO users (id, ctxt, timers) VALUES (?, ?, ?)"
-                    .with((
-                        id.get(),
-                        to_value(cas_compute::numerical::ctxt::Ctxt::default()).unwrap(),
-                        to_value(HashMap::<(), ()>::new()).unwrap(),
-                    ))
-                    .ignore(&self.pool)
-                    .await
-                    .unwrap();
+            Ok(Some(data)) => data,
+            Ok(None) => {
+                // every column is given its default, so that new columns are never omitted
+                let values = std::iter::once(mysql_async::Value::from(id.get()))
+                    .chain(UserField::defaults().iter().map(|field| field.to_value().into()))
+                    .collect::<Vec<_>>();
+                let query = format!(
+                    "INSERT INTO users (id, {}) VALUES (?{})",
+                    columns.join(", "),
+                    ", ?".repeat(columns.len()),
+                );
+                if let Err(err) = query.with(values).ignore(&self.pool).await {
+                    log::error!("could not create data of user {}: {}", id, err);
+                }
                 UserData::default()
             },
+            Err(err) => {
+                log::error!("could not load data of user {}; using defaults: {}", id, err);
+                // keep the stand-in from an earlier failure, which may have been changed since
+                self.users.remove(&id)
+                    .unwrap_or(UserData { load_failed: true, ..Default::default() })
+            },
         };
 
-        self.users.entry(id).or_insert(data)
+        self.users.insert(id, data);
+        &self.users[&id]
     }
 
     /// Sets the user data for the given user ID.
     ///
     /// This will update the cached value and the database value.
     pub async fn set_user(&mut self, id: Id<UserMarker>, data: UserData) {
-        "UPDATE users SET ctxt = ?, timers = ? WHERE id = ?"
-            .with((
-                to_value(&data.ctxt).unwrap(),
-                to_value(&data.timers).unwrap(),
-                id.get(),
-            ))
-            .ignore(&self.pool)
-            .await
-            .unwrap();
+        // a stand-in for data that could not be loaded would overwrite the stored data, so it is
+        // only kept in memory
+        if !data.load_failed {
+            let fields = data.fields();
+            let assignments = fields.iter()
+                .map(|field| format!("{} = ?", field.column_name()))
+                .collect::<Vec<_>>();
+            let values = fields.iter()
+                .map(|field| match field {
+                    // quarantined definitions are written back with the rest of the context
+                    UserField::Ctxt(ctxt) => store_ctxt(ctxt, &data.quarantined),
+                    field => field.to_value(),
+                }.into())
+                .chain(std::iter::once(mysql_async::Value::from(id.get())))
+                .collect::<Vec<mysql_async::Value>>();
+            if let Err(err) = format!("UPDATE users SET {} WHERE id = ?", assignments.join(", "))
+                .with(values)
+                .ignore(&self.pool)
+                .await
+            {
+                log::error!("could not store data of user {}: {}", id, err);
+            }
+        }
         self.users.insert(id, data);
     }
 
@@ -183,24 +301,72 @@ impl Database {
     ///
     /// This will update the cached value and the database value.
     pub async fn set_user_field(&mut self, id: Id<UserMarker>, field: UserField) {
+        let user = self.users.get_mut(&id).unwrap();
+
+        // a stand-in for data that could not be loaded would overwrite the stored data, so the
+        // change is only kept in memory
+        if !user.load_failed {
+            let value = match &field {
+                // quarantined definitions are written back with the rest of the context
+                UserField::Ctxt(ctxt) => store_ctxt(ctxt, &user.quarantined),
+                field => field.to_value(),
+            };
+            if let Err(err) = format!("UPDATE users SET {} = ? WHERE id = ?", field.column_name())
+                .with((value, id.get()))
+                .ignore(&self.pool)
+                .await
+            {
+                log::error!("could not store `{}` of user {}: {}", field.column_name(), id, err);
+            }
+        }
+
         match field {
             UserField::Ctxt(ctxt) => {
-                "UPDATE users SET ctxt = ? WHERE id = ?"
-                    .with((to_value(&ctxt).unwrap(), id.get()))
-                    .ignore(&self.pool)
-                    .await
-                    .unwrap();
-                self.users.get_mut(&id).unwrap().ctxt = ctxt;
+                // quarantined definitions that the user has redefined are replaced
+                let vars = ctxt.get_vars();
+                let funcs = ctxt.get_funcs();
+                user.quarantined.retain(|definition| {
+                    !vars.contains_key(&definition.name) && !funcs.contains_key(&definition.name)
+                });
+                user.ctxt = ctxt;
             },
-            UserField::Timers(timers) => {
-                "UPDATE users SET timers = ? WHERE id = ?"
-                    .with((to_value(&timers).unwrap(), id.get()))
-                    .ignore(&self.pool)
-                    .await
-                    .unwrap();
-                self.users.get_mut(&id).unwrap().timers = timers;
+            UserField::Timers(mut timers) => {
+                // the new timers are usually clones without tasks, so the running tasks are moved
+                // over; the tasks of removed timers are aborted when the old timers are dropped
+                for (id, timer) in timers.iter_mut() {
+                    if let Some(old) = user.timers.get_mut(id) {
+                        timer.adopt_task(old);
+                    }
+                }
+                user.timers = timers;
             },
+            UserField::Settings(settings) => user.settings = settings,
+            UserField::CustomUnits(custom_units) => user.custom_units = custom_units,
+        }
+    }
+
+    /// Forgets the user's quarantined definition of the given name, or all of them if no name is
+    /// given, so that they are no longer written back to the database. Returns the number of
+    /// definitions forgotten.
+    pub fn forget_quarantined(&mut self, id: Id<UserMarker>, name: Option<&str>) -> usize {
+        let Some(user) = self.users.get_mut(&id) else {
+            return 0;
+        };
+        let before = user.quarantined.len();
+        user.quarantined.retain(|definition| name.is_some_and(|name| definition.name != name));
+        before - user.quarantined.len()
+    }
+
+    /// Returns the names of the user's quarantined definitions if the user has not been told about
+    /// them yet in this session, marking them as told.
+    pub fn take_quarantine_notice(&mut self, id: Id<UserMarker>) -> Option<Vec<String>> {
+        let user = self.users.get_mut(&id)?;
+        if user.quarantined.is_empty() || user.quarantine_notified {
+            return None;
         }
+
+        user.quarantine_notified = true;
+        Some(user.quarantined.iter().map(|definition| definition.name.clone()).collect())
     }
 
     /// Add a managed timer to the database.
@@ -214,10 +380,40 @@ impl Database {
 
         self.set_user_field(user_id, UserField::Timers(user_timers)).await;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a database with empty caches, whose pool is never connected to.
+    fn database() -> Database {
+        Database {
+            pool: Pool::new(OptsBuilder::default()),
+            servers: HashMap::new(),
+            users: HashMap::new(),
+            paged: HashMap::new(),
+            confirmations: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn removing_paged_message_closes_receiver() {
+        let mut database = database();
+        let mut receiver = database.set_paged_message(Id::new(1), Id::new(2));
+        assert!(database.get_paged_message(Id::new(1), Id::new(2)).is_some());
+
+        assert!(database.remove_paged_message(Id::new(1), Id::new(2)));
+        assert!(receiver.recv().await.is_none());
+        assert!(database.get_paged_message(Id::new(1), Id::new(2)).is_none());
+        assert!(!database.remove_paged_message(Id::new(1), Id::new(2)));
+    }
 
-    /// Remove a managed timer from the database. Returns the removed instance.
-    pub async fn remove_timer(&mut self, id: &Id<UserMarker>, timer_id: &str) -> Option<Timer> {
-        let user = self.users.get_mut(id)?;
-        user.timers.remove(timer_id)
+    #[tokio::test]
+    async fn dropped_receiver_unregisters_paged_message() {
+        let mut database = database();
+        drop(database.set_paged_message(Id::new(1), Id::new(2)));
+        assert!(database.get_paged_message(Id::new(1), Id::new(2)).is_none());
+        assert!(!database.remove_paged_message(Id::new(1), Id::new(2)));
     }
 }
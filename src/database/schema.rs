@@ -0,0 +1,93 @@
+/// A column that was added to a table after the table was first created. Databases created before
+/// the column existed are migrated by [`Database::migrate`](super::Database::migrate).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Column {
+    /// The table the column belongs to.
+    pub table: &'static str,
+
+    /// The name of the column.
+    pub name: &'static str,
+
+    /// The type and constraints of the column, as written in a `CREATE TABLE` statement.
+    pub definition: &'static str,
+}
+
+impl Column {
+    /// Returns the statement that adds this column to its table.
+    pub fn add_statement(&self) -> String {
+        format!("ALTER TABLE {} ADD COLUMN {} {}", self.table, self.name, self.definition)
+    }
+}
+
+/// The columns that were added after the `servers` and `users` tables were first created.
+///
+/// When adding a column that the code reads, add it here too, so that existing databases are
+/// migrated on startup instead of failing every query that selects the column.
+pub const COLUMNS: &[Column] = &[
+    Column { table: "servers", name: "inline_math", definition: "BOOLEAN NOT NULL DEFAULT FALSE" },
+    Column { table: "servers", name: "attribution_footer", definition: "BOOLEAN NOT NULL DEFAULT FALSE" },
+    Column { table: "servers", name: "dictionary_threads", definition: "BOOLEAN NOT NULL DEFAULT FALSE" },
+    Column { table: "users", name: "settings", definition: "JSON NULL" },
+    Column { table: "users", name: "custom_units", definition: "JSON NULL" },
+];
+
+/// Returns the columns in [`COLUMNS`] that are missing from the database, given the
+/// `(table, column)` pairs that exist in it. Names are compared ignoring case, like MySQL does.
+pub fn missing_columns(existing: &[(String, String)]) -> Vec<&'static Column> {
+    COLUMNS.iter()
+        .filter(|column| {
+            !existing.iter().any(|(table, name)| {
+                table.eq_ignore_ascii_case(column.table) && name.eq_ignore_ascii_case(column.name)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the `(table, column)` pairs of a database with every column in [`COLUMNS`].
+    fn all_columns() -> Vec<(String, String)> {
+        COLUMNS.iter()
+            .map(|column| (column.table.to_owned(), column.name.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn baseline_database_is_missing_every_column() {
+        let baseline = [("servers", "id"), ("servers", "prefix"), ("users", "id"), ("users", "ctxt"), ("users", "timers")]
+            .iter()
+            .map(|(table, name)| (table.to_string(), name.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(missing_columns(&baseline), COLUMNS.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn migrated_database_is_missing_nothing() {
+        assert!(missing_columns(&all_columns()).is_empty());
+    }
+
+    #[test]
+    fn names_are_compared_ignoring_case() {
+        let existing = all_columns()
+            .into_iter()
+            .map(|(table, name)| (table.to_uppercase(), name.to_uppercase()))
+            .collect::<Vec<_>>();
+        assert!(missing_columns(&existing).is_empty());
+    }
+
+    #[test]
+    fn column_in_another_table_does_not_count() {
+        let existing = vec![("users".to_owned(), "inline_math".to_owned())];
+        assert!(missing_columns(&existing).contains(&&COLUMNS[0]));
+    }
+
+    #[test]
+    fn add_statement() {
+        assert_eq!(
+            COLUMNS[0].add_statement(),
+            "ALTER TABLE servers ADD COLUMN inline_math BOOLEAN NOT NULL DEFAULT FALSE",
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use mysql_async::{prelude::FromRow, FromRowError};
+
+/// Represents server-specific data across all sessions.
+#[derive(Debug, Clone)]
+pub struct ServerData {
+    /// The prefix of CalcBot in the server.
+    pub prefix: String,
+
+    /// Whether `{{expression}}` spans in messages should be evaluated inline. This is off by
+    /// default to avoid surprising servers that did not ask for it.
+    pub inline_math: bool,
+
+    /// Whether replies to calculation commands should be attributed with the invoker's name and
+    /// the time of the invocation, to make doctored screenshots easier to spot.
+    pub attribution_footer: bool,
+
+    /// Whether dictionary entries should be posted in a new thread off the invoking message (or
+    /// the reply to a slash command), to keep long definitions out of the main channel.
+    pub dictionary_threads: bool,
+
+    /// Whether the data could not be loaded from the database, in which case this is a default
+    /// stand-in. It is loaded again the next time it is requested.
+    pub load_failed: bool,
+}
+
+impl Default for ServerData {
+    fn default() -> Self {
+        Self {
+            prefix: String::from("c-"),
+            inline_math: false,
+            attribution_footer: false,
+            dictionary_threads: false,
+            load_failed: false,
+        }
+    }
+}
+
+impl FromRow for ServerData {
+    fn from_row_opt(row: mysql_async::Row) -> Result<Self, FromRowError> {
+        Ok(Self {
+            prefix: row.get::<String, _>("prefix").unwrap(),
+            inline_math: row.get::<bool, _>("inline_math").unwrap_or(false),
+            attribution_footer: row.get::<bool, _>("attribution_footer").unwrap_or(false),
+            dictionary_threads: row.get::<bool, _>("dictionary_threads").unwrap_or(false),
+            load_failed: false,
+        })
+    }
+}
+
+/// Represents a toggleable field of [`ServerData`] and its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerField {
+    /// Whether `{{expression}}` spans in messages should be evaluated inline.
+    InlineMath(bool),
+
+    /// Whether replies to calculation commands should be attributed to their invoker.
+    AttributionFooter(bool),
+
+    /// Whether dictionary entries should be posted in a new thread.
+    DictionaryThreads(bool),
+}
+
+impl ServerField {
+    /// Returns the name of the column of the `servers` table that stores this field.
+    pub fn column_name(&self) -> &'static str {
+        match self {
+            ServerField::InlineMath(_) => "inline_math",
+            ServerField::AttributionFooter(_) => "attribution_footer",
+            ServerField::DictionaryThreads(_) => "dictionary_threads",
+        }
+    }
+
+    /// Returns the value of this field.
+    pub fn value(&self) -> bool {
+        match self {
+            ServerField::InlineMath(value)
+                | ServerField::AttributionFooter(value)
+                | ServerField::DictionaryThreads(value) => *value,
+        }
+    }
+
+    /// Sets this field on the given server data.
+    pub fn apply(self, data: &mut ServerData) {
+        match self {
+            ServerField::InlineMath(value) => data.inline_math = value,
+            ServerField::AttributionFooter(value) => data.attribution_footer = value,
+            ServerField::DictionaryThreads(value) => data.dictionary_threads = value,
+        }
+    }
+}
@@ -1,8 +1,274 @@
-use cas_compute::numerical::ctxt::Ctxt;
-use crate::timer::Timer;
+use cas_compute::numerical::ctxt::{Ctxt, Func};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+use crate::{timer::Timer, util::Notation};
 use mysql_async::{prelude::FromRow, FromRowError};
-use serde_json::from_str;
-use std::collections::HashMap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{from_str, from_value, to_value, Map, Value};
+use std::{collections::HashMap, fmt::{self, Display, Formatter}};
+
+/// A user's personal preferences, set with the `c-settings` command.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UserSettings {
+    /// How numbers should be displayed in command output.
+    pub notation: Notation,
+
+    /// Whether digits in the integer part of numbers should be grouped with commas.
+    pub group_digits: bool,
+
+    /// The user's time zone. This is used when the user gives a date and time without an offset.
+    pub time_zone: UserTimeZone,
+}
+
+/// A user's time zone, either a fixed offset from UTC or a named time zone that follows daylight
+/// saving time.
+///
+/// This is stored as a number of minutes for fixed offsets, and as the name of the time zone
+/// otherwise, so settings saved before named time zones were supported still load.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum UserTimeZone {
+    /// A fixed offset from UTC, in minutes.
+    Offset(i16),
+
+    /// A time zone from the IANA time zone database, like `America/New_York`.
+    Named(Tz),
+}
+
+impl Default for UserTimeZone {
+    fn default() -> Self {
+        Self::Offset(0)
+    }
+}
+
+impl Display for UserTimeZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offset(_) => write!(f, "UTC{}", self.offset_at(Utc::now())),
+            Self::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl UserTimeZone {
+    /// Returns the offset from UTC that is in effect in this time zone at the given instant.
+    pub fn offset_at(&self, instant: DateTime<Utc>) -> FixedOffset {
+        match self {
+            Self::Offset(minutes) => FixedOffset::east_opt(*minutes as i32 * 60)
+                .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap()),
+            Self::Named(tz) => tz.offset_from_utc_datetime(&instant.naive_utc()).fix(),
+        }
+    }
+
+    /// Resolves a date and time in this time zone, using the offset in effect at that date and
+    /// time rather than the current one. If the clocks are turned back, so that the date and time
+    /// occurs twice, the earlier one is used.
+    ///
+    /// Returns [`None`] if the date and time is skipped when the clocks are turned forward.
+    pub fn resolve_local(&self, local: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Self::Offset(_) => self.offset_at(Utc::now()).from_local_datetime(&local).earliest(),
+            Self::Named(tz) => tz.from_local_datetime(&local)
+                .earliest()
+                .map(|date_time| date_time.with_timezone(&date_time.offset().fix())),
+        }
+    }
+}
+
+/// A unit defined by a user with the `c-unitconvert define` command, in terms of a built-in unit.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomUnit {
+    /// How many of the built-in unit make up one of the custom unit.
+    pub amount: f64,
+
+    /// The built-in unit, like `g`.
+    pub unit: String,
+}
+
+/// A definition in a user's stored [`Ctxt`] that could not be loaded, usually because it was
+/// saved by an older version of CalcBot.
+///
+/// Quarantined definitions are skipped when loading the context, but are kept in memory and
+/// written back to the database with the rest of the context, so that no user data is lost.
+#[derive(Debug, Clone)]
+pub struct QuarantinedDefinition {
+    /// The field of the serialized context the definition was stored in, like `vars`.
+    pub field: String,
+
+    /// The name of the definition.
+    pub name: String,
+
+    /// The serialized definition.
+    pub raw: Value,
+}
+
+/// The name of the [`QuarantinedDefinition`] that holds a stored context that could not be loaded
+/// at all. It is written back under this key of the serialized context, so that it is kept until
+/// the user clears it.
+pub const UNREADABLE_CONTEXT: &str = "unreadable_context";
+
+impl QuarantinedDefinition {
+    /// Quarantines a whole stored context that could not be loaded, keeping its raw text.
+    fn unreadable(raw: &str) -> Self {
+        Self {
+            field: UNREADABLE_CONTEXT.to_owned(),
+            name: UNREADABLE_CONTEXT.to_owned(),
+            raw: Value::String(raw.to_owned()),
+        }
+    }
+}
+
+/// Deserializes a user's stored context. If the context cannot be deserialized as a whole, each
+/// definition is checked on its own, and the definitions that fail are quarantined so that the
+/// rest of the context can still be used.
+///
+/// If the context cannot be loaded at all, the default context is used, and the raw stored context
+/// is quarantined under [`UNREADABLE_CONTEXT`].
+pub fn load_ctxt(raw: &str) -> (Ctxt, Vec<QuarantinedDefinition>) {
+    if raw.trim().is_empty() {
+        return (Ctxt::default(), Vec::new());
+    }
+    let Ok(mut value) = from_str::<Value>(raw) else {
+        log::warn!("stored context is not valid JSON; quarantining it");
+        return (Ctxt::default(), vec![QuarantinedDefinition::unreadable(raw)]);
+    };
+
+    // a context that could not be loaded earlier is carried along as it is
+    let mut quarantined = value.as_object_mut()
+        .and_then(|fields| fields.remove(UNREADABLE_CONTEXT))
+        .map(|raw| QuarantinedDefinition {
+            field: UNREADABLE_CONTEXT.to_owned(),
+            name: UNREADABLE_CONTEXT.to_owned(),
+            raw,
+        })
+        .into_iter()
+        .collect::<Vec<_>>();
+    if let Ok(ctxt) = from_value::<Ctxt>(value.clone()) {
+        return (ctxt, quarantined);
+    }
+
+    if let Value::Object(fields) = &mut value {
+        // a copy of the context with no definitions, used to check each definition on its own
+        let mut skeleton = fields.clone();
+        for field in skeleton.values_mut() {
+            if let Value::Object(definitions) = field {
+                definitions.clear();
+            }
+        }
+
+        for (field, definitions) in fields.iter_mut() {
+            let Value::Object(definitions) = definitions else {
+                continue;
+            };
+
+            let bad = definitions.iter()
+                .filter(|(name, definition)| {
+                    let mut candidate = skeleton.clone();
+                    candidate.insert(
+                        field.clone(),
+                        Value::Object(Map::from_iter([((*name).clone(), (*definition).clone())])),
+                    );
+                    from_value::<Ctxt>(Value::Object(candidate)).is_err()
+                })
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>();
+            for name in bad {
+                let raw = definitions.remove(&name).unwrap();
+                quarantined.push(QuarantinedDefinition { field: field.clone(), name, raw });
+            }
+        }
+    }
+
+    match from_value::<Ctxt>(value) {
+        Ok(ctxt) => (ctxt, quarantined),
+        Err(err) => {
+            log::warn!("could not load stored context, even after quarantining definitions; quarantining all of it: {}", err);
+            (Ctxt::default(), vec![QuarantinedDefinition::unreadable(raw)])
+        },
+    }
+}
+
+/// Serializes a user's context for storage, adding back any quarantined definitions that the user
+/// has not since redefined.
+pub fn store_ctxt(ctxt: &Ctxt, quarantined: &[QuarantinedDefinition]) -> Value {
+    let mut value = to_value(ctxt).unwrap();
+    if let Value::Object(fields) = &mut value {
+        for definition in quarantined {
+            if definition.field == UNREADABLE_CONTEXT {
+                fields.entry(UNREADABLE_CONTEXT).or_insert_with(|| definition.raw.clone());
+            } else if let Some(Value::Object(definitions)) = fields.get_mut(&definition.field) {
+                definitions.entry(definition.name.clone())
+                    .or_insert_with(|| definition.raw.clone());
+            }
+        }
+    }
+    value
+}
+
+/// The outcome of removing a single definition with [`remove_definition`].
+#[derive(Debug)]
+pub enum RemovedDefinition {
+    /// The variable or user-defined function was removed, resulting in the given context.
+    Removed(Ctxt),
+
+    /// The name is a built-in constant that the user had overridden, and its default value was
+    /// restored in the given context.
+    Restored(Ctxt),
+
+    /// The name is a built-in constant with its default value, or a built-in function, neither of
+    /// which can be removed.
+    Builtin,
+
+    /// The context has no definition with the name.
+    NotDefined,
+}
+
+/// Removes the variable or user-defined function of the given name from a copy of the context.
+///
+/// Built-in constants, like `pi`, are the variables of the default context. They are never
+/// removed; if the user had overridden one, its default value is restored instead.
+pub fn remove_definition(ctxt: &Ctxt, name: &str) -> RemovedDefinition {
+    let default = Ctxt::default();
+    if let Some(default_value) = default.get_vars().get(name) {
+        let overridden = ctxt.get_vars()
+            .get(name)
+            .map_or(true, |value| to_value(value).ok() != to_value(default_value).ok());
+        if !overridden {
+            return RemovedDefinition::Builtin;
+        }
+
+        let mut ctxt = ctxt.clone();
+        ctxt.add_var(name, default_value.clone());
+        return RemovedDefinition::Restored(ctxt);
+    }
+
+    match ctxt.get_funcs().get(name) {
+        Some(Func::UserFunc(_)) => (),
+        Some(_) => return RemovedDefinition::Builtin,
+        None if !ctxt.get_vars().contains_key(name) => return RemovedDefinition::NotDefined,
+        None => (),
+    }
+
+    // the context has no method to remove a definition, so remove it from the serialized context
+    let mut value = to_value(ctxt).unwrap();
+    if let Value::Object(fields) = &mut value {
+        for field in fields.values_mut() {
+            if let Value::Object(definitions) = field {
+                definitions.remove(name);
+            }
+        }
+    }
+    from_value(value).map_or(RemovedDefinition::NotDefined, RemovedDefinition::Removed)
+}
+
+/// Returns the default context with the calculation mode of the given context, removing every
+/// definition.
+pub fn reset_ctxt(ctxt: &Ctxt) -> Ctxt {
+    let mut reset = Ctxt::default();
+    reset.trig_mode = ctxt.trig_mode;
+    reset
+}
 
 /// Represents user-specific data across all sessions.
 #[derive(Debug, Clone, Default)]
@@ -12,13 +278,72 @@ pub struct UserData {
 
     /// The timers the user has set.
     pub timers: HashMap<String, Timer>,
+
+    /// The user's personal preferences.
+    pub settings: UserSettings,
+
+    /// The units the user has defined for unit conversion, keyed by name.
+    pub custom_units: HashMap<String, CustomUnit>,
+
+    /// Definitions in the user's stored context that could not be loaded. This is not stored
+    /// separately in the database; see [`QuarantinedDefinition`].
+    pub quarantined: Vec<QuarantinedDefinition>,
+
+    /// Whether the user has been told about their quarantined definitions in this session.
+    pub quarantine_notified: bool,
+
+    /// Whether the data could not be loaded from the database, in which case this is a default
+    /// stand-in. It is not written back to the database, so that the stored data is not
+    /// overwritten, and it is loaded again the next time it is requested.
+    pub load_failed: bool,
+}
+
+impl UserData {
+    /// Returns every field of the user data that is stored in the `users` table, in the order of
+    /// [`UserField::column_names`].
+    pub fn fields(&self) -> Vec<UserField> {
+        // destructured so that a new field must be either stored or explicitly left out
+        let Self {
+            ctxt,
+            timers,
+            settings,
+            custom_units,
+            quarantined: _,
+            quarantine_notified: _,
+            load_failed: _,
+        } = self;
+        vec![
+            UserField::Ctxt(ctxt.clone()),
+            UserField::Timers(timers.clone()),
+            UserField::Settings(settings.clone()),
+            UserField::CustomUnits(custom_units.clone()),
+        ]
+    }
+}
+
+/// Deserializes a JSON column of the `users` table, using the default value if the column is
+/// missing, `NULL`, or cannot be deserialized.
+fn json_or_default<T: DeserializeOwned + Default>(column: &str, raw: Option<String>) -> T {
+    let Some(raw) = raw else {
+        return T::default();
+    };
+    from_str(&raw).unwrap_or_else(|err| {
+        log::warn!("could not load stored `{}`; using the default: {}", column, err);
+        T::default()
+    })
 }
 
 impl FromRow for UserData {
     fn from_row_opt(row: mysql_async::Row) -> Result<Self, FromRowError> {
+        let (ctxt, quarantined) = load_ctxt(&row.get::<Option<String>, _>("ctxt").flatten().unwrap_or_default());
         Ok(Self {
-            ctxt: from_str(&row.get::<String, _>("ctxt").unwrap()).unwrap(),
-            timers: from_str(&row.get::<String, _>("timers").unwrap()).unwrap(),
+            ctxt,
+            timers: json_or_default("timers", row.get::<Option<String>, _>("timers").flatten()),
+            settings: json_or_default("settings", row.get::<Option<String>, _>("settings").flatten()),
+            custom_units: json_or_default("custom_units", row.get::<Option<String>, _>("custom_units").flatten()),
+            quarantined,
+            quarantine_notified: false,
+            load_failed: false,
         })
     }
 }
@@ -31,4 +356,226 @@ pub enum UserField {
 
     /// The timers the user has set.
     Timers(HashMap<String, Timer>),
+
+    /// The user's personal preferences.
+    Settings(UserSettings),
+
+    /// The units the user has defined for unit conversion.
+    CustomUnits(HashMap<String, CustomUnit>),
+}
+
+impl UserField {
+    /// Returns every field with its default value, which is what a new user's row is created
+    /// with. The order matches the order of the columns in [`UserField::column_names`].
+    ///
+    /// When adding a variant, add it here too; the `users` table must have a column for it.
+    pub fn defaults() -> Vec<UserField> {
+        vec![
+            UserField::Ctxt(Ctxt::default()),
+            UserField::Timers(HashMap::new()),
+            UserField::Settings(UserSettings::default()),
+            UserField::CustomUnits(HashMap::new()),
+        ]
+    }
+
+    /// Returns the names of the columns of the `users` table that store [`UserData`].
+    pub fn column_names() -> Vec<&'static str> {
+        Self::defaults().iter().map(UserField::column_name).collect()
+    }
+
+    /// Returns the name of the column of the `users` table that stores this field.
+    pub fn column_name(&self) -> &'static str {
+        match self {
+            UserField::Ctxt(_) => "ctxt",
+            UserField::Timers(_) => "timers",
+            UserField::Settings(_) => "settings",
+            UserField::CustomUnits(_) => "custom_units",
+        }
+    }
+
+    /// Serializes the value of this field for storage.
+    pub fn to_value(&self) -> Value {
+        match self {
+            UserField::Ctxt(ctxt) => to_value(ctxt),
+            UserField::Timers(timers) => to_value(timers),
+            UserField::Settings(settings) => to_value(settings),
+            UserField::CustomUnits(custom_units) => to_value(custom_units),
+        }.unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cas_compute::numerical::ctxt::TrigMode;
+    use super::*;
+
+    #[test]
+    fn missing_column_uses_default() {
+        let custom_units = json_or_default::<HashMap<String, CustomUnit>>("custom_units", None);
+        assert!(custom_units.is_empty());
+    }
+
+    #[test]
+    fn invalid_column_uses_default() {
+        let settings = json_or_default::<UserSettings>("settings", Some("not json".to_owned()));
+        assert!(!settings.group_digits);
+    }
+
+    #[test]
+    fn stored_column_is_loaded() {
+        let custom_units = json_or_default::<HashMap<String, CustomUnit>>(
+            "custom_units",
+            Some(r#"{"smoot": {"amount": 1.7018, "unit": "m"}}"#.to_owned()),
+        );
+        assert_eq!(custom_units["smoot"].amount, 1.7018);
+        assert_eq!(custom_units["smoot"].unit, "m");
+    }
+
+    #[test]
+    fn settings_saved_before_new_fields_load() {
+        let settings = json_or_default::<UserSettings>("settings", Some(r#"{"group_digits": true}"#.to_owned()));
+        assert!(settings.group_digits);
+        assert_eq!(settings.time_zone, UserTimeZone::Offset(0));
+    }
+
+    #[test]
+    fn offset_time_zone_is_stored_as_minutes() {
+        let settings = json_or_default::<UserSettings>("settings", Some(r#"{"time_zone": 330}"#.to_owned()));
+        assert_eq!(settings.time_zone, UserTimeZone::Offset(330));
+        assert_eq!(to_value(settings.time_zone).unwrap(), Value::from(330));
+    }
+
+    #[test]
+    fn offset_time_zone_resolves_local_times() {
+        let local = NaiveDateTime::parse_from_str("2024-03-10 09:00", "%Y-%m-%d %H:%M").unwrap();
+        let resolved = UserTimeZone::Offset(-300).resolve_local(local).unwrap();
+        assert_eq!(resolved.to_rfc3339(), "2024-03-10T09:00:00-05:00");
+    }
+
+    /// Resolves the local date and time, like `2025-03-09 02:30`, in New York.
+    fn resolve_in_new_york(local: &str) -> Option<String> {
+        let local = NaiveDateTime::parse_from_str(local, "%Y-%m-%d %H:%M").unwrap();
+        UserTimeZone::Named(chrono_tz::America::New_York)
+            .resolve_local(local)
+            .map(|resolved| resolved.to_rfc3339())
+    }
+
+    #[test]
+    fn named_time_zone_follows_daylight_saving_time() {
+        assert_eq!(resolve_in_new_york("2025-01-15 09:00").as_deref(), Some("2025-01-15T09:00:00-05:00"));
+        assert_eq!(resolve_in_new_york("2025-07-15 09:00").as_deref(), Some("2025-07-15T09:00:00-04:00"));
+    }
+
+    #[test]
+    fn skipped_local_time_is_rejected() {
+        assert_eq!(resolve_in_new_york("2025-03-09 02:30"), None);
+    }
+
+    #[test]
+    fn repeated_local_time_is_the_earlier() {
+        assert_eq!(resolve_in_new_york("2025-11-02 01:30").as_deref(), Some("2025-11-02T01:30:00-04:00"));
+    }
+
+    #[test]
+    fn empty_context_is_default() {
+        let (_, quarantined) = load_ctxt("");
+        assert!(quarantined.is_empty());
+    }
+
+    #[test]
+    fn context_round_trips() {
+        let stored = store_ctxt(&Ctxt::default(), &[]).to_string();
+        let (ctxt, quarantined) = load_ctxt(&stored);
+        assert!(quarantined.is_empty());
+        assert_eq!(to_value(ctxt).unwrap(), to_value(Ctxt::default()).unwrap());
+    }
+
+    #[test]
+    fn unreadable_context_is_kept() {
+        let (ctxt, quarantined) = load_ctxt("not json");
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].name, UNREADABLE_CONTEXT);
+
+        // saving the default context in its place writes the raw context back
+        let stored = store_ctxt(&ctxt, &quarantined);
+        assert_eq!(stored[UNREADABLE_CONTEXT], Value::String("not json".to_owned()));
+
+        // and it survives being loaded and saved again
+        let (ctxt, quarantined) = load_ctxt(&stored.to_string());
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(store_ctxt(&ctxt, &quarantined), stored);
+    }
+
+    /// Returns the default context with the variable of the given name set to the default value
+    /// of the other given variable.
+    fn with_var(name: &str, value_of: &str) -> Ctxt {
+        let mut ctxt = Ctxt::default();
+        ctxt.add_var(name, Ctxt::default().get_vars()[value_of].clone());
+        ctxt
+    }
+
+    #[test]
+    fn remove_user_variable() {
+        let RemovedDefinition::Removed(ctxt) = remove_definition(&with_var("x", "e"), "x") else {
+            panic!("expected the variable to be removed");
+        };
+        assert!(!ctxt.get_vars().contains_key("x"));
+        assert!(ctxt.get_vars().contains_key("pi"));
+    }
+
+    #[test]
+    fn remove_undefined_name() {
+        assert!(matches!(remove_definition(&Ctxt::default(), "x"), RemovedDefinition::NotDefined));
+    }
+
+    #[test]
+    fn builtin_constants_are_not_removed() {
+        assert!(matches!(remove_definition(&Ctxt::default(), "pi"), RemovedDefinition::Builtin));
+        assert!(matches!(remove_definition(&Ctxt::default(), "e"), RemovedDefinition::Builtin));
+    }
+
+    #[test]
+    fn overridden_builtin_constant_is_restored() {
+        let RemovedDefinition::Restored(ctxt) = remove_definition(&with_var("pi", "e"), "pi") else {
+            panic!("expected the constant to be restored");
+        };
+        assert_eq!(to_value(ctxt).unwrap(), to_value(Ctxt::default()).unwrap());
+    }
+
+    #[test]
+    fn reset_keeps_calculation_mode() {
+        let mut ctxt = with_var("x", "e");
+        ctxt.trig_mode = TrigMode::Degrees;
+        let reset = reset_ctxt(&ctxt);
+        assert!(matches!(reset.trig_mode, TrigMode::Degrees));
+        assert!(!reset.get_vars().contains_key("x"));
+        assert!(reset.get_vars().contains_key("pi"));
+    }
+
+    #[test]
+    fn stored_fields_match_columns() {
+        let columns = UserData::default()
+            .fields()
+            .iter()
+            .map(UserField::column_name)
+            .collect::<Vec<_>>();
+        assert_eq!(columns, UserField::column_names());
+    }
+
+    #[test]
+    fn every_field_has_a_column() {
+        let existing = [("users", "id"), ("users", "ctxt"), ("users", "timers")]
+            .iter()
+            .map(|(table, name)| (table.to_string(), name.to_string()))
+            .collect::<Vec<_>>();
+        let migrated = crate::database::schema::missing_columns(&existing)
+            .into_iter()
+            .filter(|column| column.table == "users")
+            .map(|column| column.name)
+            .chain(["ctxt", "timers"])
+            .collect::<Vec<_>>();
+        for name in UserField::column_names() {
+            assert!(migrated.contains(&name), "`users.{}` is not created or migrated", name);
+        }
+    }
 }
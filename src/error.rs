@@ -1,8 +1,7 @@
-use std::{fmt::Debug, future::IntoFuture};
-use twilight_http::{
-    request::channel::message::CreateMessage,
-    response::{DeserializeBodyError, ResponseFuture},
-};
+use crate::commands::{Context, Reply};
+use std::{fmt::Debug, future::IntoFuture, io};
+use strip_ansi_escapes::strip;
+use twilight_http::response::{DeserializeBodyError, ResponseFuture};
 use twilight_model::channel::message::Message;
 use twilight_validate::message::MessageValidationError;
 
@@ -10,11 +9,11 @@ use twilight_validate::message::MessageValidationError;
 pub trait Error: Debug {
     /// Creates a rich Discord message with the given base, describing the error.
     ///
-    /// Because [`CreateMessage`] borrows its content, this makes it impossible to return a
-    /// [`CreateMessage`] directly, as many error types need to generate their own data. Instead,
-    /// this method takes an extra step and returns a [`ResponseFuture`] (which can be done by
-    /// using the [`std::future::IntoFuture`] trait). When awaited, the message will be sent.
-    fn rich_fmt<'a>(&self, init: CreateMessage<'a>) -> Result<ResponseFuture<Message>, MessageValidationError>;
+    /// Because [`Reply`] borrows its content, this makes it impossible to return a [`Reply`]
+    /// directly, as many error types need to generate their own data. Instead, this method takes
+    /// an extra step and returns a [`ResponseFuture`] (which can be done by using the
+    /// [`std::future::IntoFuture`] trait). When awaited, the message will be sent.
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError>;
 }
 
 impl<T> From<T> for Box<dyn Error + Send + Sync>
@@ -33,7 +32,7 @@ macro_rules! generic_error_impl {
     ($($name:ty)+) => {
         $(
             impl Error for $name {
-                fn rich_fmt<'a>(&self, init: CreateMessage<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+                fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
                     Ok(init.content(&format!("**Oops!** CalcBot processed your command correctly, but Discord rejected the response message. This could be a bug!\nPlease report this to the developers, and include this error code:\n```\n{}\n```", stringify!($name)))?
                         .into_future())
                 }
@@ -43,29 +42,241 @@ macro_rules! generic_error_impl {
 }
 
 generic_error_impl! {
+    crate::custom_id::CustomIdError
     DeserializeBodyError
     MessageValidationError
     std::env::VarError
     twilight_http::Error
+    twilight_util::builder::embed::ImageSourceUrlError
 }
 
 impl Error for &str {
-    fn rich_fmt<'a>(&self, init: CreateMessage<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
         Ok(init.content(self)?
             .into_future())
     }
 }
 
+/// The user tried to create a reminder while already having the maximum number of reminders.
+#[derive(Debug)]
+pub struct TooManyTimers {
+    /// The maximum number of reminders a user can have.
+    pub limit: usize,
+}
+
+impl Error for TooManyTimers {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        Ok(init.content(&format!(
+            "**You can have at most {} reminders at a time.** Delete some of your reminders before creating new ones.",
+            self.limit,
+        ))?
+            .into_future())
+    }
+}
+
+/// A quantity of time could not be converted to a duration, for example when creating or snoozing
+/// a reminder.
+#[derive(Debug, PartialEq)]
+pub enum InvalidDuration {
+    /// The unit is not a valid time unit.
+    Unit(String),
+
+    /// The quantity is negative or not a number.
+    Quantity(f64),
+
+    /// The duration is too large to be represented.
+    TooLarge,
+}
+
+impl Error for InvalidDuration {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        let content = match self {
+            Self::Unit(unit) => format!("**`{}` is not a valid time unit.**", unit),
+            Self::Quantity(quantity) => format!("**`{}` is not a valid quantity of time.** It cannot be negative.", quantity),
+            Self::TooLarge => "**That amount of time is too large.**".to_string(),
+        };
+        Ok(init.content(&content)?
+            .into_future())
+    }
+}
+
 /// An argument was missing from a command invocation.
 #[derive(Debug)]
 pub struct MissingArgument {
     /// The index of the argument that was missing.
     pub index: usize,
+
+    /// The placeholder of the argument in the command's syntax, like `<time unit>`, if known.
+    pub name: Option<&'static str>,
+
+    /// The syntax of the command, shown to help the user fix their command.
+    pub syntax_hint: Option<&'static str>,
 }
 
 impl Error for MissingArgument {
-    fn rich_fmt<'a>(&self, init: CreateMessage<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
-        Ok(init.content(&format!("Missing argument at index {}.", self.index))?
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        let mut content = match self.name {
+            Some(name) => format!("**Missing `{}` (argument {}).**", name, self.index + 1),
+            None => format!("**Missing argument {}.**", self.index + 1),
+        };
+        if let Some(syntax_hint) = self.syntax_hint {
+            content.push_str(&format!(" Syntax: {}", syntax_hint));
+        }
+
+        Ok(init.content(&content)?
+            .into_future())
+    }
+}
+
+/// An argument of a command invocation could not be parsed as the expected type.
+#[derive(Debug)]
+pub struct InvalidArgument {
+    /// The index of the argument that could not be parsed.
+    pub index: usize,
+
+    /// The argument as given by the user.
+    pub value: String,
+
+    /// A description of the values the argument accepts, like `an integer from 0 to 255`.
+    pub expected: String,
+}
+
+impl Error for InvalidArgument {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        Ok(init.content(&format!(
+            "**`{}` is not valid for argument {}.** Expected {}.",
+            self.value,
+            self.index + 1,
+            self.expected,
+        ))?
             .into_future())
     }
 }
+
+/// The user gave a word that does not match any option of an enum deriving `ParseArg`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InvalidOption {
+    /// The word as given by the user.
+    pub value: String,
+
+    /// The first alias of each option.
+    pub options: &'static [&'static str],
+}
+
+impl InvalidOption {
+    /// Describes the accepted options, like ``one of: `radians`, `degrees` ``.
+    pub fn expected(&self) -> String {
+        let options = self.options.iter()
+            .map(|option| format!("`{}`", option))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("one of: {}", options)
+    }
+}
+
+impl Error for InvalidOption {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        Ok(init.content(&format!(
+            "**`{}` is not a valid option.** Choose {}.",
+            self.value,
+            self.expected(),
+        ))?
+            .into_future())
+    }
+}
+
+/// The user's input could not be parsed or evaluated by the CAS.
+///
+/// Each error is shown as a report rendered by [`ariadne`], which points to the part of the input
+/// that caused it. The reports are shown in a code block, and the last lines are left out if they
+/// do not fit in a single message.
+#[derive(Debug)]
+pub struct CasErrors {
+    /// The rendered reports, without ANSI colors.
+    reports: Vec<String>,
+}
+
+impl CasErrors {
+    /// Creates a [`CasErrors`] from reports rendered with [`CasErrors::render_report`].
+    pub fn new(reports: Vec<String>) -> Self {
+        Self { reports }
+    }
+
+    /// Renders a report as plain text, given a function that writes an [`ariadne::Report`] to a
+    /// buffer, like `|buf| err.build_report().write(("input", Source::from(input)), buf)`.
+    pub fn render_report(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut buf = Vec::new();
+        write(&mut buf).unwrap();
+        String::from_utf8_lossy(&strip(buf).unwrap()).into_owned()
+    }
+
+    /// Returns the reports in a code block that fits in a single message.
+    pub fn code_block(&self) -> String {
+        // room for the code block fences and an ellipsis line
+        let budget = Context::MAX_CONTENT_LENGTH - "```rs\n\n```".len() - "\n…".len();
+
+        let mut shown = String::new();
+        let mut length = 0;
+        for line in self.reports.join("\n").lines() {
+            length += line.chars().count() + 1;
+            if length > budget {
+                shown.push('…');
+                break;
+            }
+            shown.push_str(line);
+            shown.push('\n');
+        }
+        format!("```rs\n{}\n```", shown.trim_end_matches('\n'))
+    }
+}
+
+impl Error for CasErrors {
+    fn rich_fmt<'a>(&self, init: Reply<'a>) -> Result<ResponseFuture<Message>, MessageValidationError> {
+        Ok(init.content(&self.code_block())?
+            .into_future())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ariadne::Source;
+    use cas_parser::parser::Parser;
+
+    #[test]
+    fn short_reports_are_shown_whole() {
+        let errors = CasErrors::new(vec!["first\nreport".to_owned(), "second".to_owned()]);
+        assert_eq!(errors.code_block(), "```rs\nfirst\nreport\nsecond\n```");
+    }
+
+    #[test]
+    fn long_reports_are_truncated() {
+        let errors = CasErrors::new(vec!["a line of the report\n".repeat(1000)]);
+        let block = errors.code_block();
+        assert!(block.chars().count() <= Context::MAX_CONTENT_LENGTH);
+        assert!(block.starts_with("```rs\na line of the report\n"));
+        assert!(block.ends_with("a line of the report\n…\n```"));
+    }
+
+    #[test]
+    fn caret_points_at_error() {
+        let source = "1 +";
+        let errs = Parser::new(source).try_parse_full_many().unwrap_err();
+        let span = errs[0].spans[0].clone();
+        let report = CasErrors::render_report(|buf| {
+            errs[0].build_report().write(("input", Source::from(source)), buf)
+        });
+
+        // the marker under the source line starts at the column of the error's span
+        let lines = report.lines().collect::<Vec<_>>();
+        let source_line = lines.iter().position(|line| line.ends_with(source)).unwrap();
+        let column = lines[source_line].chars().count() - source.chars().count();
+        let marker = lines[source_line + 1].chars()
+            .enumerate()
+            .skip(column)
+            .find(|(_, ch)| *ch != ' ')
+            .map(|(i, _)| i)
+            .unwrap();
+        assert_eq!(marker, column + span.start);
+    }
+}
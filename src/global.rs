@@ -1,9 +1,352 @@
-use super::commands::{self, CommandGroup};
-use std::{collections::HashMap, time::Instant};
+use super::{commands::{self, remind::MAX_TIMERS_PER_USER, CommandGroup, CommandInfo}, custom_id::CustomId, database::Database, handler, tasks::TaskTracker};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    error::Error,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    sync::{atomic::AtomicU64, Arc, Mutex as StdMutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
 use twilight_cache_inmemory::{InMemoryCache, ResourceType};
 use twilight_http::Client as HttpClient;
-use twilight_model::{channel::message::Embed, id::{marker::ApplicationMarker, Id}};
-use twilight_util::builder::embed::{EmbedBuilder, EmbedFieldBuilder};
+use twilight_model::{
+    application::command::{Command as ApplicationCommand, CommandType},
+    channel::message::Embed,
+    gateway::payload::incoming::InteractionCreate,
+    id::{marker::{ApplicationMarker, GuildMarker, UserMarker}, Id},
+};
+use twilight_util::builder::{
+    command::{CommandBuilder, StringBuilder, SubCommandBuilder, SubCommandGroupBuilder},
+    embed::{EmbedBuilder, EmbedFieldBuilder},
+};
+
+/// A function that handles component interactions whose custom ID is in a specific namespace.
+pub type InteractionHandler = fn(
+    Arc<State>,
+    Arc<Mutex<Database>>,
+    InteractionCreate,
+    CustomId,
+) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>>;
+
+/// Parses the comma-separated list of owner user IDs, as given by the `AUTHOR_ID` environment
+/// variable. Invalid IDs are skipped with a warning.
+fn parse_owners(value: Option<&str>) -> Vec<Id<UserMarker>> {
+    let owners = value.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .filter_map(|id| match id.parse::<Id<UserMarker>>() {
+            Ok(id) => Some(id),
+            Err(_) => {
+                log::warn!("ignoring invalid owner ID in AUTHOR_ID: {}", id);
+                None
+            },
+        })
+        .collect::<Vec<_>>();
+
+    if owners.is_empty() {
+        log::warn!("no owners configured in AUTHOR_ID; owner-only features are disabled");
+    }
+
+    owners
+}
+
+/// Parses the comma-separated list of command paths to disable, as given by the
+/// `DISABLED_COMMANDS` environment variable, like `dictionary, nm unscramble`. Each path is
+/// resolved to the default aliases of the command, and unknown paths are skipped with a warning.
+fn parse_disabled_commands(commands: &CommandGroup, value: Option<&str>) -> HashSet<String> {
+    value.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .filter_map(|path| match commands.resolve_path(path) {
+            Some(resolved) => Some(resolved),
+            None => {
+                log::warn!("ignoring unknown command in DISABLED_COMMANDS: {}", path);
+                None
+            },
+        })
+        .collect()
+}
+
+/// Parses the maximum number of reminders a user can have, as given by the `MAX_TIMERS_PER_USER`
+/// environment variable. If the variable is missing or invalid, [`MAX_TIMERS_PER_USER`] is used.
+fn parse_max_timers(value: Option<&str>) -> usize {
+    match value.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(limit)) => limit,
+        Some(Err(_)) => {
+            log::warn!("ignoring invalid MAX_TIMERS_PER_USER; using the default of {}", MAX_TIMERS_PER_USER);
+            MAX_TIMERS_PER_USER
+        },
+        None => MAX_TIMERS_PER_USER,
+    }
+}
+
+/// The default minimum time between invocations of the same command by the same user.
+const DEFAULT_COMMAND_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Parses the minimum time between invocations of the same command by the same user, as given in
+/// milliseconds by the `COMMAND_COOLDOWN_MS` environment variable. If the variable is missing or
+/// invalid, [`DEFAULT_COMMAND_COOLDOWN`] is used.
+fn parse_command_cooldown(value: Option<&str>) -> Duration {
+    match value.map(|value| value.trim().parse::<u64>()) {
+        Some(Ok(millis)) => Duration::from_millis(millis),
+        Some(Err(_)) => {
+            log::warn!("ignoring invalid COMMAND_COOLDOWN_MS; using the default of {}ms", DEFAULT_COMMAND_COOLDOWN.as_millis());
+            DEFAULT_COMMAND_COOLDOWN
+        },
+        None => DEFAULT_COMMAND_COOLDOWN,
+    }
+}
+
+/// The default time a calculation is allowed to run for.
+const DEFAULT_CALCULATION_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Parses the time a calculation is allowed to run for, as given in seconds by the
+/// `CALCULATION_TIMEOUT_SECS` environment variable. If the variable is missing or invalid,
+/// [`DEFAULT_CALCULATION_TIMEOUT`] is used.
+fn parse_calculation_timeout(value: Option<&str>) -> Duration {
+    match value.map(|value| value.trim().parse::<u64>()) {
+        Some(Ok(secs)) if secs > 0 => Duration::from_secs(secs),
+        Some(_) => {
+            log::warn!("ignoring invalid CALCULATION_TIMEOUT_SECS; using the default of {}s", DEFAULT_CALCULATION_TIMEOUT.as_secs());
+            DEFAULT_CALCULATION_TIMEOUT
+        },
+        None => DEFAULT_CALCULATION_TIMEOUT,
+    }
+}
+
+/// The name of the slash subcommand that runs its parent command, for parent commands that take
+/// input of their own. See [`State::application_commands`].
+pub const RUN_SUBCOMMAND: &str = "run";
+
+/// The maximum length of a slash command or option description.
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+
+/// The maximum number of options of a slash command, including subcommands.
+const MAX_OPTIONS: usize = 25;
+
+/// The root commands that only CalcBot's owners can use. These are not registered as slash
+/// commands, so that they are not shown to everyone else.
+const OWNER_ONLY_COMMANDS: &[&str] = &["admin"];
+
+/// Shortens the given text to fit in a slash command or option description.
+fn truncate_description(text: &str) -> String {
+    match text.chars().count() {
+        0 => "No description.".to_owned(),
+        len if len > MAX_DESCRIPTION_LENGTH => {
+            let truncated = text.chars().take(MAX_DESCRIPTION_LENGTH - 1).collect::<String>();
+            format!("{}…", truncated)
+        },
+        _ => text.to_owned(),
+    }
+}
+
+/// Returns the first sentence of the command's description, to be used as the description of its
+/// slash command.
+fn short_description(info: &CommandInfo) -> String {
+    let first_line = info.description.lines().next().unwrap_or_default();
+    let sentence = match first_line.find(". ") {
+        Some(end) => &first_line[..=end],
+        None => first_line,
+    };
+    truncate_description(&sentence.replace("{prefix}", "").replace('`', ""))
+}
+
+/// Returns the option that takes the input of the command as a slash command, described by the
+/// command's syntax, or [`None`] if the command takes no input. The option is required if every
+/// form of the syntax starts with a required argument.
+fn input_option(info: &CommandInfo) -> Option<StringBuilder> {
+    let syntax = info.syntax?
+        .iter()
+        .filter(|syntax| !syntax.is_empty())
+        .copied()
+        .collect::<Vec<_>>();
+    if syntax.is_empty() {
+        return None;
+    }
+
+    let required = info.syntax?.iter().all(|syntax| syntax.starts_with('<'));
+    Some(StringBuilder::new("input", truncate_description(&syntax.join(" or "))).required(required))
+}
+
+/// Builds a slash subcommand with the given name for the command.
+fn subcommand(name: &str, info: &CommandInfo) -> SubCommandBuilder {
+    let builder = SubCommandBuilder::new(name, short_description(info));
+    match input_option(info) {
+        Some(option) => builder.option(option),
+        None => builder,
+    }
+}
+
+/// Builds the slash subcommands of a command with children: one for each child, and one named
+/// [`RUN_SUBCOMMAND`] if the command takes input of its own. Children of the children are left
+/// out, as Discord does not allow subcommands to be nested any deeper.
+fn subcommands(info: &CommandInfo) -> Vec<SubCommandBuilder> {
+    input_option(info)
+        .map(|_| subcommand(RUN_SUBCOMMAND, info))
+        .into_iter()
+        .chain(info.children.commands.iter().map(|child| {
+            let child_info = child.info();
+            subcommand(child_info.default_alias(), &child_info)
+        }))
+        .take(MAX_OPTIONS)
+        .collect()
+}
+
+/// Returns the alias of the command as listed in the `help commands` embed, marked with ⚠️ if the
+/// command is deprecated.
+fn listed_alias(info: &CommandInfo) -> String {
+    match info.deprecated {
+        Some(_) => format!("{} ⚠️", info.default_alias()),
+        None => info.default_alias().to_owned(),
+    }
+}
+
+/// The minimum time between two deprecation notices sent to the same user. See
+/// [`State::take_deprecation_notice`].
+const DEPRECATION_NOTICE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The default length, in characters, of the longest message that can be treated as a command.
+/// Longer messages, such as pasted logs, are ignored.
+const DEFAULT_MAX_COMMAND_LENGTH: usize = 4000;
+
+/// Parses the length of the longest message that can be treated as a command, as given in
+/// characters by the `MAX_COMMAND_LENGTH` environment variable. If the variable is missing or
+/// invalid, [`DEFAULT_MAX_COMMAND_LENGTH`] is used.
+fn parse_max_command_length(value: Option<&str>) -> usize {
+    match value.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(length)) if length > 0 => length,
+        Some(_) => {
+            log::warn!("ignoring invalid MAX_COMMAND_LENGTH; using the default of {}", DEFAULT_MAX_COMMAND_LENGTH);
+            DEFAULT_MAX_COMMAND_LENGTH
+        },
+        None => DEFAULT_MAX_COMMAND_LENGTH,
+    }
+}
+
+/// What [`State::could_be_command`] needs to know about a guild, copied from its server data so
+/// that messages can be filtered without locking the database.
+#[derive(Clone, Copy, Debug)]
+pub struct PrefixHint {
+    /// The first character of the guild's prefix.
+    pub first_char: char,
+
+    /// Whether the guild has inline math enabled.
+    pub inline_math: bool,
+}
+
+/// Returns true if a message with the given content could be a command or contain inline math,
+/// given the prefix hint of the guild it was sent in, if known. See [`State::could_be_command`].
+fn could_be_command(content: &str, hint: Option<PrefixHint>, max_length: usize) -> bool {
+    if content.is_empty() || content.chars().count() > max_length {
+        return false;
+    }
+
+    match hint {
+        Some(hint) => content.starts_with(hint.first_char)
+            || (hint.inline_math && content.contains("{{")),
+        None => true,
+    }
+}
+
+/// Counts of messages seen by the message handler, shown in the `about` command.
+#[derive(Debug, Default)]
+pub struct MessageStats {
+    /// The number of messages skipped by [`State::could_be_command`].
+    pub filtered: AtomicU64,
+
+    /// The number of messages that went through the full message handler.
+    pub processed: AtomicU64,
+}
+
+/// How much the latest execution time of a command moves its average, from 0 to 1.
+const LATENCY_SMOOTHING: f64 = 0.1;
+
+/// Execution metrics of a single command since the bot started, shown in the `admin stats`
+/// command.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CommandStats {
+    /// The number of times the command was run.
+    pub invocations: u64,
+
+    /// The number of times the command returned an error.
+    pub errors: u64,
+
+    /// The average execution time of the command, weighted towards recent runs.
+    pub average_latency: Duration,
+}
+
+impl CommandStats {
+    /// Records a run of the command that took the given time.
+    fn record(&mut self, elapsed: Duration, failed: bool) {
+        self.average_latency = if self.invocations == 0 {
+            elapsed
+        } else {
+            self.average_latency.mul_f64(1.0 - LATENCY_SMOOTHING) + elapsed.mul_f64(LATENCY_SMOOTHING)
+        };
+        self.invocations += 1;
+        if failed {
+            self.errors += 1;
+        }
+    }
+}
+
+/// Remembers when each key was last taken, so that whatever the key stands for, like a user being
+/// sent a notice, happens at most once per interval.
+struct RateLimiter<K> {
+    /// The minimum time between two takes of the same key.
+    interval: Duration,
+
+    /// The last time each key was taken.
+    last: StdMutex<HashMap<K, Instant>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    /// Creates a [`RateLimiter`] that allows each key to be taken once per the given interval.
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records that the key was taken at the given time. If it was already taken less than the
+    /// interval before, nothing is recorded, and the time left until it can be taken again is
+    /// returned.
+    fn take_at(&self, key: K, now: Instant) -> Option<Duration> {
+        let mut last = self.last.lock().unwrap();
+        last.retain(|_, taken| now.saturating_duration_since(*taken) < self.interval);
+        match last.get(&key) {
+            Some(taken) => Some(self.interval.saturating_sub(now.saturating_duration_since(*taken))),
+            None => {
+                last.insert(key, now);
+                None
+            },
+        }
+    }
+
+    /// Returns the keys that cannot be taken again yet at the given time, with the time left
+    /// before they can.
+    fn remaining_at(&self, now: Instant) -> Vec<(K, Duration)>
+    where
+        K: Clone,
+    {
+        self.last.lock().unwrap()
+            .iter()
+            .map(|(key, taken)| (key.clone(), self.interval.saturating_sub(now.saturating_duration_since(*taken))))
+            .filter(|(_, remaining)| !remaining.is_zero())
+            .collect()
+    }
+}
+
+/// Returns the key of the given user's cooldown on the command at the given path.
+fn cooldown_key(user_id: Id<UserMarker>, path: &[&str]) -> (Id<UserMarker>, String) {
+    (user_id, path.join(" "))
+}
 
 /// The global state of the bot.
 ///
@@ -12,6 +355,10 @@ pub struct State {
     /// The application ID of the bot.
     pub application_id: Id<ApplicationMarker>,
 
+    /// The IDs of the bot's owners, parsed from the `AUTHOR_ID` environment variable. The first
+    /// owner is credited in the `about` command. If empty, owner-only features are disabled.
+    pub owners: Vec<Id<UserMarker>>,
+
     /// The [`Instant`] the bot was started. This can be used to determine the bot's uptime.
     pub start_time: Instant,
 
@@ -23,24 +370,255 @@ pub struct State {
 
     /// The cache, which stores information received from Discord.
     pub cache: InMemoryCache,
+
+    /// Handlers for component interactions, keyed by the namespace of their custom ID.
+    pub interaction_handlers: HashMap<&'static str, InteractionHandler>,
+
+    /// The maximum number of reminders a user can have at once, parsed from the
+    /// `MAX_TIMERS_PER_USER` environment variable.
+    pub max_timers: usize,
+
+    /// The time a calculation is allowed to run for before it is given up on, parsed from the
+    /// `CALCULATION_TIMEOUT_SECS` environment variable.
+    pub calculation_timeout: Duration,
+
+    /// The minimum time between invocations of the same command by the same user, parsed from the
+    /// `COMMAND_COOLDOWN_MS` environment variable. See [`State::take_cooldown`].
+    pub command_cooldown: Duration,
+
+    /// The length of the longest message that can be treated as a command, parsed from the
+    /// `MAX_COMMAND_LENGTH` environment variable. See [`State::could_be_command`].
+    pub max_command_length: usize,
+
+    /// The long-lived tasks spawned by the bot, such as timer and pager tasks.
+    pub tasks: TaskTracker,
+
+    /// The paths of commands that are disabled globally, such as while a feature is misbehaving in
+    /// production. See [`State::is_disabled`].
+    disabled_commands: StdMutex<HashSet<String>>,
+
+    /// The last time each user ran each command, keyed by the user and the path of the command.
+    cooldowns: RateLimiter<(Id<UserMarker>, String)>,
+
+    /// The last time each user was told that a command they ran is deprecated.
+    deprecation_notices: RateLimiter<Id<UserMarker>>,
+
+    /// The prefix hints of guilds whose server data has been fetched. See
+    /// [`State::could_be_command`].
+    prefix_hints: StdMutex<HashMap<Id<GuildMarker>, PrefixHint>>,
+
+    /// Counts of messages filtered and processed by the message handler.
+    pub message_stats: MessageStats,
+
+    /// Execution metrics of each command, keyed by the path of the command. See
+    /// [`State::record_command`].
+    command_stats: StdMutex<HashMap<String, CommandStats>>,
+
+    /// The average heartbeat latency of the shard, updated whenever Discord acknowledges a
+    /// heartbeat. This is [`None`] until the first acknowledgement.
+    pub gateway_latency: StdMutex<Option<Duration>>,
 }
 
 impl State {
     /// Creates a new [`State`] with the given token.
     pub async fn new(token: String) -> Self {
         let http = HttpClient::new(token);
+        let commands = commands::root();
+        let command_cooldown = parse_command_cooldown(env::var("COMMAND_COOLDOWN_MS").ok().as_deref());
         Self {
             application_id: http.current_user_application().await.unwrap()
                 .model().await.unwrap().id,
+            owners: parse_owners(env::var("AUTHOR_ID").ok().as_deref()),
             start_time: Instant::now(),
-            commands: commands::root(),
+            disabled_commands: StdMutex::new(parse_disabled_commands(
+                &commands,
+                env::var("DISABLED_COMMANDS").ok().as_deref(),
+            )),
+            commands,
             http,
             cache: InMemoryCache::builder()
-                .resource_types(ResourceType::USER_CURRENT | ResourceType::MESSAGE)
+                .resource_types(
+                    ResourceType::USER_CURRENT
+                        | ResourceType::MESSAGE
+                        // used to check the permissions of members
+                        | ResourceType::GUILD
+                        | ResourceType::ROLE
+                        | ResourceType::MEMBER
+                        // used to tell group DMs from 1:1 DMs
+                        | ResourceType::CHANNEL
+                )
                 .build(),
+            interaction_handlers: HashMap::from([
+                ("confirm", handler::confirm as InteractionHandler),
+                ("help", commands::help::browse::handle),
+                ("pager", handler::pager as InteractionHandler),
+            ]),
+            max_timers: parse_max_timers(env::var("MAX_TIMERS_PER_USER").ok().as_deref()),
+            calculation_timeout: parse_calculation_timeout(env::var("CALCULATION_TIMEOUT_SECS").ok().as_deref()),
+            command_cooldown,
+            max_command_length: parse_max_command_length(env::var("MAX_COMMAND_LENGTH").ok().as_deref()),
+            tasks: TaskTracker::default(),
+            cooldowns: RateLimiter::new(command_cooldown),
+            deprecation_notices: RateLimiter::new(DEPRECATION_NOTICE_INTERVAL),
+            prefix_hints: StdMutex::new(HashMap::new()),
+            message_stats: MessageStats::default(),
+            command_stats: StdMutex::new(HashMap::new()),
+            gateway_latency: StdMutex::new(None),
         }
     }
 
+    /// Returns true if the command at the given path, or one of its parents, is disabled globally.
+    pub fn is_disabled(&self, path: &[&str]) -> bool {
+        let disabled = self.disabled_commands.lock().unwrap();
+        (1..=path.len()).any(|len| disabled.contains(&path[..len].join(" ")))
+    }
+
+    /// Disables or enables the command at the given path globally, until the bot restarts.
+    /// Returns the resolved path of the command, or [`None`] if there is no such command.
+    pub fn set_disabled(&self, path: &str, disabled: bool) -> Option<String> {
+        let resolved = self.commands.resolve_path(path)?;
+        let mut disabled_commands = self.disabled_commands.lock().unwrap();
+        if disabled {
+            disabled_commands.insert(resolved.clone());
+        } else {
+            disabled_commands.remove(&resolved);
+        }
+        Some(resolved)
+    }
+
+    /// Returns the paths of the commands that are disabled globally, in sorted order.
+    pub fn disabled_commands(&self) -> Vec<String> {
+        let mut disabled = self.disabled_commands.lock().unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        disabled.sort();
+        disabled
+    }
+
+    /// Returns the paths of the commands that the given user cannot run again yet, with the time
+    /// left before they can, in sorted order. See [`State::take_cooldown`].
+    pub fn active_cooldowns(&self, user_id: Id<UserMarker>) -> Vec<(String, Duration)> {
+        let mut active = self.cooldowns.remaining_at(Instant::now())
+            .into_iter()
+            .filter_map(|((id, path), remaining)| (id == user_id).then_some((path, remaining)))
+            .collect::<Vec<_>>();
+        active.sort();
+        active
+    }
+
+    /// Returns true if the given user is one of the bot's owners.
+    pub fn is_owner(&self, id: Id<UserMarker>) -> bool {
+        self.owners.contains(&id)
+    }
+
+    /// Records that the given user is running the command at the given path. If they already ran
+    /// it less than [`State::command_cooldown`] ago, nothing is recorded, and the time left until
+    /// they can run it again is returned.
+    pub fn take_cooldown(&self, user_id: Id<UserMarker>, path: &[&str]) -> Option<Duration> {
+        if self.command_cooldown.is_zero() {
+            return None;
+        }
+
+        self.cooldowns.take_at(cooldown_key(user_id, path), Instant::now())
+    }
+
+    /// Records a run of the command at the given path that took the given time, and whether it
+    /// returned an error.
+    pub fn record_command(&self, path: &[&str], elapsed: Duration, failed: bool) {
+        self.command_stats.lock().unwrap()
+            .entry(path.join(" "))
+            .or_default()
+            .record(elapsed, failed);
+    }
+
+    /// Returns the execution metrics of every command that has been run, most used first.
+    pub fn command_stats(&self) -> Vec<(String, CommandStats)> {
+        let mut stats = self.command_stats.lock().unwrap()
+            .iter()
+            .map(|(path, stats)| (path.clone(), *stats))
+            .collect::<Vec<_>>();
+        stats.sort_by(|(a_path, a), (b_path, b)| b.invocations.cmp(&a.invocations).then_with(|| a_path.cmp(b_path)));
+        stats
+    }
+
+    /// Returns true if the given user should be told that a command they ran is deprecated. Each
+    /// user is told at most once per day, no matter how many deprecated commands they run.
+    pub fn take_deprecation_notice(&self, user_id: Id<UserMarker>) -> bool {
+        self.deprecation_notices.take_at(user_id, Instant::now()).is_none()
+    }
+
+    /// Records the prefix and inline math setting of the given guild, to be used by
+    /// [`State::could_be_command`].
+    pub fn set_prefix_hint(&self, guild_id: Id<GuildMarker>, prefix: &str, inline_math: bool) {
+        let Some(first_char) = prefix.chars().next() else {
+            // an empty prefix matches every message, so there is nothing to filter by
+            self.prefix_hints.lock().unwrap().remove(&guild_id);
+            return;
+        };
+        self.prefix_hints.lock().unwrap()
+            .insert(guild_id, PrefixHint { first_char, inline_math });
+    }
+
+    /// Cheaply checks if a message could be a command or contain inline math, without locking the
+    /// database. This is conservative: if it returns false, the message handler would have ignored
+    /// the message anyway.
+    ///
+    /// Empty messages and messages longer than [`State::max_command_length`] are always rejected.
+    /// Guild messages are also rejected if they don't start with the first character of the
+    /// guild's prefix and can't contain inline math, but only if the guild's prefix hint is known.
+    /// DMs and guilds without a hint are never filtered by prefix.
+    pub fn could_be_command(&self, guild_id: Option<Id<GuildMarker>>, content: &str) -> bool {
+        let hint = guild_id.and_then(|id| self.prefix_hints.lock().unwrap().get(&id).copied());
+        could_be_command(content, hint, self.max_command_length)
+    }
+
+    /// Builds the slash commands to register with Discord from the command tree. Each command at
+    /// the root becomes a slash command, with its input as a single string option.
+    ///
+    /// Commands with children become slash commands with subcommands, and children with children
+    /// of their own become subcommand groups. Since a slash command with subcommands cannot be
+    /// used by itself, parent commands that take input also get a [`RUN_SUBCOMMAND`] subcommand.
+    ///
+    /// Owner-only commands are left out; see [`OWNER_ONLY_COMMANDS`].
+    pub fn application_commands(&self) -> Vec<ApplicationCommand> {
+        self.commands.commands.iter()
+            .map(|cmd| cmd.info())
+            .filter(|info| !OWNER_ONLY_COMMANDS.contains(&info.default_alias()))
+            .map(|info| {
+                let mut builder = CommandBuilder::new(
+                    info.default_alias(),
+                    short_description(&info),
+                    CommandType::ChatInput,
+                );
+
+                if info.children.commands.is_empty() {
+                    if let Some(option) = input_option(&info) {
+                        builder = builder.option(option);
+                    }
+                    return builder.build();
+                }
+
+                let mut options = 0;
+                if input_option(&info).is_some() {
+                    builder = builder.option(subcommand(RUN_SUBCOMMAND, &info));
+                    options += 1;
+                }
+                for child in info.children.commands.iter().take(MAX_OPTIONS - options) {
+                    let child_info = child.info();
+                    builder = match child_info.children.commands.is_empty() {
+                        true => builder.option(subcommand(child_info.default_alias(), &child_info)),
+                        false => builder.option(
+                            SubCommandGroupBuilder::new(child_info.default_alias(), short_description(&child_info))
+                                .subcommands(subcommands(&child_info)),
+                        ),
+                    };
+                }
+                builder.build()
+            })
+            .collect()
+    }
+
     /// Build the `c-help commands` embed.
     pub fn build_commands_embed(&self, prefix: Option<&str>) -> Embed {
         let mut embed = EmbedBuilder::new()
@@ -71,11 +649,15 @@ impl State {
 
         for cmd in &self.commands.commands {
             let info = cmd.info();
+            if self.is_disabled(&[info.default_alias()]) {
+                continue;
+            }
+
             let category = info.category.unwrap();
             categories
                 .entry(category)
                 .or_insert_with(Vec::new)
-                .push(info.default_alias());
+                .push(listed_alias(&info));
         }
 
         for (category, commands) in categories {
@@ -92,3 +674,197 @@ impl State {
         embed.build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: PrefixHint = PrefixHint { first_char: 'c', inline_math: false };
+
+    #[test]
+    fn commands_pass_filter() {
+        for content in ["c-calc 1+1", "c-help", "c-remind 5 minutes tea"] {
+            assert!(could_be_command(content, Some(PREFIX), DEFAULT_MAX_COMMAND_LENGTH));
+        }
+
+        let custom = PrefixHint { first_char: '!', inline_math: false };
+        assert!(could_be_command("!calc 1+1", Some(custom), DEFAULT_MAX_COMMAND_LENGTH));
+        assert!(!could_be_command("c-calc 1+1", Some(custom), DEFAULT_MAX_COMMAND_LENGTH));
+    }
+
+    #[test]
+    fn unknown_prefix_is_not_filtered() {
+        // DMs, and guilds whose server data has not been fetched yet
+        assert!(could_be_command("calc 1+1", None, DEFAULT_MAX_COMMAND_LENGTH));
+        assert!(could_be_command("hello there", None, DEFAULT_MAX_COMMAND_LENGTH));
+    }
+
+    #[test]
+    fn inline_math_passes_filter() {
+        let hint = PrefixHint { inline_math: true, ..PREFIX };
+        assert!(could_be_command("what is {{2^10}}?", Some(hint), DEFAULT_MAX_COMMAND_LENGTH));
+        assert!(!could_be_command("what is {{2^10}}?", Some(PREFIX), DEFAULT_MAX_COMMAND_LENGTH));
+    }
+
+    #[test]
+    fn long_and_empty_messages_are_filtered() {
+        let pasted = format!("c-calc {}", "1+".repeat(DEFAULT_MAX_COMMAND_LENGTH));
+        assert!(!could_be_command(&pasted, None, DEFAULT_MAX_COMMAND_LENGTH));
+        assert!(could_be_command(&pasted, None, pasted.len()));
+        assert!(!could_be_command("", None, DEFAULT_MAX_COMMAND_LENGTH));
+    }
+
+    #[test]
+    fn max_command_length_from_env() {
+        assert_eq!(parse_max_command_length(None), DEFAULT_MAX_COMMAND_LENGTH);
+        assert_eq!(parse_max_command_length(Some(" 8000 ")), 8000);
+        assert_eq!(parse_max_command_length(Some("0")), DEFAULT_MAX_COMMAND_LENGTH);
+        assert_eq!(parse_max_command_length(Some("lots")), DEFAULT_MAX_COMMAND_LENGTH);
+    }
+
+    #[test]
+    fn owners_from_env() {
+        assert!(parse_owners(None).is_empty());
+        assert_eq!(parse_owners(Some("1, 2,,")), vec![Id::new(1), Id::new(2)]);
+        assert_eq!(parse_owners(Some("0,abc,3")), vec![Id::new(3)]);
+    }
+
+    #[test]
+    fn disabled_commands_from_env() {
+        let commands = commands::root();
+        assert!(parse_disabled_commands(&commands, None).is_empty());
+        assert_eq!(
+            parse_disabled_commands(&commands, Some("calc base, nm unscramble,")),
+            HashSet::from(["calculate base".to_owned(), "notmath unscramble".to_owned()]),
+        );
+        assert!(parse_disabled_commands(&commands, Some("calc nonsense, nothing")).is_empty());
+    }
+
+    #[test]
+    fn max_timers_from_env() {
+        assert_eq!(parse_max_timers(None), MAX_TIMERS_PER_USER);
+        assert_eq!(parse_max_timers(Some(" 5 ")), 5);
+        assert_eq!(parse_max_timers(Some("-1")), MAX_TIMERS_PER_USER);
+    }
+
+    #[test]
+    fn command_cooldown_from_env() {
+        assert_eq!(parse_command_cooldown(None), DEFAULT_COMMAND_COOLDOWN);
+        assert_eq!(parse_command_cooldown(Some("250")), Duration::from_millis(250));
+        assert_eq!(parse_command_cooldown(Some("0")), Duration::ZERO);
+        assert_eq!(parse_command_cooldown(Some("1s")), DEFAULT_COMMAND_COOLDOWN);
+    }
+
+    #[test]
+    fn first_run_sets_latency() {
+        let mut stats = CommandStats::default();
+        stats.record(Duration::from_millis(200), false);
+        assert_eq!(stats.invocations, 1);
+        assert_eq!(stats.errors, 0);
+        assert_eq!(stats.average_latency, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn later_runs_are_smoothed() {
+        let mut stats = CommandStats::default();
+        stats.record(Duration::from_millis(100), false);
+        stats.record(Duration::from_millis(1100), true);
+        assert_eq!(stats.invocations, 2);
+        assert_eq!(stats.errors, 1);
+        assert!((stats.average_latency.as_secs_f64() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn calculation_timeout_from_env() {
+        assert_eq!(parse_calculation_timeout(None), DEFAULT_CALCULATION_TIMEOUT);
+        assert_eq!(parse_calculation_timeout(Some(" 10 ")), Duration::from_secs(10));
+        assert_eq!(parse_calculation_timeout(Some("0")), DEFAULT_CALCULATION_TIMEOUT);
+        assert_eq!(parse_calculation_timeout(Some("forever")), DEFAULT_CALCULATION_TIMEOUT);
+    }
+
+    /// Returns the metadata of the command at the given path, given as default aliases.
+    fn info(path: &str) -> CommandInfo {
+        commands::root().walk()
+            .into_iter()
+            .find(|(command_path, _)| command_path.join(" ") == path)
+            .map(|(_, info)| info)
+            .unwrap()
+    }
+
+    #[test]
+    fn descriptions_are_truncated() {
+        assert_eq!(truncate_description(""), "No description.");
+        assert_eq!(truncate_description("short"), "short");
+
+        let truncated = truncate_description(&"x".repeat(MAX_DESCRIPTION_LENGTH + 1));
+        assert_eq!(truncated.chars().count(), MAX_DESCRIPTION_LENGTH);
+        assert!(truncated.ends_with('…'));
+    }
+
+    #[test]
+    fn short_description_is_first_sentence() {
+        assert_eq!(
+            short_description(&info("calculate base")),
+            "Converts a whole number from one base to another.",
+        );
+    }
+
+    #[test]
+    fn input_option_follows_syntax() {
+        let option = input_option(&info("calculate base")).unwrap().build();
+        assert_eq!(option.description, "<number> <from base> <to base>");
+        assert_eq!(option.required, Some(true));
+
+        let option = input_option(&info("calculate mode")).unwrap().build();
+        assert_eq!(option.required, Some(false));
+    }
+
+    #[test]
+    fn deprecated_commands_are_marked() {
+        assert_eq!(listed_alias(&info("notmath title")), "title ⚠️");
+        assert_eq!(listed_alias(&info("notmath case")), "case");
+    }
+
+    #[test]
+    fn deprecation_notice_once_per_day() {
+        let notices = RateLimiter::new(DEPRECATION_NOTICE_INTERVAL);
+        let now = Instant::now();
+        assert_eq!(notices.take_at(Id::new(1), now), None);
+        assert!(notices.take_at(Id::new(1), now + Duration::from_secs(60 * 60)).is_some());
+        assert_eq!(notices.take_at(Id::new(2), now + Duration::from_secs(60 * 60)), None);
+        assert_eq!(notices.take_at(Id::new(1), now + DEPRECATION_NOTICE_INTERVAL), None);
+    }
+
+    #[test]
+    fn rate_limiter_returns_time_left() {
+        let limiter = RateLimiter::new(Duration::from_secs(10));
+        let now = Instant::now();
+        limiter.take_at("key", now);
+        assert_eq!(limiter.take_at("key", now + Duration::from_secs(4)), Some(Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn rapid_runs_are_throttled() {
+        let cooldowns = RateLimiter::new(DEFAULT_COMMAND_COOLDOWN);
+        let user_id = Id::new(1);
+        let now = Instant::now();
+        let soon = now + Duration::from_millis(100);
+        assert_eq!(cooldowns.take_at(cooldown_key(user_id, &["calculate"]), now), None);
+        assert_eq!(
+            cooldowns.take_at(cooldown_key(user_id, &["calculate"]), soon),
+            Some(Duration::from_millis(900)),
+        );
+
+        // a different command, or another user, is not throttled
+        assert_eq!(cooldowns.take_at(cooldown_key(user_id, &["calculate", "base"]), soon), None);
+        assert_eq!(cooldowns.take_at(cooldown_key(Id::new(2), &["calculate"]), soon), None);
+
+        let mut remaining = cooldowns.remaining_at(soon);
+        remaining.sort();
+        assert_eq!(remaining, [
+            (cooldown_key(user_id, &["calculate"]), Duration::from_millis(900)),
+            (cooldown_key(user_id, &["calculate", "base"]), DEFAULT_COMMAND_COOLDOWN),
+            (cooldown_key(Id::new(2), &["calculate"]), DEFAULT_COMMAND_COOLDOWN),
+        ]);
+    }
+}
@@ -1,7 +1,21 @@
-use super::{commands::Context, database::Database, global::State};
-use std::{error::Error, sync::Arc, time::Instant};
-use tokio::sync::Mutex;
-use twilight_model::gateway::payload::incoming::MessageCreate;
+use super::{
+    commands::{CommandInfo, Context, Trigger},
+    custom_id::CustomId,
+    database::{server::ServerData, Database},
+    global::{InteractionHandler, State, RUN_SUBCOMMAND},
+    inline,
+};
+use std::{collections::HashMap, error::Error, future::Future, pin::Pin, sync::{atomic::Ordering, Arc}, time::{Duration, Instant}};
+use tokio::sync::{mpsc::error::SendError, Mutex};
+use twilight_model::{
+    application::interaction::{application_command::CommandOptionValue, InteractionData},
+    channel::{message::MessageFlags, ChannelType},
+    gateway::payload::incoming::{InteractionCreate, MessageCreate},
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{marker::UserMarker, Id},
+    user::User,
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
 
 /// Handles a message being created in some text channel.
 pub async fn message_create(
@@ -9,29 +23,53 @@ pub async fn message_create(
     state: Arc<State>,
     database: Arc<Mutex<Database>>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // never respond to bots
-    if msg.author.bot {
+    if ignores_author(Some(&msg.author)) {
         return Ok(());
     }
 
-    // if in guild, fetch guild's prefix
-    // in dm channels, there is no prefix
+    // skip messages that cannot be commands before doing anything expensive
+    if !state.could_be_command(msg.guild_id, &msg.content) {
+        state.message_stats.filtered.fetch_add(1, Ordering::Relaxed);
+        return Ok(());
+    }
+    state.message_stats.processed.fetch_add(1, Ordering::Relaxed);
+
     // NOTE: async closures are unstable
-    let prefix = match msg.guild_id {
+    let (server_prefix, inline_math, attribution_footer) = match msg.guild_id {
         Some(id) => {
             let mut db = database.lock().await;
-            Some(db.get_server(id).await.to_owned())
+            let server = db.get_server(id).await;
+            state.set_prefix_hint(id, &server.prefix, server.inline_math);
+            (Some(server.prefix.clone()), server.inline_math, server.attribution_footer)
         },
-        None => None,
+        None => (None, false, false),
     };
+    let channel_kind = state.cache.channel(msg.channel_id).map(|channel| channel.kind);
+    let prefix = command_prefix(server_prefix.as_deref(), channel_kind);
 
-    if prefix.is_none() || msg.content.starts_with(prefix.as_ref().unwrap()) {
-        let prefix_len = prefix.as_ref().map(|p| p.len()).unwrap_or(0);
-        let mut trimmed = msg.content[prefix_len..].split_whitespace().peekable();
+    if let Some(command) = strip_command_prefix(&msg.content, prefix.as_deref()) {
+        let mut trimmed = command.split_whitespace().peekable();
 
         let now = Instant::now();
+        let path = state.commands.find_path(&mut trimmed.clone());
         match state.commands.find_command(&mut trimmed) {
+            Some(_) if state.is_disabled(&path) => {
+                state.http.create_message(msg.channel_id)
+                    .content("**This command is temporarily disabled for maintenance.** Please try again later.")?
+                    .await?;
+                log::info!("Disabled command refused: {}", msg.content);
+                return Ok(());
+            },
             Some(cmd) => {
+                if let Some(remaining) = state.take_cooldown(msg.author.id, &path) {
+                    state.http.create_message(msg.channel_id)
+                        .reply(msg.id)
+                        .content(&slow_down(remaining))?
+                        .await?;
+                    log::info!("Command refused by cooldown: {}", msg.content);
+                    return Ok(());
+                }
+
                 let raw_input = trimmed.peek()
                     .map(|s| {
                         // trimmed is a view into msg.content, so we can find the start of the
@@ -40,17 +78,34 @@ pub async fn message_create(
                         &msg.content[byte..]
                     })
                     .unwrap_or_default();
-                let ctxt = Context { trigger: (&msg.0).into(), prefix: prefix.as_deref(), raw_input };
-                if let Err(discord_error) = cmd.execute(&state, &database, ctxt).await {
-                    discord_error.rich_fmt(state.http.create_message(msg.channel_id))?
-                        .await?;
+                let ctxt = Context {
+                    trigger: Trigger::from(&msg.0)
+                        .with_attribution_footer(attribution_footer && is_attributed(&state, &path)),
+                    prefix: prefix.as_deref(),
+                    raw_input,
                 };
+                let result = cmd.execute(&state, &database, ctxt).await;
+                state.record_command(&path, now.elapsed(), result.is_err());
+                match result {
+                    Ok(()) => {
+                        if let Some(notice) = deprecation_notice(&state, &cmd.info(), msg.author.id, prefix.as_deref()) {
+                            state.http.create_message(msg.channel_id)
+                                .content(&notice)?
+                                .await?;
+                        }
+                    },
+                    Err(discord_error) => {
+                        discord_error.rich_fmt(ctxt.trigger.unattributed().reply(&state.http))?
+                            .await?;
+                    },
+                }
 
                 log::info!(
                     "Command executed in {}ms: {}",
                     now.elapsed().as_millis(),
                     msg.content
                 );
+                return Ok(());
             }
             None => log::info!(
                 "Command not found ({}ms spent): {}",
@@ -60,5 +115,449 @@ pub async fn message_create(
         }
     }
 
+    // not a command; look for `{{expression}}` spans if the server opted in, which are throttled
+    // like commands, since each span can keep a thread busy until it times out
+    if inline_math
+        && !inline::find_spans(&msg.content).is_empty()
+        && state.take_cooldown(msg.author.id, &[inline::COOLDOWN_PATH]).is_none()
+    {
+        inline::reply(&state, &msg).await?;
+    }
+
     Ok(())
 }
+
+/// Returns the message telling a user to wait the given time before running a command again.
+fn slow_down(remaining: Duration) -> String {
+    format!(
+        "**Slow down!** You can run this command again in {:.1} seconds.",
+        remaining.as_secs_f64().max(0.1),
+    )
+}
+
+/// Returns the note telling a user that the command they ran is deprecated, if it is and they have
+/// not been told about a deprecation recently. See [`State::take_deprecation_notice`].
+fn deprecation_notice(
+    state: &State,
+    info: &CommandInfo,
+    user_id: Id<UserMarker>,
+    prefix: Option<&str>,
+) -> Option<String> {
+    let deprecated = info.deprecated?;
+    state.take_deprecation_notice(user_id).then(|| format!(
+        "_note: this command is deprecated. {}_",
+        deprecated.replace("{prefix}", prefix.unwrap_or("")),
+    ))
+}
+
+/// Returns true if replies to the command at the given path are attributed to their invoker when
+/// the server has the `attribution_footer` setting on. This applies to the calculation commands.
+fn is_attributed(state: &State, path: &[&str]) -> bool {
+    state.commands.category(path) == Some("Calculate")
+}
+
+/// Returns true if messages and interactions from the given author are ignored, which is the case
+/// for bots.
+fn ignores_author(author: Option<&User>) -> bool {
+    author.map_or(false, |author| author.bot)
+}
+
+/// Returns the prefix that messages must start with to run commands, given the prefix of the
+/// server the message was sent in, if any, and the type of its channel, if it is cached.
+///
+/// In servers, the server's prefix is used. In group DMs, the default prefix is required, since
+/// other people and bots are present. In 1:1 DMs, there is no prefix. The channel type is read from
+/// the cache, which is filled in by the gateway's channel events; if the channel is not cached, it
+/// is assumed to be a 1:1 DM.
+fn command_prefix(server_prefix: Option<&str>, channel_kind: Option<ChannelType>) -> Option<String> {
+    match (server_prefix, channel_kind) {
+        (Some(prefix), _) => Some(prefix.to_owned()),
+        (None, Some(ChannelType::GroupDm)) => Some(ServerData::default().prefix),
+        (None, _) => None,
+    }
+}
+
+/// Returns the content of the message after the given prefix, or [`None`] if the message does not
+/// start with it.
+fn strip_command_prefix<'a>(content: &'a str, prefix: Option<&str>) -> Option<&'a str> {
+    match prefix {
+        Some(prefix) => content.strip_prefix(prefix),
+        None => Some(content),
+    }
+}
+
+/// Handles an interaction with a message component, such as a button click, or a slash command.
+///
+/// The interaction is routed to the handler registered in [`State::interaction_handlers`] for the
+/// namespace of the component's [`CustomId`]. If the custom ID is malformed or its namespace is
+/// unknown, the user is told that the component has expired.
+pub async fn interaction_create(
+    interaction: InteractionCreate,
+    state: Arc<State>,
+    database: Arc<Mutex<Database>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if ignores_author(interaction.author()) {
+        return Ok(());
+    }
+
+    if matches!(interaction.data, Some(InteractionData::ApplicationCommand(_))) {
+        return application_command(interaction, state, database).await;
+    }
+
+    let raw_custom_id = match &interaction.data {
+        Some(InteractionData::MessageComponent(data)) => data.custom_id.clone(),
+        _ => return Ok(()),
+    };
+
+    match route(&state.interaction_handlers, &raw_custom_id) {
+        Some((handler, custom_id)) => handler(Arc::clone(&state), database, interaction, custom_id).await?,
+        None => {
+            log::info!("No interaction handler for custom ID: {}", raw_custom_id);
+            expired(&state, &interaction).await?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Returns the handler registered for the namespace of the given custom ID, along with the parsed
+/// custom ID. Returns [`None`] if the custom ID is malformed or its namespace is unknown.
+fn route(
+    handlers: &HashMap<&'static str, InteractionHandler>,
+    raw_custom_id: &str,
+) -> Option<(InteractionHandler, CustomId)> {
+    let custom_id = raw_custom_id.parse::<CustomId>().ok()?;
+    let handler = handlers.get(custom_id.namespace.as_str())?;
+    Some((*handler, custom_id))
+}
+
+/// Handles a slash command, registered with [`State::application_commands`].
+///
+/// The command at the path named by the slash command and its subcommands is run with the values
+/// of the options as its input, as if they were typed after the command's name. The interaction
+/// response is deferred first, so that the command's replies are sent as followups.
+async fn application_command(
+    interaction: InteractionCreate,
+    state: Arc<State>,
+    database: Arc<Mutex<Database>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Some(InteractionData::ApplicationCommand(data)) = &interaction.data else {
+        return Ok(());
+    };
+
+    // follow subcommands and subcommand groups down to the command they name; the run subcommand
+    // runs its parent
+    let mut path = vec![data.name.as_str()];
+    let mut options = &data.options;
+    while let Some((name, CommandOptionValue::SubCommand(inner) | CommandOptionValue::SubCommandGroup(inner))) =
+        options.first().map(|option| (option.name.as_str(), &option.value))
+    {
+        if name != RUN_SUBCOMMAND {
+            path.push(name);
+        }
+        options = inner;
+    }
+
+    let Some(cmd) = state.commands.find_command(&mut path.iter().copied().peekable()) else {
+        log::info!("Slash command not found: /{}", path.join(" "));
+        return Ok(());
+    };
+    if state.is_disabled(&path) {
+        state.http.interaction(state.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::ChannelMessageWithSource,
+                    data: Some(InteractionResponseDataBuilder::new()
+                        .content("**This command is temporarily disabled for maintenance.** Please try again later.")
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build()),
+                },
+            )
+            .await?;
+        log::info!("Disabled slash command refused: /{}", path.join(" "));
+        return Ok(());
+    }
+    if let Some(remaining) = interaction.author_id().and_then(|id| state.take_cooldown(id, &path)) {
+        state.http.interaction(state.application_id)
+            .create_response(
+                interaction.id,
+                &interaction.token,
+                &InteractionResponse {
+                    kind: InteractionResponseType::ChannelMessageWithSource,
+                    data: Some(InteractionResponseDataBuilder::new()
+                        .content(slow_down(remaining))
+                        .flags(MessageFlags::EPHEMERAL)
+                        .build()),
+                },
+            )
+            .await?;
+        log::info!("Slash command refused by cooldown: /{}", path.join(" "));
+        return Ok(());
+    }
+
+    state.http.interaction(state.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::DeferredChannelMessageWithSource,
+                data: None,
+            },
+        )
+        .await?;
+
+    let now = Instant::now();
+    let raw_input = options.iter()
+        .filter_map(|option| match &option.value {
+            CommandOptionValue::String(value) => Some(value.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let (prefix, attribution_footer) = match interaction.guild_id {
+        Some(id) => {
+            let mut db = database.lock().await;
+            let server = db.get_server(id).await;
+            (Some(server.prefix.clone()), server.attribution_footer)
+        },
+        None => (None, false),
+    };
+    let ctxt = Context {
+        trigger: Trigger::from(&interaction.0)
+            .with_attribution_footer(attribution_footer && is_attributed(&state, &path)),
+        prefix: prefix.as_deref(),
+        raw_input: raw_input.trim(),
+    };
+    let result = cmd.execute(&state, &database, ctxt).await;
+    state.record_command(&path, now.elapsed(), result.is_err());
+    match result {
+        Ok(()) => {
+            let notice = interaction.author_id()
+                .and_then(|id| deprecation_notice(&state, &cmd.info(), id, prefix.as_deref()));
+            if let Some(notice) = notice {
+                state.http.interaction(state.application_id)
+                    .create_followup(&interaction.token)
+                    .content(&notice)?
+                    .flags(MessageFlags::EPHEMERAL)
+                    .await?;
+            }
+        },
+        Err(discord_error) => {
+            discord_error.rich_fmt(ctxt.trigger.unattributed().reply(&state.http))?
+                .await?;
+        },
+    }
+
+    log::info!(
+        "Slash command executed in {}ms: /{} {}",
+        now.elapsed().as_millis(),
+        path.join(" "),
+        raw_input,
+    );
+    Ok(())
+}
+
+/// Responds to the interaction with an ephemeral message stating that the component has expired.
+pub async fn expired(
+    state: &State,
+    interaction: &InteractionCreate,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    state.http.interaction(state.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(InteractionResponseDataBuilder::new()
+                    .content("**This button has expired.**")
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build()),
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Responds to the interaction with an ephemeral message stating that only the user who ran the
+/// command can interact with the component.
+pub async fn unauthorized(
+    state: &State,
+    interaction: &InteractionCreate,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    state.http.interaction(state.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::ChannelMessageWithSource,
+                data: Some(InteractionResponseDataBuilder::new()
+                    .content("**Only the user who ran this command can respond to it.**")
+                    .flags(MessageFlags::EPHEMERAL)
+                    .build()),
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Interaction handler for the `pager` namespace. The interaction is forwarded to the task
+/// managing the paged message it was triggered on.
+pub fn pager(
+    state: Arc<State>,
+    database: Arc<Mutex<Database>>,
+    interaction: InteractionCreate,
+    _: CustomId,
+) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>> {
+    Box::pin(async move {
+        let sender = match (&interaction.channel, &interaction.message) {
+            (Some(channel), Some(message)) => database.lock()
+                .await
+                .get_paged_message(channel.id, message.id)
+                .cloned(),
+            _ => None,
+        };
+
+        match sender {
+            Some(sender) => {
+                // the task may have ended between the lookup and now
+                if let Err(SendError(interaction)) = sender.send(interaction) {
+                    expired(&state, &interaction).await?;
+                }
+            },
+            None => expired(&state, &interaction).await?,
+        }
+
+        Ok(())
+    })
+}
+
+/// Returns true if the user who clicked a confirmation dialog's button is the user allowed to
+/// respond, whose ID is stored in the payload of the button's custom ID.
+fn is_authorized(author_id: Option<Id<UserMarker>>, authorized_user: Option<&str>) -> bool {
+    author_id.zip(authorized_user)
+        .map_or(false, |(author_id, authorized_user)| author_id.to_string() == authorized_user)
+}
+
+/// Interaction handler for the `confirm` namespace. The click is forwarded to the confirmation
+/// dialog it was triggered on, if it was made by the user allowed to respond (stored in the
+/// payload of the custom ID). Anyone else is told that they cannot respond to the dialog.
+pub fn confirm(
+    state: Arc<State>,
+    database: Arc<Mutex<Database>>,
+    interaction: InteractionCreate,
+    custom_id: CustomId,
+) -> Pin<Box<dyn Future<Output = Result<(), Box<dyn Error + Send + Sync>>> + Send>> {
+    Box::pin(async move {
+        if !is_authorized(interaction.author_id(), custom_id.payload.as_deref()) {
+            return unauthorized(&state, &interaction).await;
+        }
+
+        let sender = match (&interaction.channel, &interaction.message) {
+            (Some(channel), Some(message)) => database.lock()
+                .await
+                .take_confirmation(channel.id, message.id),
+            _ => None,
+        };
+
+        match sender {
+            Some(sender) => {
+                // the dialog may have timed out between the lookup and now
+                if let Err(interaction) = sender.send(interaction) {
+                    expired(&state, &interaction).await?;
+                }
+            },
+            None => expired(&state, &interaction).await?,
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(bot: bool) -> User {
+        serde_json::from_value(serde_json::json!({
+            "id": "1",
+            "username": "someone",
+            "discriminator": "0",
+            "avatar": null,
+            "bot": bot,
+        })).unwrap()
+    }
+
+    #[test]
+    fn bots_are_ignored() {
+        assert!(ignores_author(Some(&user(true))));
+        assert!(!ignores_author(Some(&user(false))));
+        assert!(!ignores_author(None));
+    }
+
+    #[test]
+    fn guild_uses_server_prefix() {
+        let prefix = command_prefix(Some("!"), Some(ChannelType::GuildText));
+        assert_eq!(prefix.as_deref(), Some("!"));
+        assert_eq!(strip_command_prefix("!calc 1+1", prefix.as_deref()), Some("calc 1+1"));
+        assert_eq!(strip_command_prefix("calc 1+1", prefix.as_deref()), None);
+    }
+
+    #[test]
+    fn group_dm_requires_default_prefix() {
+        let prefix = command_prefix(None, Some(ChannelType::GroupDm));
+        assert_eq!(prefix, Some(ServerData::default().prefix));
+
+        let content = format!("{}calc 1+1", ServerData::default().prefix);
+        assert_eq!(strip_command_prefix(&content, prefix.as_deref()), Some("calc 1+1"));
+        assert_eq!(strip_command_prefix("calc 1+1", prefix.as_deref()), None);
+    }
+
+    #[test]
+    fn dm_has_no_prefix() {
+        for channel_kind in [Some(ChannelType::Private), None] {
+            let prefix = command_prefix(None, channel_kind);
+            assert_eq!(prefix, None);
+            assert_eq!(strip_command_prefix("calc 1+1", prefix.as_deref()), Some("calc 1+1"));
+        }
+    }
+
+    #[test]
+    fn confirm_clicks_from_other_users_are_rejected() {
+        assert!(is_authorized(Some(Id::new(42)), Some("42")));
+        assert!(!is_authorized(Some(Id::new(7)), Some("42")));
+        assert!(!is_authorized(None, Some("42")));
+        assert!(!is_authorized(Some(Id::new(42)), None));
+    }
+
+    /// Returns the handlers of the `confirm` and `pager` namespaces.
+    fn handlers() -> HashMap<&'static str, InteractionHandler> {
+        HashMap::from([
+            ("confirm", confirm as InteractionHandler),
+            ("pager", pager as InteractionHandler),
+        ])
+    }
+
+    #[test]
+    fn known_namespace_is_routed_to_its_handler() {
+        let (handler, custom_id) = route(&handlers(), "pager:next").unwrap();
+        assert_eq!(handler as usize, pager as InteractionHandler as usize);
+        assert_eq!(custom_id, CustomId::new("pager", "next"));
+
+        let (handler, custom_id) = route(&handlers(), "confirm:yes:42").unwrap();
+        assert_eq!(handler as usize, confirm as InteractionHandler as usize);
+        assert_eq!(custom_id.payload.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn unknown_namespace_expires() {
+        assert!(route(&handlers(), "timer:snooze:abcd").is_none());
+    }
+
+    #[test]
+    fn malformed_custom_id_expires() {
+        assert!(route(&handlers(), "next").is_none());
+        assert!(route(&handlers(), "").is_none());
+    }
+}
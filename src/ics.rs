@@ -0,0 +1,175 @@
+//! A minimal builder for iCalendar files, as specified by
+//! [RFC 5545](https://datatracker.ietf.org/doc/html/rfc5545). Only what is needed to export
+//! reminders is supported.
+
+use chrono::{DateTime, Utc};
+use std::time::SystemTime;
+
+/// The maximum length of a content line in octets, not including the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// The line break used between content lines.
+const CRLF: &str = "\r\n";
+
+/// Escapes text for use in a `TEXT` property value, such as `SUMMARY`.
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => (),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Folds a content line so that no line is longer than [`MAX_LINE_OCTETS`] octets. Each
+/// continuation line starts with a space, which counts towards its length. Lines are only split
+/// between characters, never inside a multi-byte character.
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str(CRLF);
+            folded.push(' ');
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded
+}
+
+/// Formats a time as a UTC `DATE-TIME` value, like `20250101T120000Z`.
+pub fn format_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Creates a globally unique identifier for an event, derived from the ID of the user who owns it
+/// and an ID that is unique among that user's events. The same event always gets the same UID, so
+/// calendar apps update events that are exported again instead of duplicating them.
+pub fn uid(user_id: impl std::fmt::Display, id: &str) -> String {
+    format!("{}-{}@calcbot", user_id, id)
+}
+
+/// An event in a [`Calendar`].
+#[derive(Clone, Debug)]
+pub struct Event {
+    /// The unique identifier of the event. See [`uid`].
+    pub uid: String,
+
+    /// The time the event starts.
+    pub start: SystemTime,
+
+    /// A short summary of the event, shown as its title.
+    pub summary: String,
+
+    /// A longer description of the event, if any.
+    pub description: Option<String>,
+}
+
+/// An iCalendar file containing a list of events.
+#[derive(Clone, Debug, Default)]
+pub struct Calendar {
+    /// The events in the calendar.
+    pub events: Vec<Event>,
+}
+
+impl Calendar {
+    /// Builds the iCalendar file. The given time is used as the `DTSTAMP` of every event, which
+    /// should be the time the file is created.
+    pub fn build(&self, stamp: SystemTime) -> String {
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_owned(),
+            "VERSION:2.0".to_owned(),
+            "PRODID:-//CalcBot//Reminders//EN".to_owned(),
+            "CALSCALE:GREGORIAN".to_owned(),
+        ];
+
+        for event in &self.events {
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!("UID:{}", event.uid));
+            lines.push(format!("DTSTAMP:{}", format_time(stamp)));
+            lines.push(format!("DTSTART:{}", format_time(event.start)));
+            lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+            if let Some(description) = &event.description {
+                lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+            }
+            lines.push("END:VEVENT".to_owned());
+        }
+
+        lines.push("END:VCALENDAR".to_owned());
+
+        // every content line, including the last, ends with a line break
+        lines.iter()
+            .map(|line| fold_line(line) + CRLF)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape_text("a;b,c\\d\r\ne"), r"a\;b\,c\\d\ne");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let line = "x".repeat(MAX_LINE_OCTETS);
+        assert_eq!(fold_line(&line), line);
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let folded = fold_line(&"x".repeat(150));
+        let lines = folded.split(CRLF).collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), MAX_LINE_OCTETS);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(MAX_LINE_OCTETS - 1)));
+        assert_eq!(lines[2], " x");
+    }
+
+    #[test]
+    fn multi_byte_characters_are_not_split() {
+        let folded = fold_line(&"é".repeat(40));
+        for line in folded.split(CRLF) {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        assert_eq!(folded.replace("\r\n ", ""), "é".repeat(40));
+    }
+
+    #[test]
+    fn build_calendar() {
+        let calendar = Calendar {
+            events: vec![Event {
+                uid: uid(1, "abc"),
+                start: UNIX_EPOCH + Duration::from_secs(1_735_732_800),
+                summary: "drink water, now".to_owned(),
+                description: None,
+            }],
+        };
+        assert_eq!(calendar.build(UNIX_EPOCH), [
+            "BEGIN:VCALENDAR",
+            "VERSION:2.0",
+            "PRODID:-//CalcBot//Reminders//EN",
+            "CALSCALE:GREGORIAN",
+            "BEGIN:VEVENT",
+            "UID:1-abc@calcbot",
+            "DTSTAMP:19700101T000000Z",
+            "DTSTART:20250101T120000Z",
+            "SUMMARY:drink water\\, now",
+            "END:VEVENT",
+            "END:VCALENDAR",
+            "",
+        ].join(CRLF));
+    }
+}
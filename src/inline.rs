@@ -0,0 +1,136 @@
+use cas_compute::numerical::{ctxt::Ctxt, eval::eval_stmts};
+use cas_parser::parser::Parser;
+use crate::{global::State, util::run_with_timeout};
+use std::{error::Error, sync::Arc, time::Duration};
+use twilight_model::channel::message::Message;
+
+/// The maximum number of `{{expression}}` spans evaluated in a single message.
+const MAX_SPANS: usize = 3;
+
+/// The maximum length of the expression inside a span, in bytes.
+const MAX_SPAN_LENGTH: usize = 200;
+
+/// How long a single span is allowed to evaluate for. This is stricter than the calculate command,
+/// since inline evaluation is triggered by ordinary conversation.
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Finds the `{{expression}}` spans in the given message content, returning the trimmed
+/// expressions inside them.
+///
+/// Spans inside inline code (`` `...` ``) or code blocks (```` ```...``` ````) are ignored, as are
+/// empty spans and spans longer than [`MAX_SPAN_LENGTH`]. At most [`MAX_SPANS`] spans are
+/// returned.
+pub fn find_spans(content: &str) -> Vec<&str> {
+    let mut spans = Vec::new();
+    let mut rest = content;
+
+    while spans.len() < MAX_SPANS {
+        let Some(start) = rest.find(['`', '{']) else {
+            break;
+        };
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("```") {
+            // an unterminated code block is rendered literally by Discord, so only skip the fence
+            rest = after.find("```").map_or(after, |end| &after[end + 3..]);
+        } else if let Some(after) = rest.strip_prefix('`') {
+            rest = after.find('`').map_or(after, |end| &after[end + 1..]);
+        } else if let Some(after) = rest.strip_prefix("{{") {
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            let expr = after[..end].trim();
+            if !expr.is_empty() && expr.len() <= MAX_SPAN_LENGTH {
+                spans.push(expr);
+            }
+            rest = &after[end + 2..];
+        } else {
+            // lone `{`
+            rest = &rest[1..];
+        }
+    }
+
+    spans
+}
+
+/// The cooldown path of inline evaluation, which shares the per-user cooldown with commands. See
+/// [`State::take_cooldown`].
+pub const COOLDOWN_PATH: &str = "inline";
+
+/// Evaluates the given expression in a fresh context, returning [`None`] if it fails to parse,
+/// fails to evaluate, or takes longer than the given limit.
+async fn evaluate(expr: &str, limit: Duration) -> Option<String> {
+    let expr = expr.to_owned();
+    run_with_timeout(limit, move |_| {
+        let stmts = Parser::new(&expr).try_parse_full_many().ok()?;
+        eval_stmts(&stmts, &mut Ctxt::default())
+            .ok()
+            .map(|ans| ans.to_string())
+    }).await.ok()?
+}
+
+/// Evaluates the `{{expression}}` spans in the given message and replies with the results, one
+/// `expr = result` line per span.
+///
+/// Spans that fail to evaluate are silently skipped; if none of them succeed, no reply is sent.
+/// The caller is expected to check the user's cooldown first, using [`COOLDOWN_PATH`].
+pub async fn reply(state: &Arc<State>, msg: &Message) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut lines = Vec::new();
+    for expr in find_spans(&msg.content) {
+        if let Some(result) = evaluate(expr, TIMEOUT).await {
+            lines.push(format!("`{} = {}`", expr, result));
+        }
+    }
+
+    if !lines.is_empty() {
+        state.http.create_message(msg.channel_id)
+            .reply(msg.id)
+            .content(&lines.join("\n"))?
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_multiple_spans() {
+        assert_eq!(find_spans("a {{1 + 1}} b {{ 2^10 }} c"), ["1 + 1", "2^10"]);
+        assert_eq!(find_spans("{ {1} } {{ }} {{unterminated"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn spans_in_code_are_ignored() {
+        assert_eq!(find_spans("`{{1}}` {{2}}"), ["2"]);
+        assert_eq!(find_spans("```\n{{1}}\n``` {{2}}"), ["2"]);
+        // an unterminated fence is shown literally, so the span after it counts
+        assert_eq!(find_spans("``` {{3}}"), ["3"]);
+    }
+
+    #[test]
+    fn at_most_three_spans() {
+        assert_eq!(find_spans("{{1}} {{2}} {{3}} {{4}}"), ["1", "2", "3"]);
+    }
+
+    #[test]
+    fn long_spans_are_skipped() {
+        let long = "1".repeat(MAX_SPAN_LENGTH + 1);
+        let max = "1".repeat(MAX_SPAN_LENGTH);
+        let content = format!("{{{{{}}}}} {{{{{}}}}}", long, max);
+        assert_eq!(find_spans(&content), [max.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn evaluate_spans() {
+        assert_eq!(evaluate("2^10", TIMEOUT).await, Some("1024".to_owned()));
+        assert_eq!(evaluate("1 +", TIMEOUT).await, None);
+    }
+
+    #[tokio::test]
+    async fn slow_spans_are_abandoned() {
+        assert_eq!(evaluate("2^100000", Duration::ZERO).await, None);
+    }
+}
@@ -1,8 +1,12 @@
 pub mod commands;
+pub mod custom_id;
 pub mod database;
 pub mod error;
 pub mod global;
 pub mod handler;
+pub mod ics;
+pub mod inline;
+pub mod tasks;
 pub mod timer;
 pub mod util;
 
@@ -10,9 +14,13 @@ use database::Database;
 use dotenv::dotenv;
 use global::State;
 use simple_logger::SimpleLogger;
-use std::{env, error::Error, sync::Arc};
-use tokio::sync::Mutex;
-use twilight_gateway::{Event, Intents, Shard, ShardId};
+use std::{env, error::Error, sync::Arc, time::Duration};
+use tasks::TaskCategory;
+use tokio::{signal, sync::Mutex, task::{JoinError, JoinSet}};
+use twilight_gateway::{CloseFrame, Event, Intents, Shard, ShardId};
+
+/// How long to wait for events that are still being handled when shutting down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
@@ -35,10 +43,31 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut shard = Shard::new(ShardId::ONE, token.clone(), intents);
 
     let state = Arc::new(State::new(token).await);
-    let database = Arc::new(Mutex::new(Database::new()));
+    state.http.interaction(state.application_id)
+        .set_global_commands(&state.application_commands())
+        .await?;
+    let database = Database::new();
+    if let Err(err) = database.migrate().await {
+        // reads of missing columns fall back to defaults, so CalcBot can still run
+        log::error!("could not migrate the database: {}", err);
+    }
+    let database = Arc::new(Mutex::new(database));
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    let mut handlers = JoinSet::new();
 
     loop {
-        let event = match shard.next_event().await {
+        let event = tokio::select! {
+            _ = &mut shutdown => break,
+            // reap finished handlers so that the set does not grow forever
+            Some(result) = handlers.join_next(), if !handlers.is_empty() => {
+                log_handler_result(result);
+                continue;
+            },
+            event = shard.next_event() => event,
+        };
+        let event = match event {
             Ok(event) => event,
             Err(source) => {
                 if source.is_fatal() {
@@ -49,17 +78,84 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
             }
         };
         state.cache.update(&event);
+        if matches!(event, Event::GatewayHeartbeatAck) {
+            *state.gateway_latency.lock().unwrap() = shard.latency().average();
+        }
 
-        tokio::spawn(handle_event(
+        handlers.spawn(handle_event(
             event,
             Arc::clone(&state),
             Arc::clone(&database),
         ));
     }
 
+    log::info!("Shutting down");
+    if let Err(err) = shard.close(CloseFrame::NORMAL).await {
+        log::warn!("could not close the gateway connection: {}", err);
+    }
+
+    // let the events already received finish, so that their changes are saved
+    let abandoned = finish_handlers(&mut handlers, SHUTDOWN_TIMEOUT).await;
+    if abandoned > 0 {
+        log::warn!("{} events were still being handled after {:?}; abandoned them", abandoned, SHUTDOWN_TIMEOUT);
+    }
+
+    // timers are saved whenever they change, so their tasks can simply be dropped
+    state.tasks.abort_category(TaskCategory::Pager);
+    state.tasks.abort_category(TaskCategory::Browser);
+    database.lock().await.disconnect().await?;
+
     Ok(())
 }
 
+/// Completes when the process is asked to shut down, with Ctrl+C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("should be able to listen for SIGTERM");
+        tokio::select! {
+            _ = signal::ctrl_c() => {},
+            _ = terminate.recv() => {},
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+    }
+}
+
+/// Waits for the given event handlers to finish, logging their errors. Handlers still running after
+/// the given timeout are aborted, and the number of them is returned.
+async fn finish_handlers(
+    handlers: &mut JoinSet<Result<(), Box<dyn Error + Send + Sync>>>,
+    timeout: Duration,
+) -> usize {
+    let finished = tokio::time::timeout(timeout, async {
+        while let Some(result) = handlers.join_next().await {
+            log_handler_result(result);
+        }
+    }).await;
+    if finished.is_ok() {
+        return 0;
+    }
+
+    let abandoned = handlers.len();
+    handlers.shutdown().await;
+    abandoned
+}
+
+/// Logs the error of an event handler that failed or panicked.
+fn log_handler_result(result: Result<Result<(), Box<dyn Error + Send + Sync>>, JoinError>) {
+    match result {
+        Ok(Ok(())) => {},
+        Ok(Err(err)) => log::error!("event handler failed: {}", err),
+        Err(err) if err.is_panic() => log::error!("event handler panicked: {}", err),
+        Err(err) => log::warn!("event handler was cancelled: {}", err),
+    }
+}
+
 /// Handles events relevant to the bot, delegating each event to the appropriate handler.
 async fn handle_event(
     event: Event,
@@ -68,10 +164,14 @@ async fn handle_event(
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     match event {
         Event::MessageCreate(msg) => handler::message_create(*msg, state, database).await?,
+        // removing the sender of a paged message ends its task
         Event::MessageDelete(msg) => {
-            if database.lock().await.remove_paged_message(msg.channel_id, msg.id) {
-                log::info!("paged message task ended: message deleted");
-                // NOTE: the other log message will also appear as the task is dropped
+            database.lock().await.remove_paged_message(msg.channel_id, msg.id);
+        },
+        Event::MessageDeleteBulk(msgs) => {
+            let mut database = database.lock().await;
+            for id in msgs.ids {
+                database.remove_paged_message(msgs.channel_id, id);
             }
         },
         Event::Ready(ready) => log::info!(
@@ -79,18 +179,42 @@ async fn handle_event(
             ready.shard.unwrap_or(ShardId::new(0, 1))
         ),
         Event::InteractionCreate(interaction) => {
-            if let (Some(channel), Some(message)) = (
-                &interaction.channel,
-                &interaction.message,
-            ) {
-                database.lock()
-                    .await
-                    .get_paged_message(channel.id, message.id)
-                    .map(|sender| sender.send(*interaction));
-            }
-        }
+            handler::interaction_create(*interaction, state, database).await?
+        },
         _ => {}
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn finished_handlers_are_awaited() {
+        let mut handlers = JoinSet::new();
+        handlers.spawn(async { Ok(()) });
+        handlers.spawn(async { Err("**Oops!**".into()) });
+        handlers.spawn(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(())
+        });
+
+        assert_eq!(finish_handlers(&mut handlers, Duration::from_secs(10)).await, 0);
+        assert!(handlers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn slow_handlers_are_abandoned() {
+        let mut handlers = JoinSet::new();
+        handlers.spawn(async { Ok(()) });
+        handlers.spawn(async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(())
+        });
+
+        assert_eq!(finish_handlers(&mut handlers, Duration::from_millis(50)).await, 1);
+        assert!(handlers.is_empty());
+    }
+}
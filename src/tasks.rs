@@ -0,0 +1,194 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    future::Future,
+    sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::task::{AbortHandle, JoinHandle};
+
+/// A category of long-lived task tracked by the [`TaskTracker`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TaskCategory {
+    /// A task that sends a reminder message when a timer ends.
+    Timer,
+
+    /// A task that listens for interactions on a paged message.
+    Pager,
+
+    /// A task that disables the menus of a command browser once it goes unused.
+    Browser,
+}
+
+impl Display for TaskCategory {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TaskCategory::Timer => write!(f, "timers"),
+            TaskCategory::Pager => write!(f, "pagers"),
+            TaskCategory::Browser => write!(f, "browsers"),
+        }
+    }
+}
+
+/// A task being tracked by the [`TaskTracker`].
+#[derive(Debug)]
+struct TrackedTask {
+    /// The category of the task.
+    category: TaskCategory,
+
+    /// The instant the task was spawned.
+    created_at: Instant,
+
+    /// A handle that can be used to abort the task.
+    abort_handle: AbortHandle,
+}
+
+/// Statistics about the tracked tasks in a single category.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CategoryStats {
+    /// The number of tasks that are currently running.
+    pub count: usize,
+
+    /// How long the oldest running task has been alive for.
+    pub oldest: Option<Duration>,
+}
+
+/// Removes a task from the tracker when dropped. This is held by the task itself, so that the
+/// task is removed whether it completes or is aborted.
+struct Untrack {
+    /// The ID of the task.
+    id: u64,
+
+    /// The tracked tasks.
+    tasks: Arc<Mutex<HashMap<u64, TrackedTask>>>,
+
+    /// Set once the task has untracked itself, so that a task that finishes before
+    /// [`TaskTracker::spawn`] inserts it is never inserted.
+    done: Arc<AtomicBool>,
+}
+
+impl Drop for Untrack {
+    fn drop(&mut self) {
+        let mut tasks = self.tasks.lock().unwrap();
+        self.done.store(true, Ordering::Relaxed);
+        tasks.remove(&self.id);
+    }
+}
+
+/// Keeps track of the long-lived tasks spawned by the bot, such as timer and pager tasks, so that
+/// leaked tasks are visible and can be aborted together.
+///
+/// Short-lived tasks, like the task spawned to handle each gateway event, are not tracked.
+#[derive(Debug, Default)]
+pub struct TaskTracker {
+    /// The ID to assign to the next spawned task.
+    next_id: AtomicU64,
+
+    /// The tasks that are currently running, keyed by ID.
+    tasks: Arc<Mutex<HashMap<u64, TrackedTask>>>,
+}
+
+impl TaskTracker {
+    /// Spawns a new tracked task in the given category. The task is untracked once it completes
+    /// or is aborted.
+    pub fn spawn<F>(&self, category: TaskCategory, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let done = Arc::new(AtomicBool::new(false));
+        let untrack = Untrack { id, tasks: Arc::clone(&self.tasks), done: Arc::clone(&done) };
+
+        // the lock must not be held while spawning: if the runtime is shutting down, the future is
+        // dropped right away, and the untrack would lock it again on this thread
+        let handle = tokio::spawn(async move {
+            let _untrack = untrack;
+            future.await
+        });
+
+        // the flag is set with the lock held, so a task that has already untracked itself is seen
+        let mut tasks = self.tasks.lock().unwrap();
+        if !done.load(Ordering::Relaxed) {
+            tasks.insert(id, TrackedTask {
+                category,
+                created_at: Instant::now(),
+                abort_handle: handle.abort_handle(),
+            });
+        }
+        drop(tasks);
+
+        handle
+    }
+
+    /// Returns statistics about the running tasks in each category.
+    pub fn stats(&self) -> BTreeMap<TaskCategory, CategoryStats> {
+        let mut stats = BTreeMap::<_, CategoryStats>::new();
+        for task in self.tasks.lock().unwrap().values() {
+            let entry = stats.entry(task.category).or_default();
+            let age = task.created_at.elapsed();
+            entry.count += 1;
+            entry.oldest = Some(entry.oldest.map_or(age, |oldest| oldest.max(age)));
+        }
+        stats
+    }
+
+    /// Aborts every running task in the given category, returning the number of aborted tasks.
+    pub fn abort_category(&self, category: TaskCategory) -> usize {
+        let mut tasks = self.tasks.lock().unwrap();
+        let ids = tasks.iter()
+            .filter(|(_, task)| task.category == category)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in &ids {
+            if let Some(task) = tasks.remove(id) {
+                task.abort_handle.abort();
+            }
+        }
+
+        ids.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::pending;
+
+    /// Returns the number of tracked tasks across all categories.
+    fn count(tracker: &TaskTracker) -> usize {
+        tracker.stats().values().map(|stats| stats.count).sum()
+    }
+
+    #[tokio::test]
+    async fn finished_tasks_are_untracked() {
+        let tracker = TaskTracker::default();
+        let handle = tracker.spawn(TaskCategory::Timer, async {});
+        handle.await.unwrap();
+        assert_eq!(count(&tracker), 0);
+    }
+
+    #[tokio::test]
+    async fn aborted_tasks_are_untracked() {
+        let tracker = TaskTracker::default();
+        let timer = tracker.spawn(TaskCategory::Timer, pending::<()>());
+        let pagers = [
+            tracker.spawn(TaskCategory::Pager, pending::<()>()),
+            tracker.spawn(TaskCategory::Pager, pending::<()>()),
+        ];
+        assert_eq!(tracker.stats()[&TaskCategory::Pager].count, 2);
+        assert_eq!(count(&tracker), 3);
+
+        assert_eq!(tracker.abort_category(TaskCategory::Pager), 2);
+        for pager in pagers {
+            assert!(pager.await.unwrap_err().is_cancelled());
+        }
+        assert_eq!(count(&tracker), 1);
+
+        // aborting through the join handle untracks the task too
+        timer.abort();
+        assert!(timer.await.unwrap_err().is_cancelled());
+        assert_eq!(count(&tracker), 0);
+    }
+}
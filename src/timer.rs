@@ -1,9 +1,64 @@
 use serde::{Deserialize, Serialize};
-use std::{error::Error, sync::Arc, time::{Duration, SystemTime}};
+use std::{error::Error, sync::Arc, time::{Duration, SystemTime, UNIX_EPOCH}};
 use tokio::{task::JoinHandle, time::Sleep};
 use twilight_model::id::{marker::{ChannelMarker, UserMarker}, Id};
 
-use crate::global::State;
+use crate::{global::State, tasks::TaskCategory};
+
+/// The maximum number of lines of a reminder message shown when the reminder fires.
+const MAX_QUOTED_LINES: usize = 10;
+
+/// Renders the reminder message inside a block quote, preserving the user's own formatting.
+///
+/// Block quotes cannot be nested, so a line that is already a block quote has its `>` escaped.
+/// Messages longer than [`MAX_QUOTED_LINES`] lines are truncated.
+fn quote(message: &str) -> String {
+    let lines = message.lines().collect::<Vec<_>>();
+    let mut quoted = lines.iter()
+        .take(MAX_QUOTED_LINES)
+        .map(|line| match line.strip_prefix('>') {
+            Some(rest) => format!("> \\>{}", rest),
+            None => format!("> {}", line),
+        })
+        .collect::<Vec<_>>();
+
+    if lines.len() > MAX_QUOTED_LINES {
+        quoted.push("> …".to_owned());
+    }
+
+    quoted.join("\n")
+}
+
+/// Sends the reminder message to the channel the reminder was set in. If the bot can no longer post
+/// there, such as when the channel was deleted or the bot lost its permissions, the reminder is
+/// sent to the user by DM instead.
+async fn send_reminder(
+    state: &State,
+    user_id: Id<UserMarker>,
+    channel_id: Id<ChannelMarker>,
+    content: &str,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let Err(err) = state.http.create_message(channel_id).content(content)?.await else {
+        return Ok(());
+    };
+    log::warn!("could not send reminder in channel {}, sending it to user {} by DM instead: {}", channel_id, user_id, err);
+
+    let content = format!("{}\n_This reminder could not be sent in <#{}>._", content, channel_id);
+    let result = async {
+        let dm = state.http.create_private_channel(user_id).await?.model().await?;
+        state.http.create_message(dm.id).content(&content)?.await?;
+        Ok::<_, Box<dyn Error + Send + Sync>>(())
+    }.await;
+    if let Err(err) = &result {
+        log::error!("could not send reminder to user {} by DM: {}", user_id, err);
+    }
+    result
+}
+
+/// The creation time given to timers saved before creation times were recorded.
+fn unknown_created_at() -> SystemTime {
+    UNIX_EPOCH
+}
 
 /// State of a timer.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -35,8 +90,9 @@ pub struct Timer {
     /// The ID of the channel where the message setting the timer was sent.
     pub channel_id: Id<ChannelMarker>,
 
-    /// The instant in time the timer was set. TODO: unneeded?
-    // pub start_time: Instant,
+    /// The time the timer was set. Timers saved before this was recorded have [`UNIX_EPOCH`].
+    #[serde(default = "unknown_created_at")]
+    pub created_at: SystemTime,
 
     /// State of the timer.
     pub state: TimerState,
@@ -63,6 +119,7 @@ impl Clone for Timer {
             id: self.id.clone(),
             user_id: self.user_id,
             channel_id: self.channel_id,
+            created_at: self.created_at,
             state: self.state.clone(),
             message: self.message.clone(),
             task: None,
@@ -85,12 +142,58 @@ impl Timer {
             id: random_string::generate(4, random_string::charsets::ALPHA_LOWER),
             user_id,
             channel_id,
+            created_at: SystemTime::now(),
             state: TimerState::Running { end_time },
             message,
             task: None,
         }.with_task(state)
     }
 
+    /// Moves the timer to end at the given time, replacing its task so that the reminder is sent at
+    /// the new time. The timer is running afterwards.
+    pub fn with_end_time(mut self, state: &Arc<State>, end_time: SystemTime) -> Self {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        self.state = TimerState::Running { end_time };
+        self.with_task(state)
+    }
+
+    /// Replaces the message of the timer. If the timer is running, its task is replaced so that
+    /// the new message is sent.
+    pub fn with_message(mut self, state: &Arc<State>, message: String) -> Self {
+        self.message = message;
+        match self.state {
+            TimerState::Running { end_time } => self.with_end_time(state, end_time),
+            TimerState::Paused { .. } => self,
+        }
+    }
+
+    /// Moves the task of the given timer into this timer, if this timer has no task of its own.
+    ///
+    /// Cloned timers have no task, so this keeps the reminder scheduled when a clone of a timer
+    /// replaces the original.
+    pub fn adopt_task(&mut self, other: &mut Timer) {
+        if self.task.is_none() {
+            self.task = other.task.take();
+        }
+    }
+
+    /// Returns true if the timer is running, rather than paused.
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, TimerState::Running { .. })
+    }
+
+    /// Returns true if the reminder has yet to be sent, because the timer is paused, or running
+    /// and has not ended. Timers whose reminder was sent are kept so that they can be snoozed, but
+    /// do not count towards [`State::max_timers`].
+    pub fn is_pending(&self) -> bool {
+        match self.state {
+            TimerState::Running { end_time } => end_time > SystemTime::now(),
+            TimerState::Paused { .. } => true,
+        }
+    }
+
     /// Creates a [`Sleep`] future that will complete when the timer ends.
     pub fn sleep(&self) -> Sleep {
         match &self.state {
@@ -107,24 +210,91 @@ impl Timer {
     /// Create the timer's task that will send a reminder message to the given channel when the
     /// timer ends.
     fn with_task(mut self, state: &Arc<State>) -> Self {
+        let tasks = &state.tasks;
         let state = Arc::clone(state);
         let user_id = self.user_id;
         let channel_id = self.channel_id;
         let message = self.message.clone();
         let future = self.sleep();
 
-        self.task = Some(tokio::spawn(async move {
+        self.task = Some(tasks.spawn(TaskCategory::Timer, async move {
             future.await;
 
             let msg = match message.len() {
-                0 => format!("<@{}>'s reminder: _no message provided_", user_id),
-                _ => format!("<@{}>'s reminder: **{}**", user_id, message),
+                0 => format!("<@{}>'s reminder:\n> _no message provided_", user_id),
+                _ => format!("<@{}>'s reminder:\n{}", user_id, quote(&message)),
             };
-            state.http.create_message(channel_id)
-                .content(&msg)?
-                .await?;
-            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+            send_reminder(&state, user_id, channel_id, &msg).await
         }));
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a timer with the given state and no task.
+    fn timer(state: TimerState) -> Timer {
+        Timer {
+            id: "abcd".to_owned(),
+            user_id: Id::new(1),
+            channel_id: Id::new(2),
+            created_at: UNIX_EPOCH,
+            state,
+            message: String::new(),
+            task: None,
+        }
+    }
+
+    #[test]
+    fn running_timer_is_pending() {
+        let end_time = SystemTime::now() + Duration::from_secs(60);
+        assert!(timer(TimerState::Running { end_time }).is_pending());
+    }
+
+    #[test]
+    fn paused_timer_is_pending() {
+        let remaining = Duration::from_secs(60);
+        assert!(timer(TimerState::Paused { remaining }).is_pending());
+    }
+
+    #[test]
+    fn sent_timer_is_not_pending() {
+        let end_time = SystemTime::now() - Duration::from_secs(60);
+        let timer = timer(TimerState::Running { end_time });
+        assert!(timer.is_running());
+        assert!(!timer.is_pending());
+    }
+
+    #[test]
+    fn quote_escapes_block_quotes() {
+        assert_eq!(quote("hi\n> there"), "> hi\n> \\> there");
+    }
+
+    #[test]
+    fn quote_keeps_bold_text() {
+        assert_eq!(quote("**drink water**"), "> **drink water**");
+    }
+
+    #[test]
+    fn quote_keeps_code_fences() {
+        assert_eq!(
+            quote("run this:\n```rs\nlet x = 1;\n```"),
+            "> run this:\n> ```rs\n> let x = 1;\n> ```",
+        );
+    }
+
+    #[test]
+    fn quote_truncates_long_messages() {
+        let ten_lines = (1..=MAX_QUOTED_LINES).map(|i| i.to_string()).collect::<Vec<_>>().join("\n");
+        assert_eq!(quote(&ten_lines).lines().count(), MAX_QUOTED_LINES);
+        assert!(!quote(&ten_lines).ends_with('…'));
+
+        let quoted = quote(&format!("{}\n11\n12", ten_lines));
+        let lines = quoted.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), MAX_QUOTED_LINES + 1);
+        assert_eq!(lines[MAX_QUOTED_LINES - 1], "> 10");
+        assert_eq!(lines[MAX_QUOTED_LINES], "> …");
+    }
+}
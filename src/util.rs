@@ -1,4 +1,33 @@
-use std::{ops::{Add, AddAssign, Deref, Sub, SubAssign}, time::Duration};
+use crate::{
+    commands::{components::{action_row, button}, Trigger},
+    custom_id::{CustomId, CustomIdError},
+    database::Database,
+    error::Error,
+    global::State,
+    tasks::TaskCategory,
+};
+use chrono::FixedOffset;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    future::IntoFuture,
+    ops::{Add, AddAssign, Deref, Sub, SubAssign},
+    str::FromStr,
+    sync::{atomic::{AtomicBool, Ordering}, Arc},
+    time::Duration,
+};
+use tokio::sync::Mutex;
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::message::{
+        component::{ActionRow, Button, ButtonStyle, SelectMenu, SelectMenuOption, SelectMenuType},
+        Component,
+        Embed,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseType},
+    id::{marker::UserMarker, Id},
+};
+use twilight_util::builder::InteractionResponseDataBuilder;
 
 /// A wrapper around [`usize`] that is clamped to a range. When adding or subtracting to this
 /// wrapper, the value will wrap around to the other end of the range.
@@ -74,6 +103,45 @@ pub fn pluralize(count: usize, word: &str) -> String {
     }
 }
 
+/// Parses a command argument that can be wrapped in double quotes to include spaces, like
+/// `"stop watching tv"`. The argument starts with the word `first`, and more words are taken from
+/// `words` until the closing quote is found. Within quotes, `\"` is a literal quote and `\\` is a
+/// literal backslash.
+///
+/// The words are expected to have been split on whitespace, so whitespace inside the quotes is
+/// normalized to single spaces. If `first` does not start with a quote, it is returned as is.
+pub fn parse_quoted<'a>(
+    first: &'a str,
+    words: &mut impl Iterator<Item = &'a str>,
+) -> Result<String, &'static str> {
+    let Some(mut word) = first.strip_prefix('"') else {
+        return Ok(first.to_owned());
+    };
+
+    let mut quoted = String::new();
+    loop {
+        let mut chars = word.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(c @ ('"' | '\\')) => quoted.push(c),
+                    Some(c) => {
+                        quoted.push('\\');
+                        quoted.push(c);
+                    },
+                    None => quoted.push('\\'),
+                },
+                '"' if chars.as_str().is_empty() => return Ok(quoted),
+                '"' => return Err("**There must be a space after a closing quote (`\"`).**"),
+                c => quoted.push(c),
+            }
+        }
+
+        word = words.next().ok_or("**An argument is missing its closing quote (`\"`).**")?;
+        quoted.push(' ');
+    }
+}
+
 /// Formats a time duration as a string. The output will contain one unit of time, and is formatted
 /// as "X y", where X is the amount of time and y is the unit of time.
 pub fn format_duration(duration: Duration) -> String {
@@ -92,3 +160,729 @@ pub fn format_duration(duration: Duration) -> String {
         pluralize(seconds, "second")
     }
 }
+
+/// Formats a time duration as an approximate, human-friendly phrase, such as "under a minute",
+/// "about 5 minutes", or "about 2 and a half hours".
+///
+/// The largest unit of time that the duration spans at least once (after rounding) is chosen, and
+/// the amount is rounded to the nearest half of that unit.
+pub fn format_duration_approx(duration: Duration) -> String {
+    /// The article, name, and length in seconds of each unit, from largest to smallest.
+    const UNITS: [(&str, &str, f64); 6] = [
+        ("a", "year", 31_557_600.0), // 365.25 days
+        ("a", "month", 2_592_000.0), // 30 days
+        ("a", "week", 604_800.0),
+        ("a", "day", 86_400.0),
+        ("an", "hour", 3_600.0),
+        ("a", "minute", 60.0),
+    ];
+
+    let seconds = duration.as_secs_f64();
+    if seconds < 60.0 {
+        return String::from("under a minute");
+    }
+
+    for (article, unit, length) in UNITS {
+        let halves = (seconds / length * 2.0).round() as usize;
+        if halves < 2 {
+            continue;
+        }
+
+        return match (halves / 2, halves % 2 == 1) {
+            (1, false) => format!("about {} {}", article, unit),
+            (1, true) => format!("about {} {} and a half", article, unit),
+            (whole, false) => format!("about {} {}s", whole, unit),
+            (whole, true) => format!("about {} and a half {}s", whole, unit),
+        };
+    }
+
+    unreachable!("durations of at least a minute always fit the minute unit")
+}
+
+/// A calculation run with [`run_with_timeout`] that did not finish.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unfinished {
+    /// The calculation took longer than its time limit.
+    TimedOut,
+
+    /// The calculation panicked.
+    Panicked,
+}
+
+/// A flag given to a calculation run with [`run_with_timeout`], which is set when the calculation
+/// times out.
+///
+/// A blocking thread cannot be stopped from the outside, so long-running calculations should check
+/// [`Cancellation::is_cancelled`] between steps and return early once it is set, instead of
+/// finishing a result that no one will see.
+#[derive(Clone, Debug, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    /// Returns true if the calculation timed out, and its result will be discarded.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Tells the calculation to stop.
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Runs a CPU-bound calculation, like evaluating a CAS expression, on a blocking thread, giving
+/// up on it if it takes longer than the given time limit.
+///
+/// When the time limit is reached, the calculation's [`Cancellation`] is set, so that it can stop
+/// at its next check.
+pub async fn run_with_timeout<T>(
+    limit: Duration,
+    calculation: impl FnOnce(&Cancellation) -> T + Send + 'static,
+) -> Result<T, Unfinished>
+where
+    T: Send + 'static,
+{
+    let cancellation = Cancellation::default();
+    let task = tokio::task::spawn_blocking({
+        let cancellation = cancellation.clone();
+        move || calculation(&cancellation)
+    });
+    match tokio::time::timeout(limit, task).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(err)) => {
+            log::error!("calculation panicked: {}", err);
+            Err(Unfinished::Panicked)
+        },
+        Err(_) => {
+            cancellation.cancel();
+            Err(Unfinished::TimedOut)
+        },
+    }
+}
+
+/// Parses a UTC offset, like `UTC`, `Z`, `+2`, `-05:00`, `+0530`, or `UTC+5:30`.
+pub fn parse_utc_offset(s: &str) -> Option<FixedOffset> {
+    let s = match s.get(..3) {
+        Some(utc) if utc.eq_ignore_ascii_case("utc") => &s[3..],
+        _ => s,
+    };
+    if s.is_empty() || s.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match (s.strip_prefix('+'), s.strip_prefix('-')) {
+        (Some(rest), _) => (1, rest),
+        (_, Some(rest)) => (-1, rest),
+        _ => return None,
+    };
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if rest.len() == 4 && rest.is_char_boundary(2) => rest.split_at(2),
+        None => (rest, "0"),
+    };
+    let hours = hours.parse::<i32>().ok().filter(|hours| (0..=14).contains(hours))?;
+    let minutes = minutes.parse::<i32>().ok().filter(|minutes| (0..60).contains(minutes))?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// How numbers should be displayed in command output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Notation {
+    /// Numbers are displayed however the command would normally display them.
+    #[default]
+    Auto,
+
+    /// Numbers are displayed in scientific notation, like `1.25e4`.
+    Scientific,
+
+    /// Numbers are displayed in engineering notation, where the exponent is always a multiple of
+    /// three, like `12.5e3`.
+    Engineering,
+
+    /// Numbers are displayed in full, without an exponent.
+    Plain,
+}
+
+impl Display for Notation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Notation::Auto => write!(f, "auto"),
+            Notation::Scientific => write!(f, "scientific"),
+            Notation::Engineering => write!(f, "engineering"),
+            Notation::Plain => write!(f, "plain"),
+        }
+    }
+}
+
+impl FromStr for Notation {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" | "a" => Ok(Notation::Auto),
+            "scientific" | "sci" | "s" => Ok(Notation::Scientific),
+            "engineering" | "eng" | "e" => Ok(Notation::Engineering),
+            "plain" | "p" => Ok(Notation::Plain),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A number given as a command argument. In addition to plain numbers, a percentage like `50%` or a
+/// fraction like `3/4` is accepted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Number(pub f64);
+
+impl FromStr for Number {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const INVALID: &str = "**The quantity must be a number, percentage, or fraction, like `2.5`, `50%`, or `3/4`.**";
+
+        let parse = |s: &str| s.trim().parse::<f64>().map_err(|_| INVALID);
+        if let Some(percentage) = s.strip_suffix('%') {
+            return Ok(Number(parse(percentage)? / 100.0));
+        }
+
+        match s.split_once('/') {
+            Some((numerator, denominator)) => {
+                let denominator = parse(denominator)?;
+                if denominator == 0.0 {
+                    return Err("**The fraction divides by zero.**");
+                }
+                Ok(Number(parse(numerator)? / denominator))
+            },
+            None => parse(s).map(Number),
+        }
+    }
+}
+
+/// Inserts a `,` between every group of three digits in the integer part of the given formatted
+/// number, like `1234567.89` -> `1,234,567.89`.
+fn group_digits(number: &str) -> String {
+    let start = number.find(|c: char| c.is_ascii_digit()).unwrap_or(number.len());
+    let end = number[start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(number.len(), |end| start + end);
+    let digits = &number[start..end];
+
+    let mut grouped = String::with_capacity(number.len() + digits.len() / 3);
+    grouped.push_str(&number[..start]);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(&number[end..]);
+    grouped
+}
+
+/// Formats the number in engineering notation, like `12.5e3`.
+fn format_engineering(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+
+    let mut exponent = (value.abs().log10().floor() as i32).div_euclid(3) * 3;
+    let mut mantissa = value / 10f64.powi(exponent);
+
+    // rounding error in the division can push the mantissa to 1000
+    let rounded = format!("{:.12}", mantissa).parse::<f64>().unwrap();
+    if rounded.abs() >= 1000.0 {
+        exponent += 3;
+        mantissa = value / 10f64.powi(exponent);
+    }
+
+    let mantissa = format!("{:.12}", mantissa);
+    let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+    format!("{}e{}", mantissa, exponent)
+}
+
+/// Formats a number according to the given display preferences.
+///
+/// [`Notation::Auto`] uses the default [`Display`] implementation of [`f64`].
+pub fn format_number(value: f64, notation: Notation, group: bool) -> String {
+    let formatted = match notation {
+        Notation::Auto | Notation::Plain => value.to_string(),
+        Notation::Scientific => format!("{:e}", value),
+        Notation::Engineering => format_engineering(value),
+    };
+
+    if group {
+        group_digits(&formatted)
+    } else {
+        formatted
+    }
+}
+
+/// Formats an already-displayed value according to the given display preferences. If the value
+/// is not a plain number (for example, a complex number or a list), it is returned unchanged.
+///
+/// With the default preferences, the value is always returned unchanged.
+pub fn format_displayed(value: &str, notation: Notation, group: bool) -> String {
+    if notation == Notation::Auto && !group {
+        return value.to_owned();
+    }
+
+    match value.trim().parse::<f64>() {
+        Ok(number) => format_number(number, notation, group),
+        Err(_) => value.to_owned(),
+    }
+}
+
+/// The outcome of a confirmation dialog created with [`confirm`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfirmResult {
+    /// The user clicked the confirm button.
+    Confirmed,
+
+    /// The user clicked the cancel button.
+    Cancelled,
+
+    /// The user did not respond before the timeout.
+    TimedOut,
+}
+
+/// Builds the confirm and cancel buttons of a confirmation dialog. The ID of the user allowed to
+/// respond is stored in the custom IDs, so that other users' clicks can be rejected.
+fn confirm_buttons(authorized_user: Id<UserMarker>, disabled: bool) -> Result<Component, CustomIdError> {
+    let user = authorized_user.to_string();
+    let buttons = [
+        button(CustomId::new("confirm", "yes").with_payload(&user), "Confirm", None, ButtonStyle::Danger)?,
+        button(CustomId::new("confirm", "no").with_payload(&user), "Cancel", None, ButtonStyle::Secondary)?,
+    ];
+    Ok(action_row(buttons.map(|button| Button { disabled, ..button })))
+}
+
+/// Replies to the trigger with the given prompt and confirm / cancel buttons, then waits for the
+/// user who triggered the command to click one of the buttons.
+///
+/// Clicks from other users are rejected by [`crate::handler::confirm`]. Once the dialog is
+/// resolved, or the timeout elapses, the buttons are disabled.
+pub async fn confirm(
+    state: &Arc<State>,
+    database: &Arc<Mutex<Database>>,
+    trigger: Trigger<'_>,
+    prompt: &str,
+    timeout: Duration,
+) -> Result<ConfirmResult, Box<dyn Error + Send + Sync>> {
+    let authorized_user = trigger.author_id();
+    let message = trigger.unattributed().reply(&state.http)
+        .content(prompt)?
+        .components(&[confirm_buttons(authorized_user, false)?])?
+        .await?
+        .model()
+        .await?;
+    let receiver = database.lock().await.set_confirmation(message.channel_id, message.id);
+    let disabled = confirm_buttons(authorized_user, true)?;
+
+    let interaction = match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(interaction)) => interaction,
+        _ => {
+            database.lock().await.take_confirmation(message.channel_id, message.id);
+            trigger.sent_reply(message.id)
+                .edit_components(&state.http, &[disabled])
+                .await?;
+            return Ok(ConfirmResult::TimedOut);
+        },
+    };
+
+    let result = match &interaction.data {
+        Some(InteractionData::MessageComponent(data)) => match data.custom_id.parse::<CustomId>() {
+            Ok(custom_id) if custom_id.action == "yes" => ConfirmResult::Confirmed,
+            _ => ConfirmResult::Cancelled,
+        },
+        _ => ConfirmResult::Cancelled,
+    };
+
+    state.http.interaction(state.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::UpdateMessage,
+                data: Some(InteractionResponseDataBuilder::new()
+                    .components(Some(disabled))
+                    .build()),
+            },
+        )
+        .await?;
+
+    Ok(result)
+}
+
+/// The maximum number of pages that can be jumped to with the page select menu of a paged message,
+/// as Discord allows at most 25 options in a select menu.
+const MAX_JUMP_PAGES: usize = 25;
+
+/// Builds the components of a paged message with the given number of pages, showing the page at
+/// the given index: buttons to move between pages and delete the message, and a select menu to
+/// jump to any page if there are more than two pages, but few enough to fit in the menu.
+///
+/// The buttons that move backward are disabled on the first page, and the buttons that move
+/// forward are disabled on the last page.
+fn pager_components(pages: usize, index: usize) -> Result<Vec<Component>, CustomIdError> {
+    let at_first = index == 0;
+    let at_last = index + 1 >= pages;
+    let mut components = vec![action_row([
+        Button {
+            disabled: at_first,
+            ..button(CustomId::new("pager", "first"), "First", Some("⏮️"), ButtonStyle::Secondary)?
+        },
+        Button {
+            disabled: at_first,
+            ..button(CustomId::new("pager", "prev"), "Previous", Some("◀️"), ButtonStyle::Primary)?
+        },
+        Button {
+            disabled: at_last,
+            ..button(CustomId::new("pager", "next"), "Next", Some("▶️"), ButtonStyle::Primary)?
+        },
+        Button {
+            disabled: at_last,
+            ..button(CustomId::new("pager", "last"), "Last", Some("⏭️"), ButtonStyle::Secondary)?
+        },
+        button(CustomId::new("pager", "delete"), "Delete", Some("🗑️"), ButtonStyle::Danger)?,
+    ])];
+
+    if (3..=MAX_JUMP_PAGES).contains(&pages) {
+        components.push(Component::ActionRow(ActionRow {
+            components: vec![Component::SelectMenu(SelectMenu {
+                channel_types: None,
+                custom_id: CustomId::new("pager", "page").encode()?,
+                disabled: false,
+                kind: SelectMenuType::Text,
+                max_values: Some(1),
+                min_values: Some(1),
+                options: Some((0..pages)
+                    .map(|page| SelectMenuOption {
+                        default: page == index,
+                        description: None,
+                        emoji: None,
+                        label: format!("Page {}", page + 1),
+                        value: page.to_string(),
+                    })
+                    .collect()),
+                placeholder: Some(String::from("Jump to page")),
+            })],
+        }));
+    }
+
+    Ok(components)
+}
+
+/// Acknowledges a component interaction on a paged message without changing the message, so that
+/// Discord does not show the interaction as failed.
+async fn defer_update(state: &State, interaction: &Interaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+    state.http.interaction(state.application_id)
+        .create_response(
+            interaction.id,
+            &interaction.token,
+            &InteractionResponse {
+                kind: InteractionResponseType::DeferredUpdateMessage,
+                data: None,
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Replies to the trigger with a message that has multiple pages split as embeds. A task is
+/// spawned to listen for button clicks and page selections, and update the message accordingly.
+///
+/// The pages are not attributed, since only the first page would be.
+pub fn send_paged_message(
+    state: &Arc<State>,
+    database: &Arc<Mutex<Database>>,
+    trigger: Trigger<'_>,
+    pages: &[Embed],
+    index: usize,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // validate before sending
+    let components = pager_components(pages.len(), index)?;
+    let pages = pages.to_vec();
+    let msg = trigger.unattributed().reply(&state.http)
+        .embeds(&[pages[index].clone()])?
+        .components(&components)?
+        .into_future();
+
+    let tasks = &state.tasks;
+    let state = Arc::clone(state);
+    let database = Arc::clone(database);
+    tasks.spawn(TaskCategory::Pager, async move {
+        let mut clamped = Clamped::new(index, pages.len());
+        let message = msg.await?.model().await?;
+        let channel_id = message.channel_id;
+        let mut receiver = database.lock().await.set_paged_message(channel_id, message.id);
+
+        // if the message is deleted by anyone, including through the delete button, the
+        // message delete event removes the sender, which closes the receiver and ends the loop
+        let mut reason = "message deleted";
+        while let Some(mut interaction) = receiver.recv().await {
+            let Some(InteractionData::MessageComponent(component_interaction)) = interaction.data.take() else {
+                defer_update(&state, &interaction).await?;
+                continue;
+            };
+            let Ok(custom_id) = component_interaction.custom_id.parse::<CustomId>() else {
+                log::warn!("malformed pager custom id: {}", component_interaction.custom_id);
+                defer_update(&state, &interaction).await?;
+                continue;
+            };
+            match custom_id.action.as_str() {
+                "first" => clamped = Clamped::new(0, clamped.max()),
+                "prev" => clamped -= 1,
+                "next" => clamped += 1,
+                "last" => clamped = Clamped::new(clamped.max() - 1, clamped.max()),
+                "page" => match component_interaction.values.first().and_then(|page| page.parse().ok()) {
+                    Some(page) => clamped = Clamped::new(page, clamped.max()),
+                    None => {
+                        defer_update(&state, &interaction).await?;
+                        continue;
+                    },
+                },
+                "delete" => {
+                    database.lock().await.remove_paged_message(channel_id, message.id);
+                    state.http.delete_message(channel_id, message.id).await?;
+                    reason = "delete button clicked";
+                    break;
+                },
+                _ => {
+                    log::warn!("unknown pager action: {}", custom_id.action);
+                    defer_update(&state, &interaction).await?;
+                    continue;
+                },
+            }
+            let new_embed = pages[*clamped].clone();
+            let components = pager_components(pages.len(), *clamped)?;
+            state.http.interaction(state.application_id)
+                .create_response(
+                    interaction.id,
+                    &interaction.token,
+                    &InteractionResponse {
+                        kind: InteractionResponseType::UpdateMessage,
+                        data: Some(InteractionResponseDataBuilder::new()
+                            .components(components)
+                            .embeds(vec![new_embed])
+                            .build()),
+                    },
+                )
+                .await?;
+        }
+
+        log::info!("paged message task ended: {}", reason);
+
+        Ok::<(), Box<dyn Error + Send + Sync>>(())
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the offset of the given number of minutes east of UTC.
+    fn minutes(minutes: i32) -> Option<FixedOffset> {
+        FixedOffset::east_opt(minutes * 60)
+    }
+
+    /// Parses the first argument of the input with [`parse_quoted`], returning the result and the
+    /// words left over.
+    fn quoted(input: &str) -> (Result<String, &'static str>, Vec<&str>) {
+        let mut words = input.split_whitespace();
+        let first = words.next().unwrap();
+        (parse_quoted(first, &mut words), words.collect())
+    }
+
+    #[test]
+    fn unquoted_word() {
+        assert_eq!(quoted("tv later"), (Ok("tv".to_owned()), vec!["later"]));
+    }
+
+    #[test]
+    fn quoted_words() {
+        assert_eq!(quoted(r#""stop  watching tv" later"#), (Ok("stop watching tv".to_owned()), vec!["later"]));
+        assert_eq!(quoted(r#""tv""#), (Ok("tv".to_owned()), vec![]));
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        assert_eq!(quoted(r#""say \"hi\" \\ now""#).0, Ok(r#"say "hi" \ now"#.to_owned()));
+        assert_eq!(quoted(r#""a\b""#).0, Ok(r"a\b".to_owned()));
+    }
+
+    #[test]
+    fn unterminated_quotes() {
+        assert!(quoted(r#""stop watching"#).0.is_err());
+        assert!(quoted(r#""stop watching\""#).0.is_err());
+        assert!(quoted(r#""stop"watching"#).0.is_err());
+    }
+
+    #[test]
+    fn approximate_short_durations() {
+        assert_eq!(format_duration_approx(Duration::from_secs(30)), "under a minute");
+        assert_eq!(format_duration_approx(Duration::from_secs(60)), "about a minute");
+        assert_eq!(format_duration_approx(Duration::from_secs(90)), "about a minute and a half");
+        assert_eq!(format_duration_approx(Duration::from_secs(45 * 60)), "about an hour");
+    }
+
+    #[test]
+    fn approximate_long_durations() {
+        assert_eq!(format_duration_approx(Duration::from_secs(9_000)), "about 2 and a half hours");
+        assert_eq!(format_duration_approx(Duration::from_secs(3 * 86_400)), "about 3 days");
+        assert_eq!(format_duration_approx(Duration::from_secs(10 * 86_400)), "about a week and a half");
+        assert_eq!(format_duration_approx(Duration::from_secs(2 * 31_557_600)), "about 2 years");
+    }
+
+    #[test]
+    fn parse_utc() {
+        assert_eq!(parse_utc_offset("UTC"), minutes(0));
+        assert_eq!(parse_utc_offset("utc"), minutes(0));
+        assert_eq!(parse_utc_offset("Z"), minutes(0));
+    }
+
+    #[test]
+    fn parse_hour_offsets() {
+        assert_eq!(parse_utc_offset("+2"), minutes(120));
+        assert_eq!(parse_utc_offset("-5"), minutes(-300));
+        assert_eq!(parse_utc_offset("UTC+14"), minutes(840));
+    }
+
+    #[test]
+    fn parse_minute_offsets() {
+        assert_eq!(parse_utc_offset("-05:00"), minutes(-300));
+        assert_eq!(parse_utc_offset("+0530"), minutes(330));
+        assert_eq!(parse_utc_offset("UTC+5:30"), minutes(330));
+        assert_eq!(parse_utc_offset("utc-09:30"), minutes(-570));
+    }
+
+    #[test]
+    fn parse_invalid_offsets() {
+        assert_eq!(parse_utc_offset("5"), None);
+        assert_eq!(parse_utc_offset("+15"), None);
+        assert_eq!(parse_utc_offset("+5:60"), None);
+        assert_eq!(parse_utc_offset("+"), None);
+        assert_eq!(parse_utc_offset("EST"), None);
+        assert_eq!(parse_utc_offset("+é5"), None);
+    }
+
+    #[test]
+    fn parse_plain_numbers() {
+        assert_eq!("2.5".parse::<Number>(), Ok(Number(2.5)));
+        assert_eq!("-3".parse::<Number>(), Ok(Number(-3.0)));
+    }
+
+    #[test]
+    fn parse_percentages_and_fractions() {
+        assert_eq!("50%".parse::<Number>(), Ok(Number(0.5)));
+        assert_eq!("3/4".parse::<Number>(), Ok(Number(0.75)));
+        assert_eq!("-1/ 8".parse::<Number>(), Ok(Number(-0.125)));
+    }
+
+    #[test]
+    fn parse_invalid_numbers() {
+        assert_eq!("1/0".parse::<Number>(), Err("**The fraction divides by zero.**"));
+        assert!("%".parse::<Number>().is_err());
+        assert!("1/2/3".parse::<Number>().is_err());
+        assert!("ten".parse::<Number>().is_err());
+    }
+
+    #[test]
+    fn format_in_notations() {
+        assert_eq!(format_number(12500.0, Notation::Auto, false), "12500");
+        assert_eq!(format_number(12500.0, Notation::Scientific, false), "1.25e4");
+        assert_eq!(format_number(12500.0, Notation::Engineering, false), "12.5e3");
+        assert_eq!(format_number(0.00125, Notation::Engineering, false), "1.25e-3");
+        assert_eq!(format_number(0.0, Notation::Engineering, false), "0");
+    }
+
+    #[test]
+    fn group_integer_digits() {
+        assert_eq!(format_number(-1234567.89, Notation::Auto, true), "-1,234,567.89");
+        assert_eq!(format_number(123.0, Notation::Auto, true), "123");
+        assert_eq!(format_number(1000.0, Notation::Auto, true), "1,000");
+    }
+
+    #[test]
+    fn format_displayed_values() {
+        assert_eq!(format_displayed("12500", Notation::Auto, false), "12500");
+        assert_eq!(format_displayed("12500", Notation::Auto, true), "12,500");
+        assert_eq!(format_displayed("3 + 4i", Notation::Scientific, true), "3 + 4i");
+    }
+
+    /// Returns whether each button of the pager is disabled, in order.
+    fn disabled_buttons(components: &[Component]) -> Vec<bool> {
+        let Component::ActionRow(row) = &components[0] else {
+            panic!("expected an action row");
+        };
+        row.components.iter()
+            .map(|component| match component {
+                Component::Button(button) => button.disabled,
+                _ => panic!("expected a button"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pager_buttons_at_ends_are_disabled() {
+        assert_eq!(disabled_buttons(&pager_components(3, 0).unwrap()), [true, true, false, false, false]);
+        assert_eq!(disabled_buttons(&pager_components(3, 1).unwrap()), [false; 5]);
+        assert_eq!(disabled_buttons(&pager_components(3, 2).unwrap()), [false, false, true, true, false]);
+    }
+
+    #[test]
+    fn single_page_disables_navigation() {
+        assert_eq!(disabled_buttons(&pager_components(1, 0).unwrap()), [true, true, true, true, false]);
+    }
+
+    #[test]
+    fn jump_menu_needs_three_pages() {
+        assert_eq!(pager_components(2, 0).unwrap().len(), 1);
+        assert_eq!(pager_components(MAX_JUMP_PAGES + 1, 0).unwrap().len(), 1);
+
+        let components = pager_components(3, 1).unwrap();
+        let Component::ActionRow(row) = &components[1] else {
+            panic!("expected an action row");
+        };
+        let Component::SelectMenu(menu) = &row.components[0] else {
+            panic!("expected a select menu");
+        };
+        let options = menu.options.as_ref().unwrap();
+        assert_eq!(options.iter().map(|option| option.value.as_str()).collect::<Vec<_>>(), ["0", "1", "2"]);
+        assert!(options[1].default);
+    }
+
+    /// Returns the buttons of the given action row.
+    fn row_buttons(component: &Component) -> Vec<&Button> {
+        let Component::ActionRow(row) = component else {
+            panic!("expected an action row");
+        };
+        row.components.iter()
+            .map(|component| match component {
+                Component::Button(button) => button,
+                _ => panic!("expected a button"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn confirm_buttons_store_the_authorized_user() {
+        let component = confirm_buttons(Id::new(42), false).unwrap();
+        let buttons = row_buttons(&component);
+        let custom_ids = buttons.iter()
+            .map(|button| button.custom_id.as_deref().unwrap().parse::<CustomId>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(custom_ids, [
+            CustomId::new("confirm", "yes").with_payload("42"),
+            CustomId::new("confirm", "no").with_payload("42"),
+        ]);
+        assert_eq!(buttons.iter().map(|button| button.style).collect::<Vec<_>>(), [ButtonStyle::Danger, ButtonStyle::Secondary]);
+        assert!(buttons.iter().all(|button| !button.disabled));
+    }
+
+    #[test]
+    fn disabled_confirm_buttons() {
+        let component = confirm_buttons(Id::new(42), true).unwrap();
+        assert!(row_buttons(&component).iter().all(|button| button.disabled));
+    }
+}